@@ -4,7 +4,11 @@ use reth_primitives::{
     ChainSpec, SnapshotSegment,
 };
 use reth_provider::{DatabaseProviderRO, ProviderFactory};
-use std::{fmt::Debug, sync::Arc, time::Instant};
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 #[derive(Debug)]
 pub(crate) enum BenchKind {
@@ -12,6 +16,7 @@ pub(crate) enum BenchKind {
     RandomAll,
     RandomOne,
     RandomHash,
+    RandomHashes,
 }
 
 pub(crate) fn bench<F1, F2, R>(
@@ -55,3 +60,115 @@ where
 
     Ok(())
 }
+
+/// Like [`bench`], but times every lookup over `inputs` individually and reports the p50, p95 and
+/// p99 latencies for both the snapshot and the database path.
+pub(crate) fn bench_latencies<I, F1, F2, R>(
+    bench_kind: BenchKind,
+    db: (DatabaseEnv, Arc<ChainSpec>),
+    segment: SnapshotSegment,
+    filters: Filters,
+    compression: Compression,
+    inputs: &[I],
+    mut snapshot_method: F1,
+    database_method: F2,
+) -> eyre::Result<()>
+where
+    F1: FnMut(&I) -> eyre::Result<R>,
+    F2: Fn(&DatabaseProviderRO<DatabaseEnv>, &I) -> eyre::Result<R>,
+    R: Debug + PartialEq,
+{
+    let (db, chain) = db;
+
+    println!();
+    println!("############");
+    println!("## [{segment:?}] [{compression:?}] [{filters:?}] [{bench_kind:?}]");
+
+    let mut snap_latencies = Vec::with_capacity(inputs.len());
+    let mut snap_results = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let start = Instant::now();
+        snap_results.push(snapshot_method(input)?);
+        snap_latencies.push(start.elapsed());
+    }
+    print_percentiles("snapshot", &bench_kind, &mut snap_latencies);
+
+    let factory = ProviderFactory::new(db, chain);
+    let provider = factory.provider()?;
+    let mut db_latencies = Vec::with_capacity(inputs.len());
+    let mut db_results = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let start = Instant::now();
+        db_results.push(database_method(&provider, input)?);
+        db_latencies.push(start.elapsed());
+    }
+    print_percentiles("database", &bench_kind, &mut db_latencies);
+
+    assert_eq!(snap_results, db_results);
+
+    Ok(())
+}
+
+fn print_percentiles(source: &str, bench_kind: &BenchKind, latencies: &mut [Duration]) {
+    if let Some(Percentiles { p50, p95, p99 }) = percentiles(latencies) {
+        println!(
+            "# {source} {bench_kind:?} | p50 {} μs | p95 {} μs | p99 {} μs",
+            p50.as_micros(),
+            p95.as_micros(),
+            p99.as_micros()
+        );
+    }
+}
+
+/// Latency percentiles of a benchmark run.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Percentiles {
+    pub(crate) p50: Duration,
+    pub(crate) p95: Duration,
+    pub(crate) p99: Duration,
+}
+
+/// Sorts `latencies` and returns their nearest-rank p50, p95 and p99, or `None` if empty.
+pub(crate) fn percentiles(latencies: &mut [Duration]) -> Option<Percentiles> {
+    if latencies.is_empty() {
+        return None
+    }
+    latencies.sort_unstable();
+
+    let rank = |percentile: usize| {
+        let index = (percentile * latencies.len()).div_ceil(100).max(1) - 1;
+        latencies[index]
+    };
+
+    Some(Percentiles { p50: rank(50), p95: rank(95), p99: rank(99) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_percentiles() {
+        assert_eq!(percentiles(&mut []), None);
+
+        let mut latencies = (1..=100).rev().map(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(
+            percentiles(&mut latencies),
+            Some(Percentiles {
+                p50: Duration::from_millis(50),
+                p95: Duration::from_millis(95),
+                p99: Duration::from_millis(99),
+            })
+        );
+
+        let mut latencies = vec![Duration::from_micros(7)];
+        assert_eq!(
+            percentiles(&mut latencies),
+            Some(Percentiles {
+                p50: Duration::from_micros(7),
+                p95: Duration::from_micros(7),
+                p99: Duration::from_micros(7),
+            })
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use reth_nippy_jar::NippyJar;
+use reth_primitives::{
+    snapshot::{Compression, Filters, SegmentHeader},
+    BlockNumber, SnapshotSegment,
+};
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Parameters of a generation run that determine its block ranges and the contents of its
+/// snapshots. A checkpoint is only used to resume a run with the same parameters.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GenerationParams {
+    /// First block of the first range.
+    pub(crate) from: BlockNumber,
+    /// Number of blocks of every range.
+    pub(crate) block_interval: u64,
+    /// Compression of the snapshots.
+    pub(crate) compression: Compression,
+    /// Filters of the snapshots.
+    pub(crate) filters: Filters,
+}
+
+impl fmt::Display for GenerationParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filters = match self.filters {
+            Filters::WithFilters(inclusion_filter, phf) => {
+                format!("{}-{}", inclusion_filter.as_ref(), phf.as_ref())
+            }
+            Filters::WithoutFilters => "none".to_string(),
+        };
+        write!(
+            f,
+            "from={} interval={} compression={} filters={filters}",
+            self.from,
+            self.block_interval,
+            self.compression.as_ref()
+        )
+    }
+}
+
+/// Returns the path of the generation checkpoint of `segment` inside `dir`.
+pub(crate) fn checkpoint_path(dir: &Path, segment: SnapshotSegment) -> PathBuf {
+    dir.join(format!("snapshot_{}.checkpoint", segment.as_ref()))
+}
+
+/// Records `block` as the last block of the last fully completed range of `segment`, generated
+/// with the given parameters.
+///
+/// The checkpoint is written to a temporary file first and then renamed, so an interrupted write
+/// never leaves a corrupted checkpoint behind.
+pub(crate) fn write_checkpoint(
+    dir: &Path,
+    segment: SnapshotSegment,
+    params: GenerationParams,
+    block: BlockNumber,
+) -> eyre::Result<()> {
+    let path = checkpoint_path(dir, segment);
+    let tmp_path = path.with_extension("checkpoint.tmp");
+    fs::write(&tmp_path, format!("{params}\n{block}"))?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+/// Returns the last completed block recorded in the checkpoint of `segment`, if there is one and
+/// it was written by a run with the given parameters.
+pub(crate) fn read_checkpoint(
+    dir: &Path,
+    segment: SnapshotSegment,
+    params: GenerationParams,
+) -> eyre::Result<Option<BlockNumber>> {
+    let path = checkpoint_path(dir, segment);
+    if !path.exists() {
+        return Ok(None)
+    }
+
+    let checkpoint = fs::read_to_string(path)?;
+    match checkpoint.trim().split_once('\n') {
+        Some((checkpoint_params, block)) if checkpoint_params == params.to_string() => {
+            Ok(Some(block.parse()?))
+        }
+        _ => {
+            info!(
+                target: "reth::cli",
+                ?segment,
+                "Ignoring snapshot checkpoint of a run with different parameters"
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Returns the last block of the longest prefix of `ranges` which already have a loadable snapshot
+/// file of `segment` in `dir`.
+fn completed_by_files(
+    dir: &Path,
+    segment: SnapshotSegment,
+    ranges: &[RangeInclusive<BlockNumber>],
+) -> eyre::Result<Option<BlockNumber>> {
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+
+    let mut existing = HashSet::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some((file_segment, block_range, _)) =
+            path.file_name().and_then(SnapshotSegment::parse_filename)
+        else {
+            continue
+        };
+
+        if file_segment == segment && NippyJar::<SegmentHeader>::load(&path).is_ok() {
+            existing.insert(block_range);
+        }
+    }
+
+    Ok(ranges.iter().take_while(|range| existing.contains(*range)).last().map(|range| *range.end()))
+}
+
+/// Removes the ranges that were already completed by a previous generation run of `segment`.
+///
+/// The checkpoint is used if present and written with the same parameters, otherwise falls back to
+/// scanning `dir` for existing snapshot files of the same ranges.
+pub(crate) fn pending_ranges(
+    dir: &Path,
+    segment: SnapshotSegment,
+    params: GenerationParams,
+    ranges: Vec<RangeInclusive<BlockNumber>>,
+) -> eyre::Result<Vec<RangeInclusive<BlockNumber>>> {
+    let completed = match read_checkpoint(dir, segment, params)? {
+        Some(block) => Some(block),
+        None => completed_by_files(dir, segment, &ranges)?,
+    };
+
+    let Some(completed) = completed else { return Ok(ranges) };
+    info!(target: "reth::cli", ?segment, completed, "Resuming snapshot generation");
+    Ok(ranges.into_iter().filter(|range| *range.start() > completed).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges() -> Vec<RangeInclusive<BlockNumber>> {
+        vec![0..=9, 10..=19, 20..=29]
+    }
+
+    fn params() -> GenerationParams {
+        GenerationParams {
+            from: 0,
+            block_interval: 10,
+            compression: Compression::Uncompressed,
+            filters: Filters::WithoutFilters,
+        }
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = SnapshotSegment::Transactions;
+        let pending = |segment| pending_ranges(dir.path(), segment, params(), ranges()).unwrap();
+
+        // nothing was generated yet
+        assert_eq!(read_checkpoint(dir.path(), segment, params()).unwrap(), None);
+        assert_eq!(pending(segment), ranges());
+
+        // the run is interrupted after completing the first two ranges
+        for range in &ranges()[..2] {
+            write_checkpoint(dir.path(), segment, params(), *range.end()).unwrap();
+        }
+        assert_eq!(read_checkpoint(dir.path(), segment, params()).unwrap(), Some(19));
+        assert_eq!(pending(segment), vec![20..=29]);
+
+        // checkpoints are kept per segment
+        assert_eq!(pending(SnapshotSegment::Headers), ranges());
+
+        write_checkpoint(dir.path(), segment, params(), 29).unwrap();
+        assert!(pending(segment).is_empty());
+    }
+
+    #[test]
+    fn resume_ignores_checkpoint_of_other_parameters() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = SnapshotSegment::Transactions;
+        write_checkpoint(dir.path(), segment, params(), 19).unwrap();
+
+        // the interval changed between the runs, so none of the new ranges were generated
+        let other = GenerationParams { block_interval: 5, ..params() };
+        let other_ranges = vec![0..=4, 5..=9, 10..=14, 15..=19, 20..=24, 25..=29];
+        assert_eq!(read_checkpoint(dir.path(), segment, other).unwrap(), None);
+        assert_eq!(
+            pending_ranges(dir.path(), segment, other, other_ranges.clone()).unwrap(),
+            other_ranges
+        );
+
+        // the same applies to different contents of the same ranges
+        let other = GenerationParams { compression: Compression::Lz4, ..params() };
+        assert_eq!(read_checkpoint(dir.path(), segment, other).unwrap(), None);
+        assert_eq!(pending_ranges(dir.path(), segment, other, ranges()).unwrap(), ranges());
+
+        // the checkpoint is still used by a run with the original parameters
+        assert_eq!(pending_ranges(dir.path(), segment, params(), ranges()).unwrap(), vec![20..=29]);
+    }
+
+    #[test]
+    fn resume_falls_back_to_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = SnapshotSegment::Transactions;
+        let pending = || pending_ranges(dir.path(), segment, params(), ranges()).unwrap();
+
+        // a file without a jar configuration is not a completed snapshot
+        fs::write(dir.path().join(segment.filename(&(10..=19), &(5..=9))), b"").unwrap();
+        assert_eq!(pending(), ranges());
+
+        let path = dir.path().join(segment.filename(&(0..=9), &(0..=4)));
+        let mut jar = NippyJar::new(1, &path, SegmentHeader::new(0..=9, 0..=4, segment));
+        jar.freeze(vec![(0..5u8).map(|row| Ok(vec![row]))], 5).unwrap();
+        assert_eq!(pending(), vec![10..=19, 20..=29]);
+
+        // the checkpoint takes precedence over the files
+        write_checkpoint(dir.path(), segment, params(), 19).unwrap();
+        assert_eq!(pending(), vec![20..=29]);
+    }
+}
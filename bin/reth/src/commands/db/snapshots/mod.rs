@@ -7,22 +7,28 @@ use reth_db::{
     mdbx::{DatabaseArguments, MaxReadTransactionDuration},
     open_db_read_only, DatabaseEnv,
 };
-use reth_interfaces::db::LogLevel;
+use reth_interfaces::{db::LogLevel, provider::ProviderResult};
 use reth_nippy_jar::{NippyJar, NippyJarCursor};
 use reth_primitives::{
     snapshot::{Compression, Filters, InclusionFilter, PerfectHashingFunction, SegmentHeader},
-    BlockNumber, ChainSpec, SnapshotSegment,
+    BlockNumber, ChainSpec, PruneSegment, SnapshotSegment,
 };
-use reth_provider::{BlockNumReader, ProviderFactory, TransactionsProviderExt};
-use reth_snapshot::{segments as snap_segments, segments::Segment};
+use reth_provider::{
+    BlockNumReader, DatabaseProviderRO, ProviderFactory, PruneCheckpointReader,
+    TransactionsProviderExt,
+};
+use reth_snapshot::{segments as snap_segments, segments::Segment, tools::verify_row_crcs};
 use std::{
+    fs,
     ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
+use tracing::warn;
 
 mod bench;
+mod checkpoint;
 mod headers;
 mod receipts;
 mod transactions;
@@ -54,6 +60,46 @@ pub struct Command {
     #[arg(long, default_value = "false")]
     only_stats: bool,
 
+    /// Flag to resume an interrupted run, skipping the block ranges it has already completed.
+    ///
+    /// Runs with this flag record their progress in a checkpoint in the current directory, which
+    /// is only used by later runs with the same `--from`, `--block-interval`, compression and
+    /// filters. Otherwise, the completed ranges are found by scanning for existing snapshots.
+    #[arg(long, default_value = "false")]
+    resume: bool,
+
+    /// Flag to generate transaction snapshots in portable mode, which records in the segment
+    /// header that all numeric metadata is little-endian.
+    #[arg(long, default_value = "false")]
+    portable: bool,
+
+    /// Flag to compute a Merkle root over the transaction rows and store it in the header of the
+    /// transaction snapshots.
+    #[arg(long, default_value = "false")]
+    with_root: bool,
+
+    /// Flag to store a CRC32 of every row in the transaction snapshots, so silent corruption can
+    /// be detected with `--verify-crc`.
+    #[arg(long, default_value = "false")]
+    with_crc: bool,
+
+    /// Upper bound in bytes on the row offsets buffered in memory while writing transaction
+    /// snapshots. They are flushed to disk more often to stay under it, trading speed for a lower
+    /// memory footprint. Rows are streamed from the database either way, but the transaction
+    /// hashes of snapshots with filters are still held in memory to build them.
+    #[arg(long)]
+    max_memory: Option<usize>,
+
+    /// Flag to skip creating transaction snapshots for block ranges without any transactions,
+    /// instead of creating snapshots without rows.
+    #[arg(long, default_value = "false")]
+    skip_empty: bool,
+
+    /// Flag to verify the row checksums of the snapshots, failing on the first corrupted row.
+    /// Snapshots generated without `--with-crc` are not verified.
+    #[arg(long, default_value = "false")]
+    verify_crc: bool,
+
     /// Flag to enable database-to-snapshot benchmarking.
     #[arg(long, default_value = "false")]
     bench: bool,
@@ -70,8 +116,20 @@ pub struct Command {
     #[arg(long, default_value = "false")]
     with_filters: bool,
 
+    /// Flag to generate transaction snapshots both with and without filters from a single read
+    /// of every block range, into the `filtered` and `unfiltered` directories respectively.
+    #[arg(long, default_value = "false")]
+    both_filter_variants: bool,
+
     /// Specifies the perfect hashing function to use.
-    #[arg(long, value_delimiter = ',', default_value_if("with_filters", "true", "fmph"))]
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value_ifs([
+            ("with_filters", "true", "fmph"),
+            ("both_filter_variants", "true", "fmph"),
+        ])
+    )]
     phf: Vec<PerfectHashingFunction>,
 }
 
@@ -112,14 +170,37 @@ impl Command {
                         SnapshotSegment::Headers => self.generate_snapshot::<DatabaseEnv>(
                             factory.clone(),
                             snap_segments::Headers::new(*compression, filters),
+                            *compression,
+                            filters,
                         )?,
-                        SnapshotSegment::Transactions => self.generate_snapshot::<DatabaseEnv>(
-                            factory.clone(),
-                            snap_segments::Transactions::new(*compression, filters),
-                        )?,
+                        SnapshotSegment::Transactions => {
+                            let segment = snap_segments::Transactions::new(*compression, filters)
+                                .with_portable(self.portable)
+                                .with_root(self.with_root)
+                                .with_crc(self.with_crc)
+                                .with_max_memory(self.max_memory)
+                                .with_skip_empty(self.skip_empty);
+                            if self.both_filter_variants {
+                                self.generate_transaction_filter_variants::<DatabaseEnv>(
+                                    factory.clone(),
+                                    segment,
+                                    *compression,
+                                    phf.unwrap_or(PerfectHashingFunction::Fmph),
+                                )?
+                            } else {
+                                self.generate_snapshot::<DatabaseEnv>(
+                                    factory.clone(),
+                                    segment,
+                                    *compression,
+                                    filters,
+                                )?
+                            }
+                        }
                         SnapshotSegment::Receipts => self.generate_snapshot::<DatabaseEnv>(
                             factory.clone(),
                             snap_segments::Receipts::new(*compression, filters),
+                            *compression,
+                            filters,
                         )?,
                     }
                 }
@@ -160,9 +241,12 @@ impl Command {
         Ok(())
     }
 
-    /// Generates successive inclusive block ranges up to the tip starting at `self.from`.
-    fn block_ranges(&self, tip: BlockNumber) -> Vec<RangeInclusive<BlockNumber>> {
-        let mut from = self.from;
+    /// Generates successive inclusive block ranges up to the tip starting at `from`.
+    fn block_ranges(
+        &self,
+        mut from: BlockNumber,
+        tip: BlockNumber,
+    ) -> Vec<RangeInclusive<BlockNumber>> {
         let mut ranges = Vec::new();
 
         while from <= tip {
@@ -176,13 +260,114 @@ impl Command {
 
     /// Generates snapshots from `self.from` with a `self.block_interval`. Generates them in
     /// parallel if specified.
+    ///
+    /// On pruned nodes, `self.from` is clamped to the lowest block with available data.
+    ///
+    /// With `--resume`, a checkpoint of the generation parameters is written after every completed
+    /// batch of ranges, which allows a later `--resume` run with the same parameters to skip them
+    /// when the run is interrupted.
     fn generate_snapshot<DB: Database>(
         &self,
         factory: Arc<ProviderFactory<DB>>,
         segment: impl Segment + Send + Sync,
+        compression: Compression,
+        filters: Filters,
     ) -> eyre::Result<()> {
         let dir = PathBuf::default();
-        let ranges = self.block_ranges(factory.best_block_number()?);
+        self.generate_ranges(
+            &factory,
+            segment.segment(),
+            &dir,
+            compression,
+            filters,
+            |provider, block_range| {
+                if !self.only_stats {
+                    segment.snapshot::<DB>(provider, &dir, block_range.clone())?;
+                }
+
+                let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+                Ok(vec![dir.join(segment.segment().filename(block_range, &tx_range))])
+            },
+        )
+    }
+
+    /// Generates transaction snapshots with and without filters from `self.from` with a
+    /// `self.block_interval`, reading every block range only once, see
+    /// [`snap_segments::Transactions::snapshot_filter_variants`].
+    ///
+    /// The filtered snapshots are written into the `filtered` directory, and the unfiltered ones
+    /// into the `unfiltered` directory, since they share their file names. The filtered snapshot
+    /// of a range is written last, so the `filtered` directory holds the checkpoint of
+    /// `--resume`.
+    fn generate_transaction_filter_variants<DB: Database>(
+        &self,
+        factory: Arc<ProviderFactory<DB>>,
+        segment: snap_segments::Transactions,
+        compression: Compression,
+        phf: PerfectHashingFunction,
+    ) -> eyre::Result<()> {
+        let filtered_dir = PathBuf::from("filtered");
+        let unfiltered_dir = PathBuf::from("unfiltered");
+        fs::create_dir_all(&filtered_dir)?;
+        fs::create_dir_all(&unfiltered_dir)?;
+
+        self.generate_ranges(
+            &factory,
+            segment.segment(),
+            &filtered_dir,
+            compression,
+            Filters::WithFilters(InclusionFilter::Cuckoo, phf),
+            |provider, block_range| {
+                if !self.only_stats {
+                    segment.snapshot_filter_variants::<DB>(
+                        provider,
+                        &filtered_dir,
+                        &unfiltered_dir,
+                        block_range.clone(),
+                        InclusionFilter::Cuckoo,
+                        phf,
+                    )?;
+                }
+
+                let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+                let filename = segment.segment().filename(block_range, &tx_range);
+                Ok(vec![filtered_dir.join(&filename), unfiltered_dir.join(filename)])
+            },
+        )
+    }
+
+    /// Calls `write` for every block range of `segment` that is generated by this run, in batches
+    /// of `self.parallel` ranges, and prints the stats of all snapshots it wrote. `write` returns
+    /// the paths of the snapshots of the range.
+    ///
+    /// With `--resume`, the ranges completed by a previous run are skipped and a checkpoint is
+    /// written into `dir` after every batch, see [`checkpoint::pending_ranges`]. With
+    /// `--verify-crc`, the row checksums of all written snapshots are verified.
+    fn generate_ranges<DB, W>(
+        &self,
+        factory: &ProviderFactory<DB>,
+        segment: SnapshotSegment,
+        dir: &Path,
+        compression: Compression,
+        filters: Filters,
+        write: W,
+    ) -> eyre::Result<()>
+    where
+        DB: Database,
+        W: Fn(&DatabaseProviderRO<DB>, &RangeInclusive<BlockNumber>) -> eyre::Result<Vec<PathBuf>>
+            + Sync,
+    {
+        let from = self.available_from(&factory.provider()?, segment)?;
+        let params = checkpoint::GenerationParams {
+            from,
+            block_interval: self.block_interval,
+            compression,
+            filters,
+        };
+        let mut ranges = self.block_ranges(from, factory.best_block_number()?);
+        if self.resume {
+            ranges = checkpoint::pending_ranges(dir, segment, params, ranges)?;
+        }
 
         let mut created_snapshots = vec![];
 
@@ -190,32 +375,65 @@ impl Command {
         for block_ranges in ranges.chunks(self.parallel as usize) {
             let created_files = block_ranges
                 .into_par_iter()
-                .map(|block_range| {
-                    let provider = factory.provider()?;
-
-                    if !self.only_stats {
-                        segment.snapshot::<DB>(&provider, &dir, block_range.clone())?;
-                    }
-
-                    let tx_range =
-                        provider.transaction_range_by_block_range(block_range.clone())?;
-
-                    Ok(segment.segment().filename(block_range, &tx_range))
-                })
+                .map(|block_range| write(&factory.provider()?, block_range))
                 .collect::<Result<Vec<_>, eyre::Report>>()?;
+            // Block ranges without transactions are skipped with `--skip-empty`
+            let created_files = created_files
+                .into_iter()
+                .flatten()
+                .filter(|file| file.exists())
+                .collect::<Vec<_>>();
+
+            if self.verify_crc {
+                for file in &created_files {
+                    verify_row_crcs(file)?;
+                }
+            }
 
             created_snapshots.extend(created_files);
+
+            if let Some(range) = block_ranges.last().filter(|_| self.resume && !self.only_stats) {
+                checkpoint::write_checkpoint(dir, segment, params, *range.end())?;
+            }
         }
 
         self.stats(created_snapshots)
     }
 
+    /// Returns `self.from`, clamped to the lowest block of which the data of `segment` is still
+    /// available.
+    ///
+    /// Pruned nodes don't have the data of the blocks below their prune checkpoint, so generating
+    /// snapshots for them would fail.
+    fn available_from(
+        &self,
+        provider: &impl PruneCheckpointReader,
+        segment: SnapshotSegment,
+    ) -> ProviderResult<BlockNumber> {
+        let lowest = lowest_available_block(provider, segment)?;
+        if self.from < lowest {
+            warn!(
+                target: "reth::cli",
+                ?segment,
+                from = self.from,
+                lowest,
+                "Data below the lowest available block was pruned, generating snapshots from the lowest available block"
+            );
+            return Ok(lowest)
+        }
+        Ok(self.from)
+    }
+
     /// Prints detailed statistics for each snapshot, including loading time.
     ///
     /// This function loads each snapshot from the provided paths and prints
     /// statistics about various aspects of each snapshot, such as filters size,
     /// offset index size, offset list size, and loading time.
     fn stats(&self, snapshots: Vec<impl AsRef<Path>>) -> eyre::Result<()> {
+        if snapshots.is_empty() {
+            return Ok(())
+        }
+
         let mut total_filters_size = 0;
         let mut total_index_size = 0;
         let mut total_duration = Duration::new(0, 0);
@@ -258,3 +476,65 @@ impl Command {
         Ok(())
     }
 }
+
+/// Returns the lowest block of which the data of `segment` is available, according to the prune
+/// checkpoint of the corresponding [PruneSegment].
+fn lowest_available_block(
+    provider: &impl PruneCheckpointReader,
+    segment: SnapshotSegment,
+) -> ProviderResult<BlockNumber> {
+    let prune_segment = match segment {
+        // headers are never pruned
+        SnapshotSegment::Headers => return Ok(0),
+        SnapshotSegment::Transactions => PruneSegment::Transactions,
+        SnapshotSegment::Receipts => PruneSegment::Receipts,
+    };
+
+    Ok(provider
+        .get_prune_checkpoint(prune_segment)?
+        .and_then(|checkpoint| checkpoint.block_number)
+        .map_or(0, |block| block + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{PruneCheckpoint, PruneMode};
+    use reth_provider::{test_utils::create_test_provider_factory, PruneCheckpointWriter};
+
+    #[test]
+    fn clamp_from_to_pruned_history() {
+        let factory = create_test_provider_factory();
+        let command = Command::parse_from(["snapshots", "transactions", "--from", "5"]);
+
+        // nothing is pruned
+        let provider = factory.provider().unwrap();
+        assert_eq!(command.available_from(&provider, SnapshotSegment::Transactions).unwrap(), 5);
+        drop(provider);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw
+            .save_prune_checkpoint(
+                PruneSegment::Transactions,
+                PruneCheckpoint {
+                    block_number: Some(9),
+                    tx_number: Some(19),
+                    prune_mode: PruneMode::Before(10),
+                },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(lowest_available_block(&provider, SnapshotSegment::Transactions).unwrap(), 10);
+        assert_eq!(command.available_from(&provider, SnapshotSegment::Transactions).unwrap(), 10);
+
+        // other segments are not affected
+        assert_eq!(command.available_from(&provider, SnapshotSegment::Headers).unwrap(), 5);
+        assert_eq!(command.available_from(&provider, SnapshotSegment::Receipts).unwrap(), 5);
+
+        // `from` above the pruned history is kept
+        let command = Command::parse_from(["snapshots", "transactions", "--from", "20"]);
+        assert_eq!(command.available_from(&provider, SnapshotSegment::Transactions).unwrap(), 20);
+    }
+}
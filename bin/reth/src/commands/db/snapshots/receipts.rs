@@ -34,7 +34,7 @@ impl Command {
         let provider = factory.provider()?;
         let tip = provider.last_block_number()?;
         let block_range =
-            self.block_ranges(tip).first().expect("has been generated before").clone();
+            self.block_ranges(self.from, tip).first().expect("has been generated before").clone();
 
         let filters = if let Some(phf) = self.with_filters.then_some(phf).flatten() {
             Filters::WithFilters(inclusion_filter, phf)
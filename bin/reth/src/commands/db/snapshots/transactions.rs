@@ -1,5 +1,5 @@
 use super::{
-    bench::{bench, BenchKind},
+    bench::{bench, bench_latencies, BenchKind},
     Command, Compression, PerfectHashingFunction,
 };
 use rand::{seq::SliceRandom, Rng};
@@ -18,6 +18,9 @@ use std::{
     sync::Arc,
 };
 
+/// Number of random hash lookups used to compute latency percentiles.
+const RANDOM_HASH_LOOKUPS: usize = 1_000;
+
 impl Command {
     pub(crate) fn bench_transactions_snapshot(
         &self,
@@ -34,7 +37,7 @@ impl Command {
         let provider = factory.provider()?;
         let tip = provider.last_block_number()?;
         let block_range =
-            self.block_ranges(tip).first().expect("has been generated before").clone();
+            self.block_ranges(self.from, tip).first().expect("has been generated before").clone();
 
         let filters = if let Some(phf) = self.with_filters.then_some(phf).flatten() {
             Filters::WithFilters(inclusion_filter, phf)
@@ -142,6 +145,40 @@ impl Command {
                 },
             )?;
         }
+
+        // BENCHMARK LATENCY DISTRIBUTION OF QUERYING RANDOM TRANSACTIONS BY HASH
+        {
+            let factory = ProviderFactory::new(open_db_read_only(db_path, db_args)?, chain.clone());
+            let transaction_hashes = row_indexes
+                .choose_multiple(&mut rng, RANDOM_HASH_LOOKUPS)
+                .map(|num| {
+                    Ok(factory
+                        .transaction_by_id(*num)?
+                        .ok_or(ProviderError::TransactionNotFound((*num).into()))?
+                        .hash())
+                })
+                .collect::<eyre::Result<Vec<_>>>()?;
+
+            bench_latencies(
+                BenchKind::RandomHashes,
+                (open_db_read_only(db_path, db_args)?, chain.clone()),
+                SnapshotSegment::Transactions,
+                filters,
+                compression,
+                &transaction_hashes,
+                |hash| {
+                    Ok(cursor
+                        .get_one::<TransactionMask<TransactionSignedNoHash>>(hash.into())?
+                        .ok_or(ProviderError::TransactionNotFound((*hash).into()))?
+                        .with_hash())
+                },
+                |provider, hash| {
+                    Ok(provider
+                        .transaction_by_hash(*hash)?
+                        .ok_or(ProviderError::TransactionNotFound((*hash).into()))?)
+                },
+            )?;
+        }
         Ok(())
     }
 }
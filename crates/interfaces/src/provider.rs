@@ -122,6 +122,30 @@ pub enum ProviderError {
     /// Snapshot file is not found for requested transaction.
     #[error("not able to find {0} snapshot file for transaction id {1}")]
     MissingSnapshotTx(SnapshotSegment, TxNumber),
+    /// Snapshot row doesn't match its checksum.
+    #[error("checksum mismatch in {0} snapshot at number {1}")]
+    SnapshotChecksumMismatch(SnapshotSegment, u64),
+    /// Snapshot files can't be merged into one.
+    #[error("unable to merge {0} snapshot files: {1}")]
+    SnapshotMerge(SnapshotSegment, &'static str),
+    /// Snapshot files can't be compared with each other.
+    #[error("unable to diff {0} snapshot files: {1}")]
+    SnapshotDiff(SnapshotSegment, &'static str),
+    /// Snapshot file doesn't match what its name encodes.
+    #[error("snapshot file {0} doesn't match its name: {1}")]
+    SnapshotFilenameMismatch(PathBuf, &'static str),
+    /// Snapshot row doesn't match the corresponding database row.
+    #[error("{0} snapshot differs from the database at number {1}")]
+    SnapshotRowMismatch(SnapshotSegment, u64),
+    /// Snapshot file can't be verified against the database.
+    #[error("unable to verify {0} snapshot file: {1}")]
+    SnapshotVerify(SnapshotSegment, &'static str),
+    /// Snapshot file can't be restored into the database.
+    #[error("unable to restore {0} snapshot file: {1}")]
+    SnapshotRestore(SnapshotSegment, &'static str),
+    /// Blob index of a snapshot file is malformed.
+    #[error("blob index {0} is corrupted")]
+    SnapshotBlobIndexCorrupted(PathBuf),
     /// Error encountered when the block number conversion from U256 to u64 causes an overflow.
     #[error("failed to convert block number U256 to u64: {0}")]
     BlockNumberOverflow(U256),
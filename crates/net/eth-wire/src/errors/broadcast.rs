@@ -0,0 +1,66 @@
+//! Error handling for broadcast message types, see [`crate::types::broadcast`].
+
+use crate::{Eth68List, EthVersion};
+use reth_primitives::{GotExpected, TxHash, B256};
+
+/// Errors when validating or converting broadcast messages.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastValidationError {
+    /// A blob transaction can't be converted into its pooled form without a sidecar.
+    #[error("missing sidecar for blob transaction {0}")]
+    MissingBlobSidecar(TxHash),
+    /// The transactions root in the block header doesn't match the transactions in the body.
+    #[error("mismatched block transactions root: {0}")]
+    TransactionsRootMismatch(GotExpected<B256>),
+    /// Post-merge blocks must not have any ommers.
+    #[error("post-merge block has {0} ommers")]
+    NonEmptyOmmers(usize),
+    /// The ommers hash of a post-merge block is not the hash of an empty ommers list.
+    #[error("mismatched post-merge ommers hash: {0}")]
+    OmmersHashMismatch(GotExpected<B256>),
+    /// Post-London blocks must have a base fee.
+    #[error("post-London block has no base fee")]
+    MissingBaseFee,
+    /// The base fee of the block doesn't follow from its parent according to EIP-1559.
+    #[error("mismatched base fee: {0}")]
+    BaseFeeMismatch(GotExpected<u64>),
+    /// Announcements of different protocol versions can't be merged.
+    #[error("mixed announcement versions: {0}")]
+    MixedAnnouncementVersions(GotExpected<EthVersion>),
+    /// There are no announcements to merge.
+    #[error("no announcements")]
+    NoAnnouncements,
+}
+
+/// Errors when decoding or validating an eth68 announcement, see
+/// [`NewPooledTransactionHashes68`](crate::NewPooledTransactionHashes68).
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeAnnouncementError {
+    /// The announcement is not valid RLP.
+    #[error(transparent)]
+    Rlp(#[from] alloy_rlp::Error),
+    /// The length of a single list differs from the length of the other two lists.
+    #[error("mismatched eth68 announcement {list:?} length: {len}")]
+    OddListLength {
+        /// The list whose length differs.
+        list: Eth68List,
+        /// The length of the list, and the length of the other two lists.
+        len: GotExpected<usize>,
+    },
+    /// The length of the `types` or `sizes` list differs from the length of the `hashes` list,
+    /// and all three lengths differ.
+    #[error("mismatched eth68 announcement list length: {0}")]
+    ListLengthMismatch(GotExpected<usize>),
+}
+
+impl From<DecodeAnnouncementError> for alloy_rlp::Error {
+    fn from(err: DecodeAnnouncementError) -> Self {
+        match err {
+            DecodeAnnouncementError::Rlp(err) => err,
+            DecodeAnnouncementError::OddListLength { len: GotExpected { got, expected }, .. } |
+            DecodeAnnouncementError::ListLengthMismatch(GotExpected { got, expected }) => {
+                alloy_rlp::Error::ListLengthMismatch { expected, got }
+            }
+        }
+    }
+}
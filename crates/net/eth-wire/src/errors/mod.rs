@@ -1,9 +1,11 @@
 //! Error types for stream variants
 
+mod broadcast;
 mod eth;
 mod muxdemux;
 mod p2p;
 
+pub use broadcast::*;
 pub use eth::*;
 pub use muxdemux::*;
 pub use p2p::*;
@@ -4,11 +4,16 @@ use crate::{
     EthVersion, HelloMessageWithProtocols, P2PStream, ProtocolVersion, Status, UnauthedP2PStream,
 };
 use alloy_chains::Chain;
+use alloy_rlp::Decodable;
 use reth_discv4::DEFAULT_DISCOVERY_PORT;
 use reth_ecies::util::pk2id;
 use reth_primitives::{ForkFilter, Head, B256, U256};
 use secp256k1::{SecretKey, SECP256K1};
-use std::net::SocketAddr;
+use std::{
+    hint::black_box,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 use tokio::net::TcpStream;
 use tokio_util::codec::{Decoder, Framed, LengthDelimitedCodec};
 
@@ -57,6 +62,24 @@ pub async fn connect_passthrough(
     p2p_stream
 }
 
+/// Decodes `bytes` as `T` `iterations` times and returns the total time it took.
+///
+/// Comparing the decode times of messages of different sizes, e.g. in a benchmark, catches
+/// decoders that don't scale linearly with the size of the message. The timings are too noisy to
+/// be asserted on in tests.
+///
+/// # Panics
+///
+/// If `bytes` can't be decoded as `T`.
+pub fn bench_decode<T: Decodable>(bytes: &[u8], iterations: usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let decoded = T::decode(&mut &bytes[..]).expect("failed to decode message");
+        black_box(decoded);
+    }
+    start.elapsed()
+}
+
 /// A Rplx subprotocol for testing
 pub mod proto {
     use super::*;
@@ -1,15 +1,34 @@
 //! Types for broadcasting new data.
 
-use crate::{EthMessage, EthVersion};
+use crate::{
+    errors::{BroadcastValidationError, DecodeAnnouncementError},
+    EthMessage, EthVersion, GetBlockBodies};
 use alloy_rlp::{
-    Decodable, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper,
+    length_of_length, Decodable, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable,
+    RlpEncodableWrapper,
 };
 
+use bitvec::vec::BitVec;
+use bytes::BytesMut;
 use derive_more::{Constructor, Deref, DerefMut, IntoIterator};
+use lru::LruCache;
+use rand::{seq::SliceRandom, Rng};
 use reth_codecs::derive_arbitrary;
-use reth_primitives::{Block, Bytes, TransactionSigned, TxHash, B256, U128};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use reth_primitives::{
+    basefee::calculate_next_block_base_fee, keccak256, proofs::calculate_transaction_root,
+    Address, BaseFeeParams, BlobTransactionSidecar, Block, Bytes, GotExpected, PeerId,
+    PooledTransactionsElement, TransactionSigned, TxHash, TxType, B256, EIP4844_TX_TYPE_ID,
+    EMPTY_OMMER_ROOT_HASH, U128,
+};
 
-use std::{collections::HashMap, mem, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 #[cfg(feature = "arbitrary")]
 use proptest::prelude::*;
@@ -20,6 +39,13 @@ use proptest::{arbitrary::Arbitrary, collection::vec};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Number of transactions from which [`Transactions::recover_senders`] recovers the senders in
+/// parallel.
+const PARALLEL_SENDER_RECOVERY_THRESHOLD: usize = 16;
+
+/// The gas used by the cheapest possible transaction, a plain value transfer.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
 /// This informs peers of new blocks that have appeared on the network.
 #[derive_arbitrary(rlp)]
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper, Default)]
@@ -42,6 +68,112 @@ impl NewBlockHashes {
             Some(block)
         })
     }
+
+    /// Merges the given announcements into one, keeping only the first announcement of each block
+    /// hash, in the order the blocks were announced.
+    ///
+    /// The merged list is preallocated for `capacity` blocks, so it isn't reallocated while merging
+    /// as long as there are at most `capacity` distinct blocks.
+    pub fn merge_all_with_capacity(
+        announcements: impl IntoIterator<Item = NewBlockHashes>,
+        capacity: usize,
+    ) -> NewBlockHashes {
+        let mut blocks = Vec::with_capacity(capacity);
+        let mut seen = HashSet::with_capacity(capacity);
+        for block in announcements.into_iter().flat_map(|announcement| announcement.0) {
+            if seen.insert(block.hash) {
+                blocks.push(block);
+            }
+        }
+        NewBlockHashes(blocks)
+    }
+
+    /// Returns how many blocks the highest announced block is ahead of `local_head`, or `None` if
+    /// no announced block is ahead of it.
+    pub fn gap_to(&self, local_head: u64) -> Option<u64> {
+        self.latest()
+            .map(|latest| latest.number.saturating_sub(local_head))
+            .filter(|gap| *gap > 0)
+    }
+
+    /// Keeps only the `max` highest-numbered blocks and returns the others.
+    ///
+    /// Of blocks with the same number, the first announced ones are kept. Both lists keep the
+    /// order in which the blocks were announced.
+    pub fn truncate_highest(&mut self, max: usize) -> NewBlockHashes {
+        if self.0.len() <= max {
+            return NewBlockHashes::default()
+        }
+
+        // stable sort, so ties are ordered by announcement
+        let mut by_number = self.0.iter().map(|block| block.number).enumerate().collect::<Vec<_>>();
+        by_number.sort_by_key(|(_, number)| Reverse(*number));
+        let mut keep = vec![false; self.0.len()];
+        for (index, _) in &by_number[..max] {
+            keep[*index] = true;
+        }
+
+        let mut keep = keep.into_iter();
+        let (kept, dropped): (Vec<_>, Vec<_>) =
+            mem::take(&mut self.0).into_iter().partition(|_| keep.next() == Some(true));
+        self.0 = kept;
+        NewBlockHashes(dropped)
+    }
+
+    /// Returns the announced blocks whose hash is not in `known`, in the order they were
+    /// announced.
+    pub fn minus(&self, known: &HashSet<B256>) -> NewBlockHashes {
+        NewBlockHashes(
+            self.0.iter().filter(|block| !known.contains(&block.hash)).cloned().collect(),
+        )
+    }
+
+    /// Splits the announced blocks into the ones that are already known and the ones that still
+    /// need to be fetched, returned as `(have, need)`. Both lists keep the order in which the
+    /// blocks were announced.
+    ///
+    /// Whether a block is known is determined by `is_known`, e.g. by looking up the header of the
+    /// block hash with a provider.
+    pub fn partition_known(
+        &self,
+        mut is_known: impl FnMut(&B256) -> bool,
+    ) -> (NewBlockHashes, NewBlockHashes) {
+        let (have, need) = self.0.iter().cloned().partition(|block| is_known(&block.hash));
+        (NewBlockHashes(have), NewBlockHashes(need))
+    }
+
+    /// Returns the [`GetBlockBodies`] request for the announced blocks, in the order they were
+    /// announced.
+    pub fn to_get_block_bodies(&self) -> GetBlockBodies {
+        GetBlockBodies(self.0.iter().map(|block| block.hash).collect())
+    }
+
+    /// Returns the encoded size of the [`GetBlockBodies`] request for the announced blocks.
+    pub fn estimated_request_bytes(&self) -> usize {
+        let payload_length = self.0.len() * B256::ZERO.length();
+        length_of_length(payload_length) + payload_length
+    }
+
+    /// Returns the Shannon entropy in bits of the distribution of the announced block numbers, or
+    /// zero if nothing is announced.
+    ///
+    /// Announcements that repeat the same numbers many times have a low entropy, which can be
+    /// used to score peers that spam announcements.
+    pub fn number_entropy(&self) -> f64 {
+        let mut counts = HashMap::new();
+        for block in &self.0 {
+            *counts.entry(block.number).or_insert(0usize) += 1;
+        }
+
+        let total = self.0.len() as f64;
+        counts
+            .values()
+            .map(|count| {
+                let p = *count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
 }
 
 /// A block hash _and_ a block number.
@@ -79,6 +211,92 @@ pub struct NewBlock {
     pub td: U128,
 }
 
+impl NewBlock {
+    /// Returns `true` if the block is the direct child of the block with the given hash and
+    /// number.
+    pub fn extends(&self, parent_hash: &B256, parent_number: u64) -> bool {
+        self.block.header.parent_hash == *parent_hash &&
+            parent_number.checked_add(1) == Some(self.block.header.number)
+    }
+
+    /// Returns a commitment to the block header and the total difficulty.
+    ///
+    /// This is the keccak256 hash of the RLP encoded header followed by the total difficulty as 16
+    /// big-endian bytes.
+    pub fn commitment(&self) -> B256 {
+        let mut buf = Vec::with_capacity(self.block.header.length() + 16);
+        self.block.header.encode(&mut buf);
+        buf.extend_from_slice(&self.td.to_be_bytes::<16>());
+        keccak256(buf)
+    }
+
+    /// Validates that the transactions root in the block header matches the transactions in the
+    /// block body.
+    pub fn validate_transactions_root(&self) -> Result<(), BroadcastValidationError> {
+        let got = calculate_transaction_root(&self.block.body);
+        let expected = self.block.header.transactions_root;
+        if got != expected {
+            return Err(BroadcastValidationError::TransactionsRootMismatch(GotExpected {
+                got,
+                expected,
+            }))
+        }
+        Ok(())
+    }
+
+    /// Validates that the block has no ommers and that the ommers hash in its header is the hash of
+    /// an empty ommers list, as required for post-merge blocks.
+    pub fn validate_post_merge_ommers(&self) -> Result<(), BroadcastValidationError> {
+        if !self.block.ommers.is_empty() {
+            return Err(BroadcastValidationError::NonEmptyOmmers(self.block.ommers.len()))
+        }
+        let got = self.block.header.ommers_hash;
+        if got != EMPTY_OMMER_ROOT_HASH {
+            return Err(BroadcastValidationError::OmmersHashMismatch(GotExpected {
+                got,
+                expected: EMPTY_OMMER_ROOT_HASH,
+            }))
+        }
+        Ok(())
+    }
+
+    /// Validates that the block has the base fee that follows from its post-London parent with the
+    /// given base fee, gas used and gas limit, according to the EIP-1559 formula with the mainnet
+    /// parameters.
+    pub fn validate_base_fee(
+        &self,
+        parent_base_fee: u64,
+        parent_gas_used: u64,
+        parent_gas_limit: u64,
+    ) -> Result<(), BroadcastValidationError> {
+        let got =
+            self.block.header.base_fee_per_gas.ok_or(BroadcastValidationError::MissingBaseFee)?;
+        let expected = calculate_next_block_base_fee(
+            parent_gas_used,
+            parent_gas_limit,
+            parent_base_fee,
+            BaseFeeParams::ethereum(),
+        );
+        if got != expected {
+            return Err(BroadcastValidationError::BaseFeeMismatch(GotExpected { got, expected }))
+        }
+        Ok(())
+    }
+
+    /// Returns the [`NewBlockHashes`] announcement of this block, which is sent to peers that
+    /// don't receive the full block.
+    pub fn to_hash_announcement(&self) -> NewBlockHashes {
+        let hash = self.block.header.hash_slow();
+        NewBlockHashes(vec![BlockHashNumber { hash, number: self.block.header.number }])
+    }
+
+    /// Returns the length of the RLP encoding of the message, i.e. the block and the total
+    /// difficulty, e.g. for bandwidth accounting of block gossip.
+    pub fn encoded_size(&self) -> usize {
+        self.length()
+    }
+}
+
 /// This informs peers of transactions that have appeared on the network and are not yet included
 /// in a block.
 #[derive_arbitrary(rlp, 10)]
@@ -94,6 +312,306 @@ impl Transactions {
     pub fn has_eip4844(&self) -> bool {
         self.0.iter().any(|tx| tx.is_eip4844())
     }
+
+    /// Returns the versioned hashes of the blobs of all contained EIP-4844 transactions, in
+    /// transaction order.
+    pub fn versioned_hashes(&self) -> Vec<B256> {
+        self.0.iter().filter_map(|tx| tx.blob_versioned_hashes()).flatten().collect()
+    }
+
+    /// Removes duplicate transactions by hash, keeping the first occurrence of each.
+    ///
+    /// Returns the number of removed duplicates, which can be used to penalize the sender.
+    pub fn dedup_by_hash(&mut self) -> usize {
+        let len = self.0.len();
+        let mut seen = HashSet::with_capacity(len);
+        self.0.retain(|tx| seen.insert(tx.hash()));
+        len - self.0.len()
+    }
+
+    /// Returns the transaction with the greatest encoded size and its size, or `None` if there are
+    /// no transactions.
+    ///
+    /// If several transactions have the greatest size, the first of them is returned.
+    pub fn largest(&self) -> Option<(&TransactionSigned, usize)> {
+        self.0.iter().map(|tx| (tx, tx.length())).rev().max_by_key(|(_, size)| *size)
+    }
+
+    /// Returns the sum of the gas limits of all transactions.
+    ///
+    /// The sum is widened to `u128` so it can't overflow.
+    pub fn total_gas_limit(&self) -> u128 {
+        self.0.iter().map(|tx| tx.gas_limit() as u128).sum()
+    }
+
+    /// Recovers the senders of all transactions, in parallel for large messages.
+    ///
+    /// The senders are returned in the order of the transactions, with `None` for transactions
+    /// whose signature is invalid.
+    pub fn recover_senders(&self) -> Vec<Option<Address>> {
+        if self.0.len() < PARALLEL_SENDER_RECOVERY_THRESHOLD {
+            self.0.iter().map(|tx| tx.recover_signer()).collect()
+        } else {
+            self.0.par_iter().map(|tx| tx.recover_signer()).collect()
+        }
+    }
+
+    /// Sorts the transactions by ascending nonce per sender.
+    ///
+    /// Transactions of the same sender are grouped together, and the groups are ordered by the
+    /// first occurrence of their sender. Transactions whose sender can't be recovered form a
+    /// group of their own. Transactions of a sender with the same nonce keep their order.
+    pub fn sort_by_sender_nonce(&mut self) {
+        let senders = self.recover_senders();
+        let mut groups = HashMap::new();
+        let mut grouped = mem::take(&mut self.0)
+            .into_iter()
+            .zip(senders)
+            .map(|(tx, sender)| {
+                let next = groups.len();
+                (*groups.entry(sender).or_insert(next), tx)
+            })
+            .collect::<Vec<_>>();
+        grouped.sort_by_key(|(group, tx)| (*group, tx.nonce()));
+        self.0 = grouped.into_iter().map(|(_, tx)| tx).collect();
+    }
+
+    /// Keeps at most `max` transactions per sender, preferring the lowest nonces, and returns the
+    /// removed transactions.
+    ///
+    /// Transactions whose sender can't be recovered are capped as if they had the same sender.
+    /// The kept transactions keep their order.
+    pub fn cap_per_sender(&mut self, max: usize) -> Transactions {
+        let senders = self.recover_senders();
+        let mut by_nonce = (0..self.0.len()).collect::<Vec<_>>();
+        by_nonce.sort_by_key(|&idx| (senders[idx], self.0[idx].nonce()));
+
+        let mut keep = vec![false; self.0.len()];
+        let mut counts = HashMap::new();
+        for idx in by_nonce {
+            let count = counts.entry(senders[idx]).or_insert(0);
+            if *count < max {
+                keep[idx] = true;
+                *count += 1;
+            }
+        }
+
+        let (kept, removed): (Vec<_>, Vec<_>) =
+            mem::take(&mut self.0).into_iter().zip(keep).partition(|(_, keep)| *keep);
+        self.0 = kept.into_iter().map(|(tx, _)| tx).collect();
+        Transactions(removed.into_iter().map(|(tx, _)| tx).collect())
+    }
+
+    /// Returns `n` distinct transactions chosen at random, each with a probability proportional
+    /// to its encoded size.
+    ///
+    /// If there are at most `n` transactions, all of them are returned in random order.
+    pub fn weighted_sample(&self, n: usize, rng: &mut impl Rng) -> Vec<&TransactionSigned> {
+        self.0
+            .choose_multiple_weighted(rng, n, |tx| tx.length() as f64)
+            .map(|sample| sample.collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the blob gas used by all blob transactions, or zero if there are none.
+    pub fn total_blob_gas(&self) -> u64 {
+        self.0.iter().filter_map(|tx| tx.blob_gas_used()).sum()
+    }
+
+    /// Removes all transactions whose effective tip at the given base fee is below `min_tip`, and
+    /// returns the removed transactions.
+    ///
+    /// Transactions that can't pay the base fee at all are removed as well.
+    pub fn retain_min_tip(&mut self, base_fee: u64, min_tip: u64) -> Transactions {
+        let (kept, removed) = mem::take(&mut self.0).into_iter().partition(|tx| {
+            tx.effective_tip_per_gas(Some(base_fee)).is_some_and(|tip| tip >= min_tip as u128)
+        });
+        self.0 = kept;
+        Transactions(removed)
+    }
+
+    /// Distributes the transactions across tiers of peers, so that transactions paying a higher
+    /// tip at the given base fee are propagated to more tiers.
+    ///
+    /// `tiers` holds the maximum number of transactions to send to the peers of each tier. Every
+    /// tier receives its share of the transactions with the highest tips, ordered by descending
+    /// tip. Transactions that can't pay the base fee rank last. The returned plan has an entry
+    /// for every tier, in the order of `tiers`.
+    pub fn broadcast_plan(&self, base_fee: u64, tiers: &[usize]) -> Vec<Transactions> {
+        let mut by_tip = self.0.iter().collect::<Vec<_>>();
+        by_tip.sort_by_key(|tx| Reverse(tx.effective_tip_per_gas(Some(base_fee))));
+
+        tiers
+            .iter()
+            .map(|max| Transactions(by_tip.iter().take(*max).map(|tx| (*tx).clone()).collect()))
+            .collect()
+    }
+
+    /// Removes all transactions whose gas limit exceeds the given block gas limit, and returns the
+    /// removed transactions.
+    ///
+    /// Such transactions can never be included in a block, so they shouldn't be broadcast.
+    pub fn retain_under_block_gas(&mut self, block_gas_limit: u64) -> Transactions {
+        let (kept, removed) =
+            mem::take(&mut self.0).into_iter().partition(|tx| tx.gas_limit() <= block_gas_limit);
+        self.0 = kept;
+        Transactions(removed)
+    }
+
+    /// Removes all transactions that are structurally invalid for the chain with the given id, and
+    /// returns the removed transactions.
+    ///
+    /// A transaction is removed if it is signed for another chain, its gas limit doesn't even
+    /// cover a plain transfer, its priority fee exceeds its max fee, or it is a blob transaction
+    /// without any blobs. Legacy transactions without a chain id are kept. Signatures aren't
+    /// checked, see [`Transactions::recover_senders`].
+    pub fn retain_valid(&mut self, chain_id: u64) -> Transactions {
+        let (kept, removed) =
+            mem::take(&mut self.0).into_iter().partition(|tx| is_well_formed(tx, chain_id));
+        self.0 = kept;
+        Transactions(removed)
+    }
+
+    /// Removes all transactions that are in the cache of recently broadcast transactions, and
+    /// returns the removed transactions. The hashes of the kept transactions are added to the
+    /// cache.
+    ///
+    /// This suppresses broadcasting the same transaction twice in short succession. Duplicates
+    /// within the message are removed as well, except for their first occurrence.
+    pub fn retain_not_recent(&mut self, recent: &mut LruCache<TxHash, ()>) -> Transactions {
+        let (kept, removed) = mem::take(&mut self.0).into_iter().partition(|tx| {
+            if recent.contains(&tx.hash()) {
+                return false
+            }
+            recent.put(tx.hash(), ());
+            true
+        });
+        self.0 = kept;
+        Transactions(removed)
+    }
+
+    /// Returns a hash of the set of contained transaction hashes, e.g. as a key for caches of
+    /// already seen messages.
+    ///
+    /// The hash doesn't depend on the order of the transactions, and duplicates are only counted
+    /// once. It is the keccak256 hash of the sorted and deduplicated transaction hashes.
+    pub fn set_hash(&self) -> B256 {
+        let mut hashes = self.0.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+        hashes.sort_unstable();
+        hashes.dedup();
+        keccak256(hashes.iter().flat_map(|hash| hash.0).collect::<Vec<_>>())
+    }
+
+    /// Returns a [`TransactionsSummary`] of the message, computed in a single pass.
+    pub fn summary(&self) -> TransactionsSummary {
+        let mut summary = TransactionsSummary { count: self.0.len(), ..Default::default() };
+        for tx in &self.0 {
+            let size = tx.length();
+            summary.total_bytes += size;
+            summary.largest_bytes = summary.largest_bytes.max(size);
+            *summary.type_counts.entry(tx.tx_type()).or_default() += 1;
+        }
+        summary
+    }
+
+    /// Encodes the message into `out`, reserving the entire encoded length up front so the buffer
+    /// is grown at most once.
+    pub fn encode_into(&self, out: &mut BytesMut) {
+        out.reserve(self.length());
+        self.encode(out);
+    }
+
+    /// Returns the announcement of the transaction hashes for peers of the given version.
+    ///
+    /// For [`EthVersion::Eth68`] the announcement includes the type and the size of the pooled
+    /// encoding of each transaction, see [`PooledTransactionsElement::length_without_header`].
+    /// Blob transactions are broadcast without their sidecar, but their pooled encoding includes
+    /// it, so the sidecar is looked up via `sidecar_lookup`. Returns an error if a sidecar isn't
+    /// available for any blob transaction.
+    pub fn to_announcement(
+        &self,
+        version: EthVersion,
+        sidecar_lookup: impl Fn(&TxHash) -> Option<BlobTransactionSidecar>,
+    ) -> Result<NewPooledTransactionHashes, BroadcastValidationError> {
+        if version != EthVersion::Eth68 {
+            let hashes = self.0.iter().map(|tx| tx.hash()).collect();
+            return Ok(NewPooledTransactionHashes66(hashes).into())
+        }
+
+        let mut msg = NewPooledTransactionHashes68 {
+            types: Vec::with_capacity(self.0.len()),
+            sizes: Vec::with_capacity(self.0.len()),
+            hashes: Vec::with_capacity(self.0.len()),
+        };
+        for tx in &self.0 {
+            let size = if tx.is_eip4844() {
+                into_pooled_element(tx.clone(), &sidecar_lookup)?.length_without_header()
+            } else {
+                // the pooled encoding of all other transactions is their EIP-2718 encoding
+                tx.length_without_header()
+            };
+            msg.types.push(tx.tx_type().into());
+            msg.sizes.push(size);
+            msg.hashes.push(tx.hash());
+        }
+        Ok(msg.into())
+    }
+
+    /// Splits the transactions into the ones that can be broadcast in full and the announcement
+    /// of the blob transactions for peers of the given version, see [`Self::to_announcement`].
+    ///
+    /// Blob transactions must only be announced, never broadcast in full. Since announcements are
+    /// the only way they're sent, their announced sizes include the sidecar looked up via
+    /// `sidecar_lookup`. Both keep the order of the transactions.
+    pub fn prepare_for_broadcast(
+        self,
+        version: EthVersion,
+        sidecar_lookup: impl Fn(&TxHash) -> Option<BlobTransactionSidecar>,
+    ) -> Result<(Transactions, NewPooledTransactionHashes), BroadcastValidationError> {
+        let (blobs, full): (Vec<_>, Vec<_>) = self.0.into_iter().partition(|tx| tx.is_eip4844());
+        let announcement = Transactions(blobs).to_announcement(version, sidecar_lookup)?;
+        Ok((Transactions(full), announcement))
+    }
+
+    /// Converts the transactions into their pooled form.
+    ///
+    /// Blob transactions are broadcast without their sidecar, so the sidecar is looked up via
+    /// `sidecar_lookup`. Returns an error if a sidecar isn't available for any blob transaction.
+    pub fn into_pooled(
+        self,
+        sidecar_lookup: impl Fn(&TxHash) -> Option<BlobTransactionSidecar>,
+    ) -> Result<Vec<PooledTransactionsElement>, BroadcastValidationError> {
+        self.0.into_iter().map(|tx| into_pooled_element(tx, &sidecar_lookup)).collect()
+    }
+}
+
+/// Converts the transaction into its pooled form, looking up the sidecar of a blob transaction via
+/// `sidecar_lookup`.
+fn into_pooled_element(
+    tx: TransactionSigned,
+    sidecar_lookup: &impl Fn(&TxHash) -> Option<BlobTransactionSidecar>,
+) -> Result<PooledTransactionsElement, BroadcastValidationError> {
+    if !tx.is_eip4844() {
+        return Ok(tx.into())
+    }
+    let hash = tx.hash();
+    let sidecar =
+        sidecar_lookup(&hash).ok_or(BroadcastValidationError::MissingBlobSidecar(hash))?;
+    PooledTransactionsElement::try_from_blob_transaction(tx, sidecar)
+        .map_err(|tx| BroadcastValidationError::MissingBlobSidecar(tx.hash()))
+}
+
+/// Summary of a [`Transactions`] message, e.g. for structured logging.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionsSummary {
+    /// Number of transactions in the message.
+    pub count: usize,
+    /// Sum of the encoded sizes of all transactions.
+    pub total_bytes: usize,
+    /// Number of transactions per transaction type.
+    pub type_counts: HashMap<TxType, usize>,
+    /// Encoded size of the largest transaction, `0` if there are no transactions.
+    pub largest_bytes: usize,
 }
 
 impl From<Vec<TransactionSigned>> for Transactions {
@@ -108,6 +626,21 @@ impl From<Transactions> for Vec<TransactionSigned> {
     }
 }
 
+/// Returns `true` if the transaction passes the structural checks of
+/// [`Transactions::retain_valid`].
+fn is_well_formed(tx: &TransactionSigned, chain_id: u64) -> bool {
+    if tx.chain_id().is_some_and(|id| id != chain_id) {
+        return false
+    }
+    if tx.gas_limit() < MIN_TRANSACTION_GAS {
+        return false
+    }
+    if tx.max_priority_fee_per_gas().is_some_and(|tip| tip > tx.max_fee_per_gas()) {
+        return false
+    }
+    tx.blob_versioned_hashes().map_or(true, |hashes| !hashes.is_empty())
+}
+
 /// Same as [`Transactions`] but this is intended as egress message send from local to _many_ peers.
 ///
 /// The list of transactions is constructed on per-peers basis, but the underlying transaction
@@ -119,6 +652,18 @@ pub struct SharedTransactions(
     pub Vec<Arc<TransactionSigned>>,
 );
 
+impl SharedTransactions {
+    /// Creates a batch of all transactions in `txs` that are not in the set of hashes the peer is
+    /// already known to have.
+    ///
+    /// Only the [`Arc`]s of included transactions are cloned.
+    pub fn exclude_known(txs: &[Arc<TransactionSigned>], known: &HashSet<TxHash>) -> Self {
+        SharedTransactions(
+            txs.iter().filter(|tx| !known.contains(&tx.hash())).map(Arc::clone).collect(),
+        )
+    }
+}
+
 /// A wrapper type for all different new pooled transaction types
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NewPooledTransactionHashes {
@@ -142,17 +687,55 @@ impl NewPooledTransactionHashes {
     }
 
     /// Returns `true` if the payload is valid for the given version
+    ///
+    /// An [`EthVersion::Eth68`] payload is only valid if its lists are aligned, see
+    /// [`NewPooledTransactionHashes68::validate_aligned`].
     pub fn is_valid_for_version(&self, version: EthVersion) -> bool {
         match self {
             NewPooledTransactionHashes::Eth66(_) => {
                 matches!(version, EthVersion::Eth67 | EthVersion::Eth66)
             }
-            NewPooledTransactionHashes::Eth68(_) => {
-                matches!(version, EthVersion::Eth68)
+            NewPooledTransactionHashes::Eth68(msg) => {
+                matches!(version, EthVersion::Eth68) && msg.validate_aligned().is_ok()
+            }
+        }
+    }
+
+    /// Returns `true` if converting this announcement to an [`EthVersion::Eth66`] announcement
+    /// would drop transaction types or sizes.
+    ///
+    /// This is the case for any [`EthVersion::Eth68`] announcement that announces at least one
+    /// transaction, since eth66 announcements carry no metadata.
+    pub fn downgrade_loses_metadata(&self) -> bool {
+        match self {
+            NewPooledTransactionHashes::Eth66(_) => false,
+            NewPooledTransactionHashes::Eth68(msg) => {
+                !msg.types.is_empty() || !msg.sizes.is_empty()
             }
         }
     }
 
+    /// Returns the announced hashes with their type and size, sorted by hash, for deterministic
+    /// debug logs.
+    ///
+    /// The metadata is `None` for [`EthVersion::Eth66`] announcements, and for hashes of
+    /// malformed [`EthVersion::Eth68`] announcements that lack a type or size.
+    pub fn debug_entries(&self) -> Vec<(B256, Option<(u8, usize)>)> {
+        let mut entries = match self {
+            NewPooledTransactionHashes::Eth66(msg) => {
+                msg.0.iter().map(|hash| (*hash, None)).collect::<Vec<_>>()
+            }
+            NewPooledTransactionHashes::Eth68(msg) => msg
+                .hashes
+                .iter()
+                .enumerate()
+                .map(|(i, hash)| (*hash, msg.types.get(i).copied().zip(msg.sizes.get(i).copied())))
+                .collect(),
+        };
+        entries.sort_by_key(|(hash, _)| *hash);
+        entries
+    }
+
     /// Returns an iterator over all transaction hashes.
     pub fn iter_hashes(&self) -> impl Iterator<Item = &B256> + '_ {
         match self {
@@ -161,6 +744,31 @@ impl NewPooledTransactionHashes {
         }
     }
 
+    /// Returns the set of all hashes announced in the given messages, regardless of their
+    /// version.
+    pub fn union_hashes(msgs: &[NewPooledTransactionHashes]) -> HashSet<TxHash> {
+        msgs.iter().flat_map(|msg| msg.iter_hashes().copied()).collect()
+    }
+
+    /// Returns the Jaccard similarity of the hashes announced in the given messages, i.e. the
+    /// number of hashes announced in both divided by the number of hashes announced in either,
+    /// regardless of their version.
+    ///
+    /// Comparing the announcements of different peers hints at how well connected they are.
+    /// Returns `0.0` if both messages are empty.
+    pub fn announcement_overlap(
+        a: &NewPooledTransactionHashes,
+        b: &NewPooledTransactionHashes,
+    ) -> f64 {
+        let a = a.iter_hashes().collect::<HashSet<_>>();
+        let b = b.iter_hashes().collect::<HashSet<_>>();
+        let union = a.union(&b).count();
+        if union == 0 {
+            return 0.0
+        }
+        a.intersection(&b).count() as f64 / union as f64
+    }
+
     /// Returns an immutable reference to transaction hashes.
     pub fn hashes(&self) -> &Vec<B256> {
         match self {
@@ -206,6 +814,109 @@ impl NewPooledTransactionHashes {
         }
     }
 
+    /// Returns the number of hashes the spec recommends as soft limit for a single announcement of
+    /// the given version.
+    ///
+    /// All versions so far recommend 4096 hashes, which is ~128 KiB for eth66 and eth67, and ~150
+    /// KiB for eth68 with the additional metadata.
+    pub const fn recommended_max_entries(version: EthVersion) -> usize {
+        match version {
+            EthVersion::Eth66 | EthVersion::Eth67 | EthVersion::Eth68 => 4096,
+        }
+    }
+
+    /// Splits the message into messages of the same version with at most
+    /// [`NewPooledTransactionHashes::recommended_max_entries`] hashes each, in order.
+    ///
+    /// See [`NewPooledTransactionHashes::chunked`].
+    pub fn chunked_recommended(self) -> Vec<NewPooledTransactionHashes> {
+        let max_per_message = Self::recommended_max_entries(self.version());
+        self.chunked(max_per_message)
+    }
+
+    /// Splits the message into messages of the same version with at most `max_per_message` hashes
+    /// each, in order. For an eth68 announcement, each message keeps the metadata of its hashes.
+    ///
+    /// Returns no messages if this message is empty. The lists of an eth68 announcement must be
+    /// aligned, see [`NewPooledTransactionHashes68::validate_aligned`], otherwise the entries
+    /// past the shortest list are dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `max_per_message` is zero, or in debug mode if the lists of an eth68 announcement are
+    /// misaligned.
+    pub fn chunked(self, max_per_message: usize) -> Vec<NewPooledTransactionHashes> {
+        assert!(max_per_message > 0, "max_per_message must be greater than 0");
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => msg
+                .0
+                .chunks(max_per_message)
+                .map(|hashes| {
+                    NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(hashes.to_vec()))
+                })
+                .collect(),
+            NewPooledTransactionHashes::Eth68(mut msg) => {
+                debug_assert!(msg.validate_aligned().is_ok(), "misaligned eth68 announcement");
+                let len = msg.types.len().min(msg.sizes.len()).min(msg.hashes.len());
+                msg.types.truncate(len);
+                msg.sizes.truncate(len);
+                msg.hashes.truncate(len);
+
+                msg.types
+                    .chunks(max_per_message)
+                    .zip(msg.sizes.chunks(max_per_message))
+                    .zip(msg.hashes.chunks(max_per_message))
+                    .map(|((types, sizes), hashes)| {
+                        NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+                            types: types.to_vec(),
+                            sizes: sizes.to_vec(),
+                            hashes: hashes.to_vec(),
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Removes the hashes that are in `known`, e.g. because they're already in the pool. For an
+    /// eth68 announcement, the metadata of the removed hashes is removed as well.
+    pub fn retain_unknown(&mut self, known: &HashSet<TxHash>) {
+        let _ = self.retain_by_hash(|hash| !known.contains(hash));
+    }
+
+    /// Removes the hashes that are in `in_flight` because they're currently being fetched, so they
+    /// aren't requested again. For an eth68 announcement, the metadata of the removed hashes is
+    /// removed as well.
+    pub fn retain_not_in_flight(&mut self, in_flight: &HashSet<TxHash>) {
+        let _ = self.retain_by_hash(|hash| !in_flight.contains(hash));
+    }
+
+    /// Returns a message of the same version with only the hashes that are not in `prev`, e.g. the
+    /// hashes of a previous announcement of the same peer, in order. For an eth68 announcement,
+    /// the metadata of the remaining hashes is kept.
+    ///
+    /// Unlike [`Self::retain_unknown`], this only copies the remaining hashes.
+    pub fn difference(&self, prev: &HashSet<TxHash>) -> NewPooledTransactionHashes {
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => {
+                NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(
+                    msg.0.iter().filter(|hash| !prev.contains(*hash)).copied().collect(),
+                ))
+            }
+            NewPooledTransactionHashes::Eth68(msg) => {
+                let mut diff = NewPooledTransactionHashes68::default();
+                for ((tx_type, size), hash) in msg.types.iter().zip(&msg.sizes).zip(&msg.hashes) {
+                    if !prev.contains(hash) {
+                        diff.types.push(*tx_type);
+                        diff.sizes.push(*size);
+                        diff.hashes.push(*hash);
+                    }
+                }
+                NewPooledTransactionHashes::Eth68(diff)
+            }
+        }
+    }
+
     /// Returns true if the message is empty
     pub fn is_empty(&self) -> bool {
         match self {
@@ -337,21 +1048,71 @@ pub struct NewPooledTransactionHashes68 {
     pub hashes: Vec<B256>,
 }
 
+/// Fee data of an announced blob transaction, see
+/// [`NewPooledTransactionHashes68::sort_by_blob_priority`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlobFeeData {
+    /// Max fee per gas the transaction is willing to pay.
+    pub max_fee_per_gas: u128,
+    /// Max priority fee per gas the transaction is willing to pay.
+    pub max_priority_fee_per_gas: u128,
+    /// Max fee per blob gas the transaction is willing to pay.
+    pub max_fee_per_blob_gas: u128,
+}
+
+impl BlobFeeData {
+    /// Returns the priority of the transaction at the given fees, or `None` if it can't be
+    /// included at these fees.
+    ///
+    /// The priority is ordered by how far the max fee per blob gas exceeds the blob base fee
+    /// first, and then by the effective tip per gas.
+    pub fn priority(&self, base_fee: u64, blob_base_fee: u128) -> Option<(u128, u128)> {
+        let blob_fee_headroom = self.max_fee_per_blob_gas.checked_sub(blob_base_fee)?;
+        let effective_tip = self
+            .max_fee_per_gas
+            .checked_sub(base_fee as u128)?
+            .min(self.max_priority_fee_per_gas);
+        Some((blob_fee_headroom, effective_tip))
+    }
+}
+
+/// Parameters for the [`Arbitrary`] implementation of [`NewPooledTransactionHashes68`].
+#[cfg(feature = "arbitrary")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewPooledTransactionHashes68Params {
+    /// Exclusive upper bound for generated sizes.
+    ///
+    /// Raising this above the default allows generating deliberately invalid, over-limit
+    /// announcements.
+    pub max_size: usize,
+}
+
+#[cfg(feature = "arbitrary")]
+impl Default for NewPooledTransactionHashes68Params {
+    fn default() -> Self {
+        // 131072(0x20000)
+        Self { max_size: 131072 }
+    }
+}
+
 #[cfg(feature = "arbitrary")]
 impl Arbitrary for NewPooledTransactionHashes68 {
-    type Parameters = ();
-    fn arbitrary_with(_args: ()) -> Self::Strategy {
+    type Parameters = NewPooledTransactionHashes68Params;
+    fn arbitrary_with(params: NewPooledTransactionHashes68Params) -> Self::Strategy {
+        let max_size = params.max_size.max(1);
+
         // Generate a single random length for all vectors
         let vec_length = any::<usize>().prop_map(|x| x % 100 + 1); // Lengths between 1 and 100
 
         vec_length
-            .prop_flat_map(|len| {
+            .prop_flat_map(move |len| {
                 // Use the generated length to create vectors of TxType, usize, and B256
                 let types_vec =
                     vec(any::<reth_primitives::TxType>().prop_map(|ty| ty as u8), len..=len);
 
-                // Map the usize values to the range 0..131072(0x20000)
-                let sizes_vec = vec(proptest::num::usize::ANY.prop_map(|x| x % 131072), len..=len);
+                // Map the usize values to the range 0..max_size
+                let sizes_vec =
+                    vec(proptest::num::usize::ANY.prop_map(move |x| x % max_size), len..=len);
                 let hashes_vec = vec(any::<B256>(), len..=len);
 
                 (types_vec, sizes_vec, hashes_vec)
@@ -368,14 +1129,141 @@ impl Arbitrary for NewPooledTransactionHashes68 {
 }
 
 impl NewPooledTransactionHashes68 {
+    /// Checks that the `types` and `sizes` lists have the same length as the `hashes` list.
+    ///
+    /// A message with misaligned lists encodes to bytes that peers will reject, so sessions don't
+    /// send it, see [`NewPooledTransactionHashes::is_valid_for_version`]. The encoder only
+    /// debug-asserts this.
+    ///
+    /// Reports the list whose length differs if only one does, see [`Self::odd_list`].
+    pub fn validate_aligned(&self) -> Result<(), DecodeAnnouncementError> {
+        if let Some(list) = self.odd_list() {
+            let (got, expected) = match list {
+                Eth68List::Types => (self.types.len(), self.hashes.len()),
+                Eth68List::Sizes => (self.sizes.len(), self.hashes.len()),
+                Eth68List::Hashes => (self.hashes.len(), self.types.len()),
+            };
+            return Err(DecodeAnnouncementError::OddListLength {
+                list,
+                len: GotExpected { got, expected },
+            })
+        }
+        for len in [self.types.len(), self.sizes.len()] {
+            if len != self.hashes.len() {
+                return Err(DecodeAnnouncementError::ListLengthMismatch(GotExpected {
+                    got: len,
+                    expected: self.hashes.len(),
+                }))
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes all entries of the given transaction type, e.g. blob transactions, and returns them.
+    ///
+    /// Both announcements stay aligned and keep the order of the entries.
+    pub fn without_type(&mut self, ty: u8) -> NewPooledTransactionHashes68 {
+        let mut kept = NewPooledTransactionHashes68::default();
+        let mut removed = NewPooledTransactionHashes68::default();
+        let entries = mem::take(&mut self.types)
+            .into_iter()
+            .zip(mem::take(&mut self.sizes))
+            .zip(mem::take(&mut self.hashes));
+        for ((tx_type, size), hash) in entries {
+            let msg = if tx_type == ty { &mut removed } else { &mut kept };
+            msg.types.push(tx_type);
+            msg.sizes.push(size);
+            msg.hashes.push(hash);
+        }
+        *self = kept;
+
+        removed
+    }
+
+    /// Reorders the entries so that the blob transactions with the highest
+    /// [`BlobFeeData::priority`] at the given fees come first, e.g. to fetch them first.
+    ///
+    /// The fee data of the blob transactions is looked up with `metadata_lookup`. Blob
+    /// transactions without fee data or that can't be included at the given fees, and all other
+    /// transactions, come last in the order they were announced. Entries stay aligned.
+    pub fn sort_by_blob_priority(
+        &mut self,
+        base_fee: u64,
+        blob_base_fee: u128,
+        metadata_lookup: impl Fn(&TxHash) -> Option<BlobFeeData>,
+    ) {
+        let priorities = self
+            .types
+            .iter()
+            .zip(&self.hashes)
+            .map(|(ty, hash)| {
+                if *ty != EIP4844_TX_TYPE_ID {
+                    return None
+                }
+                metadata_lookup(hash)?.priority(base_fee, blob_base_fee)
+            })
+            .collect::<Vec<_>>();
+
+        // stable sort, so entries of equal priority keep the announcement order
+        let mut order = (0..priorities.len()).collect::<Vec<_>>();
+        order.sort_by_key(|index| Reverse(priorities[*index]));
+
+        self.types = order.iter().map(|index| self.types[*index]).collect();
+        self.sizes = order.iter().map(|index| self.sizes[*index]).collect();
+        self.hashes = order.iter().map(|index| self.hashes[*index]).collect();
+    }
+
     /// Returns an iterator over tx hashes zipped with corresponding metadata.
     pub fn metadata_iter(&self) -> impl Iterator<Item = (&B256, (u8, usize))> {
         self.hashes.iter().zip(self.types.iter().copied().zip(self.sizes.iter().copied()))
     }
+
+    /// Returns the total announced size of the transactions, grouped by transaction type.
+    pub fn size_by_type(&self) -> HashMap<u8, usize> {
+        let mut sizes = HashMap::new();
+        for (ty, size) in self.types.iter().zip(self.sizes.iter()) {
+            *sizes.entry(*ty).or_default() += size;
+        }
+        sizes
+    }
+
+    /// Returns the average announced size of the transactions, or `None` if there are none.
+    pub fn mean_size(&self) -> Option<f64> {
+        if self.sizes.is_empty() {
+            return None
+        }
+        let total = self.sizes.iter().map(|size| *size as f64).sum::<f64>();
+        Some(total / self.sizes.len() as f64)
+    }
+}
+
+/// Merges the announcements of multiple peers into a single announcement ordered by ascending
+/// size, e.g. to plan fetching the smallest transactions first across all peers.
+///
+/// A hash announced more than once is only kept with the metadata of its first announcement.
+/// Entries of the same size keep the order of `msgs`.
+pub fn merge_by_size(msgs: Vec<NewPooledTransactionHashes68>) -> NewPooledTransactionHashes68 {
+    let mut seen = HashSet::new();
+    let mut entries = msgs
+        .into_iter()
+        .flat_map(|msg| msg.hashes.into_iter().zip(msg.types.into_iter().zip(msg.sizes)))
+        .filter(|(hash, _)| seen.insert(*hash))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|(_, (_, size))| *size);
+
+    let mut merged = NewPooledTransactionHashes68::default();
+    for (hash, (ty, size)) in entries {
+        merged.hashes.push(hash);
+        merged.types.push(ty);
+        merged.sizes.push(size);
+    }
+    merged
 }
 
 impl Encodable for NewPooledTransactionHashes68 {
     fn encode(&self, out: &mut dyn bytes::BufMut) {
+        debug_assert!(self.validate_aligned().is_ok(), "misaligned eth68 announcement");
+
         #[derive(RlpEncodable)]
         struct EncodableNewPooledTransactionHashes68<'a> {
             types: &'a [u8],
@@ -409,8 +1297,9 @@ impl Encodable for NewPooledTransactionHashes68 {
     }
 }
 
-impl Decodable for NewPooledTransactionHashes68 {
-    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+impl NewPooledTransactionHashes68 {
+    /// Decodes the message without checking that its lists are aligned.
+    fn decode_unaligned(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
         #[derive(RlpDecodable)]
         struct EncodableNewPooledTransactionHashes68 {
             types: Bytes,
@@ -419,27 +1308,59 @@ impl Decodable for NewPooledTransactionHashes68 {
         }
 
         let encodable = EncodableNewPooledTransactionHashes68::decode(buf)?;
-        let msg = Self {
-            types: encodable.types.into(),
-            sizes: encodable.sizes,
-            hashes: encodable.hashes,
-        };
+        Ok(Self { types: encodable.types.into(), sizes: encodable.sizes, hashes: encodable.hashes })
+    }
 
-        if msg.hashes.len() != msg.types.len() {
-            return Err(alloy_rlp::Error::ListLengthMismatch {
-                expected: msg.hashes.len(),
-                got: msg.types.len(),
-            })
-        }
-        if msg.hashes.len() != msg.sizes.len() {
-            return Err(alloy_rlp::Error::ListLengthMismatch {
-                expected: msg.hashes.len(),
-                got: msg.sizes.len(),
-            })
-        }
+    /// Decodes the message like [`Decodable::decode`], but returns a
+    /// [`DecodeAnnouncementError`] that tells which list is misaligned.
+    pub fn decode_aligned(buf: &mut &[u8]) -> Result<Self, DecodeAnnouncementError> {
+        let msg = Self::decode_unaligned(buf)?;
+        msg.validate_aligned()?;
+        Ok(msg)
+    }
 
+    /// Decodes the message like [`Decodable::decode`], but truncates the `types`, `sizes` and
+    /// `hashes` lists to the length of the shortest one instead of rejecting misaligned lists.
+    pub fn decode_lenient(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut msg = Self::decode_unaligned(buf)?;
+        let len = msg.types.len().min(msg.sizes.len()).min(msg.hashes.len());
+        msg.types.truncate(len);
+        msg.sizes.truncate(len);
+        msg.hashes.truncate(len);
         Ok(msg)
     }
+
+    /// Returns the list whose length differs from the lengths of the other two lists, if there's
+    /// exactly one such list.
+    pub fn odd_list(&self) -> Option<Eth68List> {
+        let (types, sizes, hashes) = (self.types.len(), self.sizes.len(), self.hashes.len());
+        if types == sizes && sizes != hashes {
+            Some(Eth68List::Hashes)
+        } else if types == hashes && hashes != sizes {
+            Some(Eth68List::Sizes)
+        } else if sizes == hashes && hashes != types {
+            Some(Eth68List::Types)
+        } else {
+            None
+        }
+    }
+}
+
+impl Decodable for NewPooledTransactionHashes68 {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        Self::decode_aligned(buf).map_err(Into::into)
+    }
+}
+
+/// One of the lists of a [`NewPooledTransactionHashes68`] announcement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eth68List {
+    /// The `types` list.
+    Types,
+    /// The `sizes` list.
+    Sizes,
+    /// The `hashes` list.
+    Hashes,
 }
 
 /// Interface for handling announcement data in filters in the transaction manager and transaction
@@ -456,6 +1377,26 @@ pub trait HandleAnnouncement {
     /// the rest.
     fn retain_by_hash(&mut self, f: impl FnMut(&TxHash) -> bool) -> Self;
 
+    /// Returns a bit per entry, in announcement order, that is set if the entry's hash is in
+    /// `known`.
+    ///
+    /// Entries without a defined order, like those of [`ValidAnnouncementData`], are ordered by
+    /// hash.
+    ///
+    /// By default, the entries are visited in the order of [`Self::retain_by_hash`] on a clone
+    /// of the announcement.
+    fn known_bitset(&self, known: &HashSet<TxHash>) -> BitVec
+    where
+        Self: Clone,
+    {
+        let mut bits = BitVec::with_capacity(self.len());
+        self.clone().retain_by_hash(|hash| {
+            bits.push(known.contains(hash));
+            true
+        });
+        bits
+    }
+
     /// Returns the announcement version, either [`Eth66`](EthVersion::Eth66) or
     /// [`Eth68`](EthVersion::Eth68).
     fn msg_version(&self) -> EthVersion;
@@ -477,6 +1418,13 @@ impl HandleAnnouncement for NewPooledTransactionHashes {
         }
     }
 
+    fn known_bitset(&self, known: &HashSet<TxHash>) -> BitVec {
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => msg.known_bitset(known),
+            NewPooledTransactionHashes::Eth68(msg) => msg.known_bitset(known),
+        }
+    }
+
     fn msg_version(&self) -> EthVersion {
         self.version()
     }
@@ -515,6 +1463,10 @@ impl HandleAnnouncement for NewPooledTransactionHashes68 {
         Self { hashes: removed_hashes, types: removed_types, sizes: removed_sizes }
     }
 
+    fn known_bitset(&self, known: &HashSet<TxHash>) -> BitVec {
+        self.hashes.iter().map(|hash| known.contains(hash)).collect()
+    }
+
     fn msg_version(&self) -> EthVersion {
         EthVersion::Eth68
     }
@@ -547,6 +1499,10 @@ impl HandleAnnouncement for NewPooledTransactionHashes66 {
         Self(removed_hashes)
     }
 
+    fn known_bitset(&self, known: &HashSet<TxHash>) -> BitVec {
+        self.0.iter().map(|hash| known.contains(hash)).collect()
+    }
+
     fn msg_version(&self) -> EthVersion {
         EthVersion::Eth66
     }
@@ -555,7 +1511,11 @@ impl HandleAnnouncement for NewPooledTransactionHashes66 {
 /// Announcement data that has been validated according to the configured network. For an eth68
 /// announcement, values of the map are `Some((u8, usize))` - the tx metadata. For an eth66
 /// announcement, values of the map are `None`.
-#[derive(Debug, Deref, DerefMut, IntoIterator, Constructor)]
+///
+/// With the `serde` feature, the data can be persisted together with its version, e.g. to keep
+/// pending announcements across restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Deref, DerefMut, IntoIterator, Constructor)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ValidAnnouncementData {
     #[deref]
     #[deref_mut]
@@ -602,6 +1562,57 @@ impl ValidAnnouncementData {
 
         (RequestTxHashes::new(hashes), self.version)
     }
+
+    /// Returns the hashes of the entries whose announced size is within `[min, max]`, e.g. to
+    /// request only medium-sized transactions in one pass.
+    ///
+    /// [`Eth66`](EthVersion::Eth66) announcements carry no sizes, so all hashes are returned.
+    /// Entries of an [`Eth68`](EthVersion::Eth68) announcement without a size are skipped.
+    pub fn request_hashes_in_size_band(&self, min: usize, max: usize) -> RequestTxHashes {
+        let hashes = if self.version == EthVersion::Eth66 {
+            self.data.keys().copied().collect()
+        } else {
+            self.data
+                .iter()
+                .filter(|(_, meta)| meta.is_some_and(|(_, size)| (min..=max).contains(&size)))
+                .map(|(hash, _)| *hash)
+                .collect()
+        };
+
+        RequestTxHashes::new(hashes)
+    }
+
+    /// Returns the number of announced transactions per transaction type.
+    ///
+    /// Only [`Eth68`](EthVersion::Eth68) announcements carry the transaction type, for other
+    /// versions this returns an empty map.
+    pub fn counts_by_type(&self) -> HashMap<u8, usize> {
+        let mut counts = HashMap::new();
+        if self.version != EthVersion::Eth68 {
+            return counts
+        }
+        for (ty, _) in self.data.values().flatten() {
+            *counts.entry(*ty).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Removes and returns up to `n` entries, ordered by announced size ascending.
+    ///
+    /// Entries without a size, e.g. from an [`Eth66`](EthVersion::Eth66) announcement, sort last.
+    /// Entries of equal size are ordered by hash.
+    pub fn drain_smallest(&mut self, n: usize) -> Vec<(TxHash, Option<(u8, usize)>)> {
+        let mut entries = self.data.iter().map(|(hash, meta)| (*hash, *meta)).collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(hash, meta)| {
+            (meta.is_none(), meta.map(|(_, size)| size), *hash)
+        });
+        entries.truncate(n);
+
+        for (hash, _) in &entries {
+            self.data.remove(hash);
+        }
+        entries
+    }
 }
 
 impl HandleAnnouncement for ValidAnnouncementData {
@@ -623,21 +1634,35 @@ impl HandleAnnouncement for ValidAnnouncementData {
         ValidAnnouncementData::new(rest, self.version)
     }
 
+    /// The map has no defined order, so there is a bit per entry in ascending hash order.
+    fn known_bitset(&self, known: &HashSet<TxHash>) -> BitVec {
+        let mut hashes = self.data.keys().collect::<Vec<_>>();
+        hashes.sort_unstable();
+        hashes.into_iter().map(|hash| known.contains(hash)).collect()
+    }
+
     fn msg_version(&self) -> EthVersion {
         self.version
     }
 }
 
 /// Hashes to request from a peer.
-#[derive(Debug, Default, Deref, DerefMut, IntoIterator, Constructor)]
+#[derive(Debug, Default, Deref, DerefMut, IntoIterator)]
 pub struct RequestTxHashes {
     #[deref]
     #[deref_mut]
     #[into_iterator(owned, ref)]
     hashes: Vec<TxHash>,
+    /// When the request was sent to the peer, if it has been sent yet.
+    issued_at: Option<Instant>,
 }
 
 impl RequestTxHashes {
+    /// Returns a new [`RequestTxHashes`] for the given hashes, that hasn't been issued yet.
+    pub fn new(hashes: Vec<TxHash>) -> Self {
+        Self { hashes, issued_at: None }
+    }
+
     /// Returns a new [`RequestTxHashes`] with given capacity for hashes. Caution! Make sure to
     /// call [`Vec::shrink_to_fit`] on [`RequestTxHashes`] when full, especially where it will be
     /// stored in its entirety like in the future waiting for a
@@ -645,6 +1670,156 @@ impl RequestTxHashes {
     pub fn with_capacity(capacity: usize) -> Self {
         Self::new(Vec::with_capacity(capacity))
     }
+
+    /// Records that the request is sent to the peer now, see [`RequestTxHashes::is_timed_out`].
+    pub fn mark_issued(&mut self) {
+        self.issued_at = Some(Instant::now());
+    }
+
+    /// Returns when the request was sent to the peer, or `None` if it hasn't been sent yet.
+    pub fn issued_at(&self) -> Option<Instant> {
+        self.issued_at
+    }
+
+    /// Returns `true` if the request was sent to the peer more than `timeout` ago. A request that
+    /// hasn't been sent yet never times out.
+    pub fn is_timed_out(&self, timeout: Duration) -> bool {
+        self.issued_at.is_some_and(|issued_at| issued_at.elapsed() > timeout)
+    }
+
+    /// Returns the hashes that are not in `received`, e.g. the part of a
+    /// [`GetPooledTransactions`](crate::GetPooledTransactions) request that wasn't fulfilled
+    /// before it timed out, so they can be requested again.
+    pub fn remaining_after(&self, received: &[TxHash]) -> RequestTxHashes {
+        let received = received.iter().collect::<HashSet<_>>();
+        let mut hashes =
+            self.hashes.iter().filter(|hash| !received.contains(hash)).copied().collect::<Vec<_>>();
+        hashes.shrink_to_fit();
+
+        RequestTxHashes::new(hashes)
+    }
+
+    /// Keeps the hashes, in order, as long as the cumulative announced size of the kept hashes
+    /// doesn't exceed `max_bytes`, and returns the remaining hashes.
+    ///
+    /// Hashes that have no entry in `sizes`, e.g. because they were announced by an
+    /// [`EthVersion::Eth66`] peer, don't count towards the budget.
+    pub fn truncate_to_byte_budget(
+        &mut self,
+        sizes: &HashMap<TxHash, usize>,
+        max_bytes: usize,
+    ) -> RequestTxHashes {
+        let mut total = 0usize;
+        let keep = self
+            .hashes
+            .iter()
+            .take_while(|hash| {
+                total = total.saturating_add(sizes.get(*hash).copied().unwrap_or_default());
+                total <= max_bytes
+            })
+            .count();
+
+        RequestTxHashes::new(self.hashes.split_off(keep))
+    }
+
+    /// Merges the hashes of the given announcements into one request, without duplicates, in the
+    /// order they are first announced.
+    ///
+    /// Returns an error if there are no announcements, or if they aren't all of the same version,
+    /// since the version determines how the response is handled.
+    pub fn from_announcements(
+        announcements: impl IntoIterator<Item = ValidAnnouncementData>,
+    ) -> Result<(RequestTxHashes, EthVersion), BroadcastValidationError> {
+        let mut version = None;
+        let mut seen = HashSet::new();
+        let mut hashes = Vec::new();
+        for announcement in announcements {
+            let expected = *version.get_or_insert(announcement.version);
+            if announcement.version != expected {
+                return Err(BroadcastValidationError::MixedAnnouncementVersions(GotExpected {
+                    got: announcement.version,
+                    expected,
+                }))
+            }
+            hashes.extend(announcement.data.into_keys().filter(|hash| seen.insert(*hash)));
+        }
+        let version = version.ok_or(BroadcastValidationError::NoAnnouncements)?;
+        hashes.shrink_to_fit();
+
+        Ok((RequestTxHashes::new(hashes), version))
+    }
+}
+
+/// Tracks the number of announced entries per peer in a sliding window, to detect peers that spam
+/// announcements.
+#[derive(Debug, Clone)]
+pub struct AnnouncementRateTracker {
+    /// Length of the sliding window.
+    window: Duration,
+    /// Announced entry counts per peer, in the order they were recorded.
+    announcements: HashMap<PeerId, VecDeque<(Instant, usize)>>,
+}
+
+impl AnnouncementRateTracker {
+    /// Returns a new [`AnnouncementRateTracker`] with the given sliding window.
+    ///
+    /// # Panics
+    ///
+    /// If the window is zero.
+    pub fn new(window: Duration) -> Self {
+        assert!(!window.is_zero(), "announcement rate window must be non-zero");
+        Self { window, announcements: HashMap::default() }
+    }
+
+    /// Returns the length of the sliding window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Records an announcement received from the given peer now.
+    pub fn record(&mut self, peer: PeerId, announcement: &impl HandleAnnouncement) {
+        self.record_at(peer, announcement, Instant::now())
+    }
+
+    /// Records an announcement received from the given peer at the given time, and evicts the
+    /// peer's entries that dropped out of the window.
+    pub fn record_at(
+        &mut self,
+        peer: PeerId,
+        announcement: &impl HandleAnnouncement,
+        now: Instant,
+    ) {
+        let window = self.window;
+        let entries = self.announcements.entry(peer).or_default();
+        entries.push_back((now, announcement.len()));
+        while entries.front().is_some_and(|(at, _)| now.saturating_duration_since(*at) > window) {
+            entries.pop_front();
+        }
+    }
+
+    /// Returns the rate of announced entries of the given peer in entries per second, over the
+    /// window ending now.
+    pub fn rate(&self, peer: &PeerId) -> f64 {
+        self.rate_at(peer, Instant::now())
+    }
+
+    /// Returns the rate of announced entries of the given peer in entries per second, over the
+    /// window ending at the given time.
+    pub fn rate_at(&self, peer: &PeerId, now: Instant) -> f64 {
+        let Some(entries) = self.announcements.get(peer) else { return 0.0 };
+        let count = entries
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) <= self.window)
+            .map(|(_, len)| len)
+            .sum::<usize>();
+
+        count as f64 / self.window.as_secs_f64()
+    }
+
+    /// Stops tracking the given peer, e.g. on disconnect.
+    pub fn remove_peer(&mut self, peer: &PeerId) {
+        self.announcements.remove(peer);
+    }
 }
 
 impl FromIterator<(TxHash, Option<(u8, usize)>)> for RequestTxHashes {
@@ -664,10 +1839,79 @@ impl FromIterator<(TxHash, Option<(u8, usize)>)> for RequestTxHashes {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::bench_decode;
     use alloy_rlp::{Decodable, Encodable};
-    use bytes::BytesMut;
-    use reth_primitives::hex;
-    use std::str::FromStr;
+    use reth_primitives::{
+        constants::eip4844::DATA_GAS_PER_BLOB, hex, kzg::Bytes48, public_key_to_address,
+        sign_message, Header, Signature, Transaction, TransactionKind, TxEip4844, TxLegacy,
+    };
+    use std::{
+        str::FromStr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// Returns a signed legacy transaction with the given nonce.
+    fn legacy_tx(nonce: u64) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy {
+                chain_id: Some(1),
+                nonce,
+                gas_price: 1_000_000_000,
+                gas_limit: 21_000,
+                to: TransactionKind::Call(hex!("3535353535353535353535353535353535353535").into()),
+                value: 0u64.into(),
+                input: Default::default(),
+            }),
+            Signature::default(),
+        )
+    }
+
+    /// Returns a legacy transaction with the given nonce and gas price.
+    fn priced_tx(nonce: u64, gas_price: u128) -> TransactionSigned {
+        let mut tx = legacy_tx(nonce);
+        if let Transaction::Legacy(ref mut legacy) = tx.transaction {
+            legacy.gas_price = gas_price;
+        }
+        tx.hash = tx.recalculate_hash();
+        tx
+    }
+
+    /// Returns a new random signing key.
+    fn random_key() -> secp256k1::KeyPair {
+        secp256k1::KeyPair::new(&secp256k1::Secp256k1::new(), &mut rand::thread_rng())
+    }
+
+    /// Returns a legacy transaction with the given nonce, signed with the given key.
+    fn signed_by(key: &secp256k1::KeyPair, nonce: u64) -> TransactionSigned {
+        let mut tx = legacy_tx(nonce);
+        tx.signature =
+            sign_message(B256::from_slice(&key.secret_bytes()), tx.signature_hash()).unwrap();
+        tx.hash = tx.recalculate_hash();
+        tx
+    }
+
+    /// Returns a blob sidecar without blobs, but with a commitment and a proof.
+    fn test_sidecar() -> BlobTransactionSidecar {
+        let (commitment, proof) = (Bytes48::from([1; 48]), Bytes48::from([2; 48]));
+        BlobTransactionSidecar::new(vec![], vec![commitment], vec![proof])
+    }
+
+    /// Returns a signed blob transaction with the given nonce and versioned hashes.
+    fn blob_tx(nonce: u64, blob_versioned_hashes: Vec<B256>) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip4844(TxEip4844 {
+                chain_id: 1,
+                nonce,
+                gas_limit: 21_000,
+                max_fee_per_gas: 1_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+                max_fee_per_blob_gas: 1,
+                blob_versioned_hashes,
+                ..Default::default()
+            }),
+            Signature::default(),
+        )
+    }
 
     /// Takes as input a struct / encoded hex message pair, ensuring that we encode to the exact hex
     /// message, and decode to the exact struct.
@@ -816,4 +2060,1502 @@ mod tests {
             test_encoding_vector(vector);
         }
     }
+
+    #[test]
+    fn dedup_transactions_by_hash() {
+        let tx_a = legacy_tx(0);
+        let tx_b = legacy_tx(1);
+        let mut txs = Transactions(vec![tx_a.clone(), tx_b.clone(), tx_a.clone(), tx_a.clone()]);
+
+        assert_eq!(txs.dedup_by_hash(), 2);
+        assert_eq!(txs.0, vec![tx_a, tx_b]);
+
+        // no duplicates left
+        assert_eq!(txs.dedup_by_hash(), 0);
+    }
+
+    #[test]
+    fn into_pooled_attaches_sidecars() {
+        let legacy = legacy_tx(0);
+        let blob = blob_tx(1, vec![B256::random()]);
+        let blob_hash = blob.hash();
+        let txs = Transactions(vec![legacy.clone(), blob]);
+
+        let pooled = txs
+            .into_pooled(|hash| (*hash == blob_hash).then(BlobTransactionSidecar::default))
+            .unwrap();
+        assert_eq!(pooled.len(), 2);
+        assert_eq!(*pooled[0].hash(), legacy.hash());
+        assert!(matches!(pooled[1], PooledTransactionsElement::BlobTransaction(_)));
+        assert_eq!(*pooled[1].hash(), blob_hash);
+    }
+
+    #[test]
+    fn into_pooled_missing_sidecar() {
+        let blob = blob_tx(0, vec![B256::random()]);
+        let blob_hash = blob.hash();
+        let txs = Transactions(vec![legacy_tx(1), blob]);
+
+        assert_eq!(
+            txs.into_pooled(|_| None),
+            Err(BroadcastValidationError::MissingBlobSidecar(blob_hash))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_eth68_over_limit_sizes() {
+        use proptest::{
+            strategy::{Strategy, ValueTree},
+            test_runner::TestRunner,
+        };
+
+        let default_max = NewPooledTransactionHashes68Params::default().max_size;
+        let params = NewPooledTransactionHashes68Params { max_size: u32::MAX as usize };
+        let strategy = NewPooledTransactionHashes68::arbitrary_with(params);
+        let mut runner = TestRunner::deterministic();
+
+        let mut over_limit = false;
+        for _ in 0..10 {
+            let msg = strategy.new_tree(&mut runner).unwrap().current();
+            assert_eq!(msg.sizes.len(), msg.hashes.len());
+            assert!(msg.sizes.iter().all(|size| *size < params.max_size));
+            over_limit |= msg.sizes.iter().any(|size| *size >= default_max);
+        }
+        assert!(over_limit);
+    }
+
+    #[test]
+    fn shared_transactions_exclude_known() {
+        let txs: Vec<_> = (0..4).map(|nonce| Arc::new(legacy_tx(nonce))).collect();
+        let known = HashSet::from([txs[0].hash(), txs[2].hash(), B256::random()]);
+
+        let shared = SharedTransactions::exclude_known(&txs, &known);
+        assert_eq!(shared.0, vec![txs[1].clone(), txs[3].clone()]);
+        assert!(Arc::ptr_eq(&shared.0[0], &txs[1]));
+
+        let shared = SharedTransactions::exclude_known(&txs, &HashSet::new());
+        assert_eq!(shared.0, txs);
+    }
+
+    #[test]
+    fn eth68_size_by_type() {
+        let msg = NewPooledTransactionHashes68 {
+            types: vec![0, 2, 3, 2, 0],
+            sizes: vec![100, 200, 131072, 300, 50],
+            hashes: vec![B256::random(); 5],
+        };
+
+        let sizes = msg.size_by_type();
+        assert_eq!(sizes.len(), 3);
+        assert_eq!(sizes[&0], 150);
+        assert_eq!(sizes[&2], 500);
+        assert_eq!(sizes[&3], 131072);
+
+        assert!(NewPooledTransactionHashes68::default().size_by_type().is_empty());
+    }
+
+    #[test]
+    fn eth68_merge_by_size() {
+        let hashes = (1..=5).map(B256::with_last_byte).collect::<Vec<_>>();
+        let first = NewPooledTransactionHashes68 {
+            types: vec![2, 0, 3],
+            sizes: vec![300, 100, 131072],
+            hashes: hashes[..3].to_vec(),
+        };
+        let second = NewPooledTransactionHashes68 {
+            types: vec![2, 2, 0],
+            sizes: vec![50, 300, 200],
+            hashes: vec![hashes[3], hashes[0], hashes[4]],
+        };
+
+        let merged = merge_by_size(vec![first, second]);
+        assert!(merged.validate_aligned().is_ok());
+        assert_eq!(merged.sizes, vec![50, 100, 200, 300, 131072]);
+        assert_eq!(merged.types, vec![2, 0, 0, 2, 3]);
+        assert_eq!(merged.hashes, vec![hashes[3], hashes[1], hashes[4], hashes[0], hashes[2]]);
+        assert_eq!(merged.hashes.iter().collect::<HashSet<_>>().len(), merged.hashes.len());
+
+        assert_eq!(merge_by_size(vec![]), NewPooledTransactionHashes68::default());
+    }
+
+    #[test]
+    fn eth68_mean_size() {
+        let msg = NewPooledTransactionHashes68 {
+            types: vec![0, 2, 3, 2],
+            sizes: vec![100, 200, 131072, 300],
+            hashes: vec![B256::random(); 4],
+        };
+        assert_eq!(msg.mean_size(), Some(32918.0));
+
+        assert_eq!(NewPooledTransactionHashes68::default().mean_size(), None);
+    }
+
+    #[test]
+    fn new_block_extends_parent() {
+        let parent_hash = B256::random();
+        let mut block = NewBlock::default();
+        block.block.header.parent_hash = parent_hash;
+        block.block.header.number = 10;
+
+        assert!(block.extends(&parent_hash, 9));
+        assert!(!block.extends(&parent_hash, 10));
+        assert!(!block.extends(&B256::random(), 9));
+        assert!(!block.extends(&parent_hash, u64::MAX));
+    }
+
+    #[test]
+    fn request_tx_hashes_remaining_after() {
+        let hashes = (0..5).map(|_| B256::random()).collect::<Vec<_>>();
+        let request = RequestTxHashes::new(hashes.clone());
+
+        let remaining = request.remaining_after(&[hashes[3], hashes[0], B256::random()]);
+        assert_eq!(*remaining, vec![hashes[1], hashes[2], hashes[4]]);
+        assert_eq!(remaining.capacity(), remaining.len());
+
+        assert!(request.remaining_after(&hashes).is_empty());
+        assert_eq!(*request.remaining_after(&[]), hashes);
+    }
+
+    #[test]
+    fn request_tx_hashes_timeout() {
+        let timeout = Duration::from_secs(5);
+        let mut request = RequestTxHashes::new(vec![B256::random()]);
+        assert_eq!(request.issued_at(), None);
+        assert!(!request.is_timed_out(timeout));
+
+        request.mark_issued();
+        assert!(request.issued_at().is_some());
+        assert!(!request.is_timed_out(timeout));
+
+        // simulate that the request was issued a while ago, unless the monotonic clock started
+        // less than that ago, e.g. on a freshly booted machine
+        let Some(issued_at) = Instant::now().checked_sub(Duration::from_secs(6)) else { return };
+        request.issued_at = Some(issued_at);
+        assert!(request.is_timed_out(timeout));
+    }
+
+    #[test]
+    fn transactions_encode_into_reserves() {
+        let txs = Transactions((0..10).map(legacy_tx).collect());
+
+        let mut expected = BytesMut::new();
+        txs.encode(&mut expected);
+
+        let mut out = BytesMut::new();
+        txs.encode_into(&mut out);
+        assert_eq!(out, expected);
+        assert!(out.capacity() >= txs.length());
+
+        // appends to existing content
+        let mut out = BytesMut::from(&[0xffu8][..]);
+        txs.encode_into(&mut out);
+        assert_eq!(out[0], 0xff);
+        assert_eq!(out[1..], expected[..]);
+    }
+
+    #[test]
+    fn valid_announcement_data_counts_by_type() {
+        let data = HashMap::from([
+            (B256::random(), Some((0, 100))),
+            (B256::random(), Some((2, 200))),
+            (B256::random(), Some((3, 131072))),
+            (B256::random(), Some((2, 300))),
+            (B256::random(), Some((2, 50))),
+        ]);
+
+        let counts = ValidAnnouncementData::new_eth68(data.clone()).counts_by_type();
+        assert_eq!(counts, HashMap::from([(0, 1), (2, 3), (3, 1)]));
+
+        let data = data.into_keys().map(|hash| (hash, None)).collect();
+        assert!(ValidAnnouncementData::new_eth66(data).counts_by_type().is_empty());
+    }
+
+    #[test]
+    fn valid_announcement_data_request_hashes_in_size_band() {
+        let small = B256::with_last_byte(1);
+        let lower = B256::with_last_byte(2);
+        let medium = B256::with_last_byte(3);
+        let upper = B256::with_last_byte(4);
+        let large = B256::with_last_byte(5);
+        let data = HashMap::from([
+            (small, Some((0, 99))),
+            (lower, Some((2, 100))),
+            (medium, Some((2, 500))),
+            (upper, Some((3, 1000))),
+            (large, Some((3, 1001))),
+        ]);
+
+        let band =
+            ValidAnnouncementData::new_eth68(data.clone()).request_hashes_in_size_band(100, 1000);
+        let band = band.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(band, HashSet::from([lower, medium, upper]));
+
+        let data = data.into_keys().map(|hash| (hash, None)).collect();
+        let band = ValidAnnouncementData::new_eth66(data).request_hashes_in_size_band(100, 1000);
+        assert_eq!(band.len(), 5);
+    }
+
+    #[test]
+    fn announcement_rate() {
+        let mut tracker = AnnouncementRateTracker::new(Duration::from_secs(10));
+        let peer = PeerId::random();
+        let other = PeerId::random();
+        let start = Instant::now();
+
+        let announcement =
+            NewPooledTransactionHashes::Eth66(vec![B256::with_last_byte(1); 5].into());
+        tracker.record_at(peer, &announcement, start);
+        tracker.record_at(peer, &announcement, start + Duration::from_secs(4));
+        tracker.record_at(other, &announcement, start + Duration::from_secs(4));
+
+        assert_eq!(tracker.rate_at(&peer, start + Duration::from_secs(5)), 1.0);
+        assert_eq!(tracker.rate_at(&other, start + Duration::from_secs(5)), 0.5);
+        // the first announcement dropped out of the window
+        assert_eq!(tracker.rate_at(&peer, start + Duration::from_secs(11)), 0.5);
+        assert_eq!(tracker.rate_at(&PeerId::random(), start), 0.0);
+
+        tracker.remove_peer(&peer);
+        assert_eq!(tracker.rate_at(&peer, start + Duration::from_secs(5)), 0.0);
+    }
+
+    #[test]
+    fn announcement_rate_window_eviction() {
+        let mut tracker = AnnouncementRateTracker::new(Duration::from_secs(10));
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        let announcement =
+            NewPooledTransactionHashes::Eth66(vec![B256::with_last_byte(1); 10].into());
+        for secs in 0..5 {
+            tracker.record_at(peer, &announcement, start + Duration::from_secs(secs));
+        }
+        assert_eq!(tracker.announcements[&peer].len(), 5);
+
+        tracker.record_at(peer, &announcement, start + Duration::from_secs(13));
+        assert_eq!(tracker.announcements[&peer].len(), 3);
+        assert_eq!(tracker.rate_at(&peer, start + Duration::from_secs(13)), 3.0);
+    }
+
+    #[test]
+    fn new_block_hashes_partition_known() {
+        let blocks = (1..=4u8)
+            .map(|n| BlockHashNumber { hash: B256::with_last_byte(n), number: n as u64 })
+            .collect::<Vec<_>>();
+        let hashes = NewBlockHashes(blocks.clone());
+
+        // headers known to the provider
+        let headers = HashMap::from([(blocks[0].hash, 1u64), (blocks[2].hash, 3)]);
+        let (have, need) = hashes.partition_known(|hash| headers.contains_key(hash));
+        assert_eq!(have, NewBlockHashes(vec![blocks[0].clone(), blocks[2].clone()]));
+        assert_eq!(need, NewBlockHashes(vec![blocks[1].clone(), blocks[3].clone()]));
+
+        let (have, need) = hashes.partition_known(|_| false);
+        assert_eq!(have, NewBlockHashes::default());
+        assert_eq!(need, hashes);
+    }
+
+    #[test]
+    fn new_block_hashes_minus() {
+        let blocks = (1..=4u8)
+            .map(|n| BlockHashNumber { hash: B256::with_last_byte(n), number: n as u64 })
+            .collect::<Vec<_>>();
+        let hashes = NewBlockHashes(blocks.clone());
+
+        // no overlap
+        assert_eq!(hashes.minus(&HashSet::new()), hashes);
+
+        // partial overlap keeps the announcement order
+        let known = HashSet::from([B256::with_last_byte(1), B256::with_last_byte(3)]);
+        assert_eq!(
+            hashes.minus(&known),
+            NewBlockHashes(vec![blocks[1].clone(), blocks[3].clone()])
+        );
+
+        // full overlap
+        let known = blocks.iter().map(|block| block.hash).collect();
+        assert_eq!(hashes.minus(&known), NewBlockHashes::default());
+    }
+
+    #[test]
+    fn transactions_to_announcement() {
+        let txs = Transactions(vec![legacy_tx(0), blob_tx(1, vec![]), legacy_tx(2)]);
+        let hashes = txs.0.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+        let sidecar_lookup = |_: &TxHash| Some(test_sidecar());
+
+        for version in [EthVersion::Eth66, EthVersion::Eth67] {
+            assert_eq!(
+                txs.to_announcement(version, |_| None).unwrap(),
+                NewPooledTransactionHashes::Eth66(hashes.clone().into())
+            );
+        }
+
+        let NewPooledTransactionHashes::Eth68(msg) =
+            txs.to_announcement(EthVersion::Eth68, sidecar_lookup).unwrap()
+        else {
+            panic!("expected eth68 announcement")
+        };
+        assert_eq!(msg.hashes, hashes);
+        assert_eq!(msg.types, vec![0, 3, 0]);
+        let pooled = txs.clone().into_pooled(sidecar_lookup).unwrap();
+        for (tx, size) in pooled.iter().zip(msg.sizes) {
+            assert_eq!(size, tx.length_without_header());
+        }
+
+        // blob transactions can't be announced without their sidecar
+        assert!(matches!(
+            txs.to_announcement(EthVersion::Eth68, |_| None),
+            Err(BroadcastValidationError::MissingBlobSidecar(hash)) if hash == hashes[1]
+        ));
+    }
+
+    #[test]
+    fn transactions_versioned_hashes() {
+        let hashes = vec![B256::with_last_byte(1), B256::with_last_byte(2)];
+        let mut txs = Transactions(vec![legacy_tx(0), blob_tx(1, hashes.clone())]);
+        assert_eq!(txs.versioned_hashes(), hashes);
+
+        txs.0.push(blob_tx(2, vec![B256::with_last_byte(3)]));
+        assert_eq!(txs.versioned_hashes().len(), 3);
+        assert_eq!(txs.versioned_hashes()[2], B256::with_last_byte(3));
+
+        assert!(Transactions(vec![legacy_tx(0)]).versioned_hashes().is_empty());
+    }
+
+    #[test]
+    fn transactions_prepare_for_broadcast() {
+        let blobs = [blob_tx(1, vec![B256::with_last_byte(1)]), blob_tx(3, vec![])];
+        let txs = Transactions(vec![
+            legacy_tx(0),
+            blobs[0].clone(),
+            legacy_tx(2),
+            blobs[1].clone(),
+        ]);
+        let blob_hashes = blobs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+
+        let (full, announcement) =
+            txs.clone().prepare_for_broadcast(EthVersion::Eth68, |_| Some(test_sidecar())).unwrap();
+        assert_eq!(full, Transactions(vec![legacy_tx(0), legacy_tx(2)]));
+        assert!(!full.has_eip4844());
+        let NewPooledTransactionHashes::Eth68(msg) = announcement else {
+            panic!("expected eth68 announcement")
+        };
+        assert_eq!(msg.hashes, blob_hashes);
+        assert_eq!(msg.types, vec![EIP4844_TX_TYPE_ID; 2]);
+
+        // the announced sizes are those of the pooled encoding, which includes the sidecar
+        for (tx, size) in blobs.iter().zip(msg.sizes) {
+            let pooled =
+                PooledTransactionsElement::try_from_blob_transaction(tx.clone(), test_sidecar())
+                    .unwrap();
+            assert_eq!(size, pooled.length_without_header());
+            assert!(size > tx.length_without_header());
+        }
+
+        let (full, announcement) = txs.prepare_for_broadcast(EthVersion::Eth66, |_| None).unwrap();
+        assert_eq!(full, Transactions(vec![legacy_tx(0), legacy_tx(2)]));
+        assert_eq!(announcement, NewPooledTransactionHashes::Eth66(blob_hashes.into()));
+
+        let (full, announcement) = Transactions(vec![legacy_tx(0)])
+            .prepare_for_broadcast(EthVersion::Eth68, |_| None)
+            .unwrap();
+        assert_eq!(full.0.len(), 1);
+        assert!(announcement.is_empty());
+    }
+
+    #[test]
+    fn transactions_largest() {
+        assert_eq!(Transactions::default().largest(), None);
+
+        let mut large = legacy_tx(1).transaction;
+        if let Transaction::Legacy(tx) = &mut large {
+            tx.input = vec![1; 1024].into();
+        }
+        let large = TransactionSigned::from_transaction_and_signature(large, Signature::default());
+        let txs = Transactions(vec![
+            legacy_tx(0),
+            blob_tx(2, vec![B256::with_last_byte(1); 4]),
+            large.clone(),
+            legacy_tx(3),
+        ]);
+
+        let (tx, size) = txs.largest().unwrap();
+        assert_eq!(tx, &large);
+        assert_eq!(size, large.length());
+        assert!(txs.0.iter().all(|tx| tx.length() <= size));
+
+        // the first of equally sized transactions
+        let txs = Transactions(vec![legacy_tx(0), legacy_tx(1)]);
+        assert_eq!(txs.largest().unwrap().0, &txs.0[0]);
+    }
+
+    #[test]
+    fn transactions_set_hash() {
+        let txs = Transactions(vec![legacy_tx(0), legacy_tx(1), legacy_tx(2)]);
+        let reordered = Transactions(vec![legacy_tx(2), legacy_tx(0), legacy_tx(1)]);
+        assert_eq!(txs.set_hash(), reordered.set_hash());
+
+        let duplicated = Transactions(vec![legacy_tx(1), legacy_tx(0), legacy_tx(2), legacy_tx(0)]);
+        assert_eq!(txs.set_hash(), duplicated.set_hash());
+
+        let other = Transactions(vec![legacy_tx(0), legacy_tx(1)]);
+        assert_ne!(txs.set_hash(), other.set_hash());
+    }
+
+    #[test]
+    fn transactions_recover_senders() {
+        let signed_tx = |nonce| {
+            let key = random_key();
+            (signed_by(&key, nonce), public_key_to_address(key.public_key()))
+        };
+
+        // sequential and parallel recovery
+        for len in [3, PARALLEL_SENDER_RECOVERY_THRESHOLD + 1] {
+            let (mut txs, mut senders): (Vec<_>, Vec<_>) =
+                (0..len as u64).map(signed_tx).map(|(tx, sender)| (tx, Some(sender))).unzip();
+            txs[1] = legacy_tx(1);
+            senders[1] = None;
+
+            assert_eq!(Transactions(txs).recover_senders(), senders);
+        }
+        assert!(Transactions::default().recover_senders().is_empty());
+    }
+
+    #[test]
+    fn transactions_sort_by_sender_nonce() {
+        let (alice, bob) = (random_key(), random_key());
+
+        let mut txs = Transactions(vec![
+            signed_by(&bob, 2),
+            signed_by(&alice, 1),
+            signed_by(&bob, 0),
+            signed_by(&alice, 0),
+            signed_by(&bob, 1),
+            signed_by(&alice, 2),
+        ]);
+        txs.sort_by_sender_nonce();
+
+        let sorted = txs
+            .recover_senders()
+            .into_iter()
+            .zip(txs.0.iter().map(|tx| tx.nonce()))
+            .collect::<Vec<_>>();
+        let bob = Some(public_key_to_address(bob.public_key()));
+        let alice = Some(public_key_to_address(alice.public_key()));
+        assert_eq!(sorted, vec![(bob, 0), (bob, 1), (bob, 2), (alice, 0), (alice, 1), (alice, 2)]);
+    }
+
+    #[test]
+    fn transactions_cap_per_sender() {
+        let (alice, bob) = (random_key(), random_key());
+
+        let mut txs = Transactions(vec![
+            signed_by(&bob, 3),
+            signed_by(&alice, 0),
+            signed_by(&bob, 1),
+            signed_by(&bob, 0),
+            signed_by(&bob, 2),
+        ]);
+        let removed = txs.cap_per_sender(2);
+
+        assert_eq!(txs.0, vec![signed_by(&alice, 0), signed_by(&bob, 1), signed_by(&bob, 0)]);
+        assert_eq!(removed.0, vec![signed_by(&bob, 3), signed_by(&bob, 2)]);
+
+        assert_eq!(txs.cap_per_sender(0).0.len(), 3);
+        assert!(txs.0.is_empty());
+    }
+
+    #[test]
+    fn transactions_retain_min_tip() {
+        let base_fee = 1_000_000_000;
+        let above = priced_tx(0, 3_000_000_000);
+        let at = priced_tx(1, 2_000_000_000);
+        let below = priced_tx(2, 1_500_000_000);
+        let underpriced = priced_tx(3, 500_000_000);
+
+        let mut txs =
+            Transactions(vec![above.clone(), below.clone(), at.clone(), underpriced.clone()]);
+        let removed = txs.retain_min_tip(base_fee, 1_000_000_000);
+        assert_eq!(txs, Transactions(vec![above, at]));
+        assert_eq!(removed, Transactions(vec![below, underpriced]));
+    }
+
+    #[test]
+    fn transactions_retain_under_block_gas() {
+        let limited_tx = |nonce, gas_limit| {
+            let mut tx = legacy_tx(nonce);
+            if let Transaction::Legacy(ref mut legacy) = tx.transaction {
+                legacy.gas_limit = gas_limit;
+            }
+            tx.hash = tx.recalculate_hash();
+            tx
+        };
+        let block_gas_limit = 30_000_000;
+        let small = limited_tx(0, 21_000);
+        let full = limited_tx(1, block_gas_limit);
+        let over = limited_tx(2, block_gas_limit + 1);
+
+        let mut txs = Transactions(vec![small.clone(), over.clone(), full.clone()]);
+        let removed = txs.retain_under_block_gas(block_gas_limit);
+        assert_eq!(txs, Transactions(vec![small, full]));
+        assert_eq!(removed, Transactions(vec![over]));
+    }
+
+    #[test]
+    fn transactions_broadcast_plan() {
+        let base_fee = 1_000_000_000;
+        let highest = priced_tx(0, 5_000_000_000);
+        let high = priced_tx(1, 3_000_000_000);
+        let low = priced_tx(2, 2_000_000_000);
+        let underpriced = priced_tx(3, 500_000_000);
+
+        let txs =
+            Transactions(vec![low.clone(), underpriced.clone(), highest.clone(), high.clone()]);
+        let plan = txs.broadcast_plan(base_fee, &[4, 2, 1]);
+        assert_eq!(
+            plan,
+            vec![
+                Transactions(vec![highest.clone(), high.clone(), low, underpriced.clone()]),
+                Transactions(vec![highest.clone(), high]),
+                Transactions(vec![highest.clone()]),
+            ]
+        );
+
+        let tiers_with =
+            |tx: &TransactionSigned| plan.iter().filter(|tier| tier.0.contains(tx)).count();
+        assert_eq!(tiers_with(&highest), plan.len());
+        assert_eq!(tiers_with(&underpriced), 1);
+    }
+
+    #[test]
+    fn transactions_weighted_sample() {
+        let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(1);
+        let small = legacy_tx(0);
+        let mut large = legacy_tx(1);
+        if let Transaction::Legacy(ref mut legacy) = large.transaction {
+            legacy.input = vec![0xff; 10 * small.length()].into();
+        }
+        large.hash = large.recalculate_hash();
+        let txs = Transactions(vec![small.clone(), large.clone()]);
+
+        let mut large_count = 0;
+        for _ in 0..1000 {
+            let sample = txs.weighted_sample(1, &mut rng);
+            assert_eq!(sample.len(), 1);
+            if sample[0] == &large {
+                large_count += 1;
+            }
+        }
+        // the large transaction is about 11 times the size of the small one
+        assert!(large_count > 800, "large transaction sampled {large_count} times");
+
+        let mut all = txs.weighted_sample(3, &mut rng);
+        all.sort_by_key(|tx| tx.nonce());
+        assert_eq!(all, vec![&small, &large]);
+        assert!(Transactions::default().weighted_sample(1, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn transactions_total_blob_gas() {
+        assert_eq!(Transactions::default().total_blob_gas(), 0);
+        assert_eq!(Transactions(vec![legacy_tx(0)]).total_blob_gas(), 0);
+
+        let txs = Transactions(vec![
+            blob_tx(0, vec![B256::with_last_byte(1); 2]),
+            legacy_tx(1),
+            blob_tx(2, vec![B256::with_last_byte(2); 3]),
+        ]);
+        assert_eq!(txs.total_blob_gas(), 5 * DATA_GAS_PER_BLOB);
+    }
+
+    #[test]
+    fn transactions_retain_not_recent() {
+        let mut recent = LruCache::new(std::num::NonZeroUsize::new(8).unwrap());
+        let txs = Transactions((0..3).map(legacy_tx).collect());
+
+        // the first broadcast is kept, except for duplicates
+        let mut first = txs.clone();
+        first.0.push(legacy_tx(0));
+        let removed = first.retain_not_recent(&mut recent);
+        assert_eq!(first, txs);
+        assert_eq!(removed, Transactions(vec![legacy_tx(0)]));
+        assert_eq!(recent.len(), 3);
+
+        // a second broadcast of the same set is fully suppressed
+        let mut second = txs.clone();
+        let removed = second.retain_not_recent(&mut recent);
+        assert!(second.0.is_empty());
+        assert_eq!(removed, txs);
+    }
+
+    #[test]
+    fn transactions_retain_valid() {
+        let with_legacy = |nonce, f: fn(&mut TxLegacy)| {
+            let mut tx = legacy_tx(nonce);
+            if let Transaction::Legacy(ref mut legacy) = tx.transaction {
+                f(legacy);
+            }
+            tx.hash = tx.recalculate_hash();
+            tx
+        };
+        let valid = legacy_tx(0);
+        let pre_eip155 = with_legacy(1, |tx| tx.chain_id = None);
+        let wrong_chain = with_legacy(2, |tx| tx.chain_id = Some(5));
+        let low_gas = with_legacy(3, |tx| tx.gas_limit = 20_999);
+        let blob = blob_tx(4, vec![B256::with_last_byte(1)]);
+        let empty_blob = blob_tx(5, vec![]);
+        let mut tip_above_max_fee = blob_tx(6, vec![B256::with_last_byte(1)]);
+        if let Transaction::Eip4844(ref mut tx) = tip_above_max_fee.transaction {
+            tx.max_priority_fee_per_gas = tx.max_fee_per_gas + 1;
+        }
+
+        let mut txs = Transactions(vec![
+            valid.clone(),
+            wrong_chain.clone(),
+            pre_eip155.clone(),
+            low_gas.clone(),
+            blob.clone(),
+            empty_blob.clone(),
+            tip_above_max_fee.clone(),
+        ]);
+        let removed = txs.retain_valid(1);
+        assert_eq!(txs, Transactions(vec![valid, pre_eip155, blob]));
+        assert_eq!(
+            removed,
+            Transactions(vec![wrong_chain, low_gas, empty_blob, tip_above_max_fee])
+        );
+    }
+
+    #[test]
+    fn transactions_summary() {
+        assert_eq!(Transactions::default().summary(), TransactionsSummary::default());
+
+        let blob = blob_tx(1, vec![B256::with_last_byte(1); 4]);
+        let txs = Transactions(vec![legacy_tx(0), blob.clone(), legacy_tx(2)]);
+        let legacy_size = legacy_tx(0).length();
+
+        let summary = txs.summary();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.total_bytes, 2 * legacy_size + blob.length());
+        assert_eq!(summary.type_counts, HashMap::from([(TxType::Legacy, 2), (TxType::Eip4844, 1)]));
+        assert_eq!(summary.largest_bytes, blob.length());
+        assert!(blob.length() > legacy_size);
+    }
+
+    #[test]
+    fn new_block_commitment() {
+        let mut block = NewBlock::default();
+        block.block.header.number = 10;
+        block.td = U128::from(100);
+
+        assert_eq!(block.commitment(), block.clone().commitment());
+
+        let mut other = block.clone();
+        other.td = U128::from(101);
+        assert_ne!(block.commitment(), other.commitment());
+
+        let mut other = block.clone();
+        other.block.header.number = 11;
+        assert_ne!(block.commitment(), other.commitment());
+
+        // the commitment doesn't depend on the body
+        let mut other = block.clone();
+        other.block.body.push(legacy_tx(0));
+        assert_eq!(block.commitment(), other.commitment());
+    }
+
+    #[test]
+    fn drain_smallest_announced() {
+        let hash = B256::with_last_byte;
+        let mut data = ValidAnnouncementData::new_eth68(HashMap::from([
+            (hash(1), Some((2, 300))),
+            (hash(2), None),
+            (hash(3), Some((0, 100))),
+            (hash(4), Some((3, 200))),
+            (hash(5), Some((2, 100))),
+        ]));
+
+        assert_eq!(
+            data.drain_smallest(3),
+            vec![(hash(3), Some((0, 100))), (hash(5), Some((2, 100))), (hash(4), Some((3, 200)))]
+        );
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.data, HashMap::from([(hash(1), Some((2, 300))), (hash(2), None)]));
+
+        assert_eq!(data.drain_smallest(5), vec![(hash(1), Some((2, 300))), (hash(2), None)]);
+        assert!(data.is_empty());
+        assert!(data.drain_smallest(1).is_empty());
+    }
+
+    #[test]
+    fn retain_unknown_hashes() {
+        let hashes = (1..=4).map(B256::with_last_byte).collect::<Vec<_>>();
+        let known = HashSet::from([hashes[0], hashes[2], B256::with_last_byte(5)]);
+
+        let mut msg = NewPooledTransactionHashes::Eth66(hashes.clone().into());
+        msg.retain_unknown(&known);
+        assert_eq!(msg, NewPooledTransactionHashes::Eth66(vec![hashes[1], hashes[3]].into()));
+
+        let mut msg = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0, 1, 2, 3],
+            sizes: vec![10, 20, 30, 40],
+            hashes: hashes.clone(),
+        });
+        msg.retain_unknown(&known);
+        assert_eq!(
+            msg,
+            NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+                types: vec![1, 3],
+                sizes: vec![20, 40],
+                hashes: vec![hashes[1], hashes[3]],
+            })
+        );
+
+        msg.retain_unknown(&HashSet::new());
+        assert_eq!(msg.len(), 2);
+    }
+
+    #[test]
+    fn retain_not_in_flight_hashes() {
+        let hashes = (1..=3).map(B256::with_last_byte).collect::<Vec<_>>();
+        let in_flight = HashSet::from([hashes[1]]);
+
+        let mut msg = NewPooledTransactionHashes::Eth66(hashes.clone().into());
+        msg.retain_not_in_flight(&in_flight);
+        assert_eq!(msg, NewPooledTransactionHashes::Eth66(vec![hashes[0], hashes[2]].into()));
+
+        let mut msg = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0, 2, 3],
+            sizes: vec![10, 20, 30],
+            hashes: hashes.clone(),
+        });
+        msg.retain_not_in_flight(&in_flight);
+        assert_eq!(
+            msg,
+            NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+                types: vec![0, 3],
+                sizes: vec![10, 30],
+                hashes: vec![hashes[0], hashes[2]],
+            })
+        );
+    }
+
+    #[test]
+    fn announcement_known_bitset() {
+        let hashes = (1..=5).map(B256::with_last_byte).collect::<Vec<_>>();
+        let known = HashSet::from([hashes[0], hashes[3], hashes[4], B256::with_last_byte(6)]);
+        let mut expected = BitVec::repeat(false, hashes.len());
+        for i in [0, 3, 4] {
+            expected.set(i, true);
+        }
+
+        let msg = NewPooledTransactionHashes::Eth66(hashes.clone().into());
+        assert_eq!(msg.known_bitset(&known), expected);
+
+        let msg = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0; 5],
+            sizes: vec![10; 5],
+            hashes,
+        });
+        assert_eq!(msg.known_bitset(&known), expected);
+
+        assert!(msg.known_bitset(&HashSet::new()).not_any());
+    }
+
+    #[test]
+    fn valid_announcement_data_known_bitset() {
+        // inserted out of order, the bits follow the hashes in ascending order
+        let data = [5, 2, 4, 1, 3]
+            .map(|byte| (B256::with_last_byte(byte), None))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let announcement = ValidAnnouncementData::new(data, EthVersion::Eth66);
+
+        let known = HashSet::from([B256::with_last_byte(2), B256::with_last_byte(5)]);
+        let mut expected = BitVec::repeat(false, 5);
+        expected.set(1, true);
+        expected.set(4, true);
+        assert_eq!(announcement.known_bitset(&known), expected);
+
+        assert!(announcement.known_bitset(&HashSet::new()).not_any());
+    }
+
+    #[test]
+    fn default_known_bitset() {
+        /// Announcement that only implements the required methods.
+        #[derive(Clone)]
+        struct Hashes(Vec<TxHash>);
+
+        impl HandleAnnouncement for Hashes {
+            fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            fn retain_by_hash(&mut self, mut f: impl FnMut(&TxHash) -> bool) -> Self {
+                let (keep, rest) = self.0.drain(..).partition(|hash| f(hash));
+                self.0 = keep;
+                Self(rest)
+            }
+
+            fn msg_version(&self) -> EthVersion {
+                EthVersion::Eth66
+            }
+        }
+
+        let hashes = (1..=5).map(B256::with_last_byte).collect::<Vec<_>>();
+        let known = HashSet::from([hashes[1], hashes[2], B256::with_last_byte(6)]);
+        let announcement = Hashes(hashes.clone());
+
+        let mut expected = BitVec::repeat(false, hashes.len());
+        expected.set(1, true);
+        expected.set(2, true);
+        assert_eq!(announcement.known_bitset(&known), expected);
+        // the announcement is left untouched
+        assert_eq!(announcement.0, hashes);
+    }
+
+    #[test]
+    fn announcement_difference() {
+        let hashes = (1..=4).map(B256::with_last_byte).collect::<Vec<_>>();
+        let prev = HashSet::from([hashes[0], hashes[2], B256::with_last_byte(5)]);
+
+        let msg = NewPooledTransactionHashes::Eth66(hashes.clone().into());
+        assert_eq!(
+            msg.difference(&prev),
+            NewPooledTransactionHashes::Eth66(vec![hashes[1], hashes[3]].into())
+        );
+
+        let msg = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0, 1, 2, 3],
+            sizes: vec![10, 20, 30, 40],
+            hashes: hashes.clone(),
+        });
+        let diff = msg.difference(&prev);
+        assert_eq!(
+            diff,
+            NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+                types: vec![1, 3],
+                sizes: vec![20, 40],
+                hashes: vec![hashes[1], hashes[3]],
+            })
+        );
+        assert!(diff.as_eth68().unwrap().validate_aligned().is_ok());
+
+        assert_eq!(msg.difference(&HashSet::new()), msg);
+        assert!(msg.difference(&hashes.iter().copied().collect()).is_empty());
+    }
+
+    #[test]
+    fn new_block_hashes_request_bytes() {
+        for len in [0, 1, 2, 100, 1024] {
+            let hashes = NewBlockHashes(
+                (0..len)
+                    .map(|n| BlockHashNumber { hash: B256::with_last_byte(n as u8), number: n })
+                    .collect(),
+            );
+            let request = GetBlockBodies(hashes.0.iter().map(|block| block.hash).collect());
+            assert_eq!(hashes.estimated_request_bytes(), alloy_rlp::encode(&request).len());
+        }
+    }
+
+    #[test]
+    fn new_block_hashes_number_entropy() {
+        let announcement = |numbers: &[u64]| {
+            NewBlockHashes(
+                numbers
+                    .iter()
+                    .map(|number| BlockHashNumber { hash: B256::random(), number: *number })
+                    .collect(),
+            )
+        };
+
+        let uniform = announcement(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let skewed = announcement(&[1, 1, 1, 1, 1, 1, 1, 2]);
+        assert_eq!(uniform.number_entropy(), 3.0);
+        assert!(skewed.number_entropy() > 0.0);
+        assert!(uniform.number_entropy() > skewed.number_entropy());
+
+        assert_eq!(announcement(&[1; 8]).number_entropy(), 0.0);
+        assert_eq!(NewBlockHashes::default().number_entropy(), 0.0);
+    }
+
+    #[test]
+    fn new_block_hashes_to_get_block_bodies() {
+        let hashes = NewBlockHashes(
+            [(3, 7), (1, 5), (2, 6)]
+                .into_iter()
+                .map(|(n, number)| BlockHashNumber { hash: B256::with_last_byte(n), number })
+                .collect(),
+        );
+        let request = hashes.to_get_block_bodies();
+        assert_eq!(request.0, hashes.0.iter().map(|block| block.hash).collect::<Vec<_>>());
+        assert!(NewBlockHashes::default().to_get_block_bodies().0.is_empty());
+    }
+
+    #[test]
+    fn validate_aligned_eth68() {
+        let mut msg = NewPooledTransactionHashes68 {
+            types: vec![0x02, 0x03],
+            sizes: vec![100, 131_072],
+            hashes: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+        };
+        assert!(msg.validate_aligned().is_ok());
+
+        msg.sizes.pop();
+        assert_eq!(
+            msg.validate_aligned(),
+            Err(DecodeAnnouncementError::OddListLength {
+                list: Eth68List::Sizes,
+                len: GotExpected { got: 1, expected: 2 }
+            })
+        );
+
+        msg.types.push(0x02);
+        assert_eq!(
+            msg.validate_aligned(),
+            Err(DecodeAnnouncementError::ListLengthMismatch(GotExpected { got: 3, expected: 2 }))
+        );
+    }
+
+    #[test]
+    fn misaligned_eth68_is_not_valid_for_version() {
+        let mut msg = NewPooledTransactionHashes68 {
+            types: vec![0x02, 0x03],
+            sizes: vec![100, 131_072],
+            hashes: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+        };
+        let announcement = NewPooledTransactionHashes::Eth68(msg.clone());
+        assert!(announcement.is_valid_for_version(EthVersion::Eth68));
+
+        msg.types.pop();
+        assert!(!NewPooledTransactionHashes::Eth68(msg).is_valid_for_version(EthVersion::Eth68));
+    }
+
+    #[test]
+    fn bench_decode_decodes_every_iteration() {
+        static DECODES: AtomicUsize = AtomicUsize::new(0);
+
+        /// Decodes an eth68 announcement and counts how often it's decoded.
+        struct CountingDecode;
+
+        impl Decodable for CountingDecode {
+            fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+                DECODES.fetch_add(1, Ordering::Relaxed);
+                NewPooledTransactionHashes68::decode(buf)?;
+                Ok(Self)
+            }
+        }
+
+        let msg = NewPooledTransactionHashes68 {
+            types: vec![0x02; 3],
+            sizes: vec![131_072; 3],
+            hashes: vec![B256::ZERO; 3],
+        };
+        bench_decode::<CountingDecode>(&alloy_rlp::encode(&msg), 7);
+        assert_eq!(DECODES.load(Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to decode message")]
+    fn bench_decode_panics_on_invalid_bytes() {
+        bench_decode::<NewPooledTransactionHashes68>(&[0xc0, 0x01], 1);
+    }
+
+    /// Encodes an eth68 announcement without checking that its lists are aligned.
+    fn encode_unaligned_eth68(types: Vec<u8>, sizes: Vec<usize>, hashes: Vec<B256>) -> Vec<u8> {
+        #[derive(RlpEncodable)]
+        struct Unaligned {
+            types: Bytes,
+            sizes: Vec<usize>,
+            hashes: Vec<B256>,
+        }
+
+        let mut buf = Vec::new();
+        Unaligned { types: types.into(), sizes, hashes }.encode(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn decode_eth68_reports_odd_list() {
+        let (a, b) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let cases = [
+            (vec![0x02, 0x02, 0x03], vec![100, 200], vec![a, b], Eth68List::Types, 3, 2),
+            (vec![0x02, 0x02], vec![100], vec![a, b], Eth68List::Sizes, 1, 2),
+            (vec![0x02, 0x02, 0x03], vec![100, 200, 300], vec![a, b], Eth68List::Hashes, 2, 3),
+        ];
+
+        for (types, sizes, hashes, odd, got, expected) in cases {
+            let encoded = encode_unaligned_eth68(types, sizes, hashes);
+            assert_eq!(
+                NewPooledTransactionHashes68::decode_aligned(&mut &encoded[..]),
+                Err(DecodeAnnouncementError::OddListLength {
+                    list: odd,
+                    len: GotExpected { got, expected }
+                })
+            );
+            assert_eq!(
+                NewPooledTransactionHashes68::decode(&mut &encoded[..]),
+                Err(alloy_rlp::Error::ListLengthMismatch { expected, got })
+            );
+
+            let lenient = NewPooledTransactionHashes68::decode_lenient(&mut &encoded[..]).unwrap();
+            assert!(lenient.validate_aligned().is_ok());
+            let len = if odd == Eth68List::Sizes { 1 } else { 2 };
+            assert_eq!(lenient.hashes, vec![a, b][..len]);
+            assert_eq!(lenient.types, vec![0x02; len]);
+        }
+
+        // no single odd list
+        let encoded = encode_unaligned_eth68(vec![0x02], vec![100, 200], vec![a, b, a]);
+        assert_eq!(
+            NewPooledTransactionHashes68::decode(&mut &encoded[..]),
+            Err(alloy_rlp::Error::ListLengthMismatch { expected: 3, got: 1 })
+        );
+        let lenient = NewPooledTransactionHashes68::decode_lenient(&mut &encoded[..]).unwrap();
+        assert_eq!(lenient.hashes, vec![a]);
+    }
+
+    #[test]
+    fn total_gas_limit_widens() {
+        let huge = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy { gas_limit: u64::MAX, ..Default::default() }),
+            Signature::default(),
+        );
+        let txs = Transactions(vec![huge.clone(), huge, legacy_tx(0)]);
+        assert_eq!(txs.total_gas_limit(), 2 * u64::MAX as u128 + 21_000);
+        assert_eq!(Transactions::default().total_gas_limit(), 0);
+    }
+
+    #[test]
+    fn validate_new_block_transactions_root() {
+        let mut block = NewBlock::default();
+        block.block.body = vec![legacy_tx(0), legacy_tx(1)];
+        block.block.header.transactions_root = calculate_transaction_root(&block.block.body);
+        assert_eq!(block.validate_transactions_root(), Ok(()));
+
+        let expected = block.block.header.transactions_root;
+        block.block.body.pop();
+        let got = calculate_transaction_root(&block.block.body);
+        assert_eq!(
+            block.validate_transactions_root(),
+            Err(BroadcastValidationError::TransactionsRootMismatch(GotExpected { got, expected }))
+        );
+    }
+
+    #[test]
+    fn validate_new_block_post_merge_ommers() {
+        let mut block = NewBlock::default();
+        block.block.header.ommers_hash = EMPTY_OMMER_ROOT_HASH;
+        assert_eq!(block.validate_post_merge_ommers(), Ok(()));
+
+        let mut with_ommers = block.clone();
+        with_ommers.block.ommers = vec![Header::default(), Header::default()];
+        assert_eq!(
+            with_ommers.validate_post_merge_ommers(),
+            Err(BroadcastValidationError::NonEmptyOmmers(2))
+        );
+
+        block.block.header.ommers_hash = B256::with_last_byte(1);
+        assert_eq!(
+            block.validate_post_merge_ommers(),
+            Err(BroadcastValidationError::OmmersHashMismatch(GotExpected {
+                got: B256::with_last_byte(1),
+                expected: EMPTY_OMMER_ROOT_HASH
+            }))
+        );
+    }
+
+    #[test]
+    fn validate_new_block_base_fee() {
+        // the parent used more than the target of 15M gas
+        let validate =
+            |block: &NewBlock| block.validate_base_fee(1_000_000_000, 20_000_000, 30_000_000);
+        let mut block = NewBlock::default();
+        assert_eq!(validate(&block), Err(BroadcastValidationError::MissingBaseFee));
+
+        block.block.header.base_fee_per_gas = Some(1_041_666_666);
+        assert_eq!(validate(&block), Ok(()));
+
+        block.block.header.base_fee_per_gas = Some(1_000_000_000);
+        assert_eq!(
+            validate(&block),
+            Err(BroadcastValidationError::BaseFeeMismatch(GotExpected {
+                got: 1_000_000_000,
+                expected: 1_041_666_666
+            }))
+        );
+    }
+
+    #[test]
+    fn new_block_hash_announcement() {
+        let mut block = NewBlock::default();
+        block.block.header.number = 42;
+        block.block.body = vec![legacy_tx(0)];
+
+        let announcement = block.to_hash_announcement();
+        assert_eq!(
+            announcement,
+            NewBlockHashes(vec![BlockHashNumber {
+                hash: block.block.header.hash_slow(),
+                number: 42
+            }])
+        );
+        assert_eq!(announcement.latest().unwrap().hash, block.block.clone().seal_slow().hash());
+    }
+
+    #[test]
+    fn new_block_encoded_size() {
+        let mut block = NewBlock::default();
+        block.block.header.number = 42;
+        block.block.body = vec![legacy_tx(0), legacy_tx(1)];
+        block.td = U128::from(1_000_000_000u64);
+
+        let mut buf = Vec::new();
+        block.encode(&mut buf);
+        assert_eq!(block.encoded_size(), buf.len());
+        assert_eq!(NewBlock::decode(&mut buf.as_slice()).unwrap(), block);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn valid_announcement_data_serde_roundtrip() {
+        let eth68 = ValidAnnouncementData::new_eth68(HashMap::from([
+            (B256::with_last_byte(1), Some((0x02, 100))),
+            (B256::with_last_byte(2), Some((0x03, 131_072))),
+        ]));
+        let eth66 =
+            ValidAnnouncementData::new_eth66(HashMap::from([(B256::with_last_byte(3), None)]));
+
+        for data in [eth68, eth66] {
+            let json = serde_json::to_string(&data).unwrap();
+            let decoded: ValidAnnouncementData = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded.msg_version(), data.msg_version());
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    fn request_hashes_byte_budget() {
+        let hashes = (0..5).map(B256::with_last_byte).collect::<Vec<_>>();
+        let sizes = HashMap::from([
+            (hashes[0], 40_000),
+            (hashes[1], 50_000),
+            (hashes[2], 30_000),
+            (hashes[4], 10_000),
+        ]);
+
+        let mut request = RequestTxHashes::new(hashes.clone());
+        let overflow = request.truncate_to_byte_budget(&sizes, 100_000);
+
+        assert_eq!(*request, hashes[..2]);
+        assert!(request.iter().map(|hash| sizes[hash]).sum::<usize>() <= 100_000);
+        assert_eq!(*overflow, hashes[2..]);
+
+        // hashes of unknown size don't count towards the budget
+        let mut request = RequestTxHashes::new(hashes[3..].to_vec());
+        assert!(request.truncate_to_byte_budget(&sizes, 10_000).is_empty());
+        assert_eq!(request.len(), 2);
+    }
+
+    #[test]
+    fn request_hashes_from_announcements() {
+        let (a, b, c) = (B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3));
+        let first = ValidAnnouncementData::new_eth68(HashMap::from([
+            (a, Some((0x02, 100))),
+            (b, Some((0x02, 200))),
+        ]));
+        let second =
+            ValidAnnouncementData::new_eth68(HashMap::from([(b, Some((0x02, 200))), (c, None)]));
+
+        let (hashes, version) = RequestTxHashes::from_announcements([first, second]).unwrap();
+        assert_eq!(version, EthVersion::Eth68);
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes.iter().copied().collect::<HashSet<_>>(), HashSet::from([a, b, c]));
+
+        let mixed = [
+            ValidAnnouncementData::new_eth66(HashMap::from([(a, None)])),
+            ValidAnnouncementData::new_eth68(HashMap::from([(b, Some((0x02, 200)))])),
+        ];
+        assert_eq!(
+            RequestTxHashes::from_announcements(mixed).unwrap_err(),
+            BroadcastValidationError::MixedAnnouncementVersions(GotExpected {
+                got: EthVersion::Eth68,
+                expected: EthVersion::Eth66,
+            })
+        );
+        assert_eq!(
+            RequestTxHashes::from_announcements([]).unwrap_err(),
+            BroadcastValidationError::NoAnnouncements
+        );
+    }
+
+    #[test]
+    fn truncate_highest_block_hashes() {
+        let blocks = [5, 1, 9, 5, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(i, number)| BlockHashNumber { hash: B256::with_last_byte(i as u8), number })
+            .collect::<Vec<_>>();
+        let mut hashes = NewBlockHashes(blocks.clone());
+
+        let dropped = hashes.truncate_highest(2);
+        assert_eq!(hashes, NewBlockHashes(vec![blocks[0].clone(), blocks[2].clone()]));
+        assert_eq!(
+            dropped,
+            NewBlockHashes(vec![blocks[1].clone(), blocks[3].clone(), blocks[4].clone()])
+        );
+
+        assert_eq!(hashes.truncate_highest(2), NewBlockHashes::default());
+        assert_eq!(hashes.0.len(), 2);
+    }
+
+    #[test]
+    fn merge_block_hashes_with_capacity() {
+        let blocks = (0..6u8)
+            .map(|i| BlockHashNumber { hash: B256::with_last_byte(i), number: i as u64 })
+            .collect::<Vec<_>>();
+        let announcements = vec![
+            NewBlockHashes(blocks[..3].to_vec()),
+            NewBlockHashes(vec![blocks[1].clone(), blocks[3].clone()]),
+            NewBlockHashes(blocks[2..].to_vec()),
+        ];
+
+        let capacity = 8;
+        let merged = NewBlockHashes::merge_all_with_capacity(announcements, capacity);
+        assert_eq!(merged, NewBlockHashes(blocks));
+        // the merged list was never reallocated
+        assert_eq!(
+            merged.0.capacity(),
+            Vec::<BlockHashNumber>::with_capacity(capacity).capacity()
+        );
+
+        assert_eq!(NewBlockHashes::merge_all_with_capacity([], 0), NewBlockHashes::default());
+    }
+
+    #[test]
+    fn block_hashes_gap_to_local_head() {
+        let hashes = NewBlockHashes(
+            [5, 9, 3]
+                .into_iter()
+                .map(|number| BlockHashNumber { hash: B256::with_last_byte(number as u8), number })
+                .collect(),
+        );
+
+        // ahead
+        assert_eq!(hashes.gap_to(4), Some(5));
+        // equal
+        assert_eq!(hashes.gap_to(9), None);
+        // behind
+        assert_eq!(hashes.gap_to(12), None);
+
+        assert_eq!(NewBlockHashes::default().gap_to(0), None);
+    }
+
+    #[test]
+    fn union_hashes_across_versions() {
+        let (a, b, c) = (B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3));
+        let msgs = [
+            NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(vec![a, b])),
+            NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+                types: vec![0x02, 0x03],
+                sizes: vec![100, 200],
+                hashes: vec![b, c],
+            }),
+        ];
+
+        let union = NewPooledTransactionHashes::union_hashes(&msgs);
+        assert_eq!(union.len(), 3);
+        assert_eq!(union, HashSet::from([a, b, c]));
+        assert!(NewPooledTransactionHashes::union_hashes(&[]).is_empty());
+    }
+
+    #[test]
+    fn announcement_overlap() {
+        let hashes = (1..=4).map(B256::with_last_byte).collect::<Vec<_>>();
+        let eth66 = |hashes: &[B256]| {
+            NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(hashes.to_vec()))
+        };
+        let eth68 = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0x02; 3],
+            sizes: vec![100; 3],
+            hashes: hashes[..3].to_vec(),
+        });
+        let overlap = NewPooledTransactionHashes::announcement_overlap;
+
+        // fully overlapping, regardless of version and order
+        assert_eq!(overlap(&eth68, &eth66(&[hashes[2], hashes[1], hashes[0]])), 1.0);
+        // partially overlapping
+        assert_eq!(overlap(&eth68, &eth66(&hashes[2..])), 0.25);
+        // disjoint
+        assert_eq!(overlap(&eth68, &eth66(&hashes[3..])), 0.0);
+        assert_eq!(overlap(&eth66(&[]), &eth66(&[])), 0.0);
+    }
+
+    #[test]
+    fn announcement_downgrade_loses_metadata() {
+        let eth68 = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0x02],
+            sizes: vec![100],
+            hashes: vec![B256::with_last_byte(1)],
+        });
+        assert!(eth68.downgrade_loses_metadata());
+
+        let empty = NewPooledTransactionHashes::Eth68(Default::default());
+        assert!(!empty.downgrade_loses_metadata());
+
+        let eth66 = NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(vec![
+            B256::with_last_byte(1),
+        ]));
+        assert!(!eth66.downgrade_loses_metadata());
+    }
+
+    #[test]
+    fn announcement_debug_entries() {
+        let hashes = [3, 1, 2].map(B256::with_last_byte);
+
+        let eth66 =
+            NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(hashes.to_vec()));
+        assert_eq!(
+            eth66.debug_entries(),
+            vec![
+                (B256::with_last_byte(1), None),
+                (B256::with_last_byte(2), None),
+                (B256::with_last_byte(3), None),
+            ]
+        );
+
+        let eth68 = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0x00, 0x02, 0x03],
+            sizes: vec![300, 100, 131_072],
+            hashes: hashes.to_vec(),
+        });
+        assert_eq!(
+            eth68.debug_entries(),
+            vec![
+                (B256::with_last_byte(1), Some((0x02, 100))),
+                (B256::with_last_byte(2), Some((0x03, 131_072))),
+                (B256::with_last_byte(3), Some((0x00, 300))),
+            ]
+        );
+    }
+
+    #[test]
+    fn chunked_announcements() {
+        let hashes = (1..=5).map(B256::with_last_byte).collect::<Vec<_>>();
+        let msg = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0x00, 0x01, 0x02, 0x03, 0x02],
+            sizes: vec![100, 200, 300, 131_072, 500],
+            hashes: hashes.clone(),
+        });
+
+        let chunks = msg.clone().chunked(2);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 2));
+
+        let mut reassembled = NewPooledTransactionHashes68::default();
+        for chunk in chunks {
+            let chunk = chunk.as_eth68().unwrap();
+            assert!(chunk.validate_aligned().is_ok());
+            reassembled.types.extend(&chunk.types);
+            reassembled.sizes.extend(&chunk.sizes);
+            reassembled.hashes.extend(&chunk.hashes);
+        }
+        assert_eq!(NewPooledTransactionHashes::Eth68(reassembled), msg);
+
+        let msg = NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(hashes.clone()));
+        let chunks = msg.chunked(5);
+        assert_eq!(
+            chunks,
+            vec![NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(hashes))]
+        );
+        let empty = NewPooledTransactionHashes::Eth66(Default::default());
+        assert!(empty.chunked(2).is_empty());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "misaligned eth68 announcement")]
+    fn chunked_misaligned_announcement() {
+        let msg = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0x00, 0x01, 0x02],
+            sizes: vec![100, 200, 300, 400, 500],
+            hashes: (1..=5).map(B256::with_last_byte).collect(),
+        });
+        let _ = msg.chunked(2);
+    }
+
+    #[test]
+    fn announcement_recommended_max_entries() {
+        for version in [EthVersion::Eth66, EthVersion::Eth67, EthVersion::Eth68] {
+            assert_eq!(NewPooledTransactionHashes::recommended_max_entries(version), 4096);
+        }
+
+        let hashes = vec![B256::ZERO; 4097];
+        let msg = NewPooledTransactionHashes::Eth66(NewPooledTransactionHashes66(hashes));
+        let chunks = msg.chunked_recommended();
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(), vec![4096, 1]);
+    }
+
+    #[test]
+    fn sort_announcements_by_blob_priority() {
+        let hashes = (1..=5).map(B256::with_last_byte).collect::<Vec<_>>();
+        let mut msg = NewPooledTransactionHashes68 {
+            types: vec![0x02, 0x03, 0x03, 0x03, 0x03],
+            sizes: vec![100, 200, 300, 400, 500],
+            hashes: hashes.clone(),
+        };
+        let fees = |blob_fee, tip| BlobFeeData {
+            max_fee_per_gas: 100 + tip,
+            max_priority_fee_per_gas: tip,
+            max_fee_per_blob_gas: blob_fee,
+        };
+        let metadata = HashMap::from([
+            (hashes[1], fees(20, 5)),
+            // can't pay the blob base fee
+            (hashes[2], fees(5, 50)),
+            (hashes[3], fees(20, 10)),
+            (hashes[4], fees(30, 1)),
+        ]);
+
+        msg.sort_by_blob_priority(100, 10, |hash| metadata.get(hash).copied());
+
+        let order = [4, 3, 1, 0, 2];
+        assert_eq!(msg.hashes, order.map(|i| hashes[i]));
+        assert_eq!(msg.sizes, order.map(|i| (i + 1) * 100));
+        assert_eq!(msg.types, vec![0x03, 0x03, 0x03, 0x02, 0x03]);
+    }
+
+    #[test]
+    fn strip_blob_announcements() {
+        let hashes = (1..=4).map(B256::with_last_byte).collect::<Vec<_>>();
+        let mut msg = NewPooledTransactionHashes68 {
+            types: vec![0x02, 0x03, 0x00, 0x03],
+            sizes: vec![100, 131_172, 110, 262_244],
+            hashes: hashes.clone(),
+        };
+
+        let blobs = msg.without_type(0x03);
+        assert_eq!(
+            msg,
+            NewPooledTransactionHashes68 {
+                types: vec![0x02, 0x00],
+                sizes: vec![100, 110],
+                hashes: vec![hashes[0], hashes[2]],
+            }
+        );
+        assert_eq!(
+            blobs,
+            NewPooledTransactionHashes68 {
+                types: vec![0x03, 0x03],
+                sizes: vec![131_172, 262_244],
+                hashes: vec![hashes[1], hashes[3]],
+            }
+        );
+        assert!(msg.validate_aligned().is_ok());
+        assert!(msg.without_type(0x03).hashes.is_empty());
+    }
 }
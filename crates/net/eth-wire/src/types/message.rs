@@ -235,6 +235,41 @@ impl EthMessage {
             EthMessage::Receipts(_) => EthMessageID::Receipts,
         }
     }
+
+    /// Returns the [`BandwidthClass`] of the message, so outbound traffic can be budgeted per
+    /// class.
+    pub fn bandwidth_class(&self) -> BandwidthClass {
+        match self {
+            EthMessage::Status(_) |
+            EthMessage::GetBlockHeaders(_) |
+            EthMessage::GetBlockBodies(_) |
+            EthMessage::GetPooledTransactions(_) |
+            EthMessage::GetNodeData(_) |
+            EthMessage::GetReceipts(_) => BandwidthClass::Control,
+            EthMessage::NewBlockHashes(_) |
+            EthMessage::NewPooledTransactionHashes66(_) |
+            EthMessage::NewPooledTransactionHashes68(_) => BandwidthClass::Announcement,
+            EthMessage::NewBlock(_) | EthMessage::Transactions(_) => BandwidthClass::FullBodies,
+            EthMessage::BlockHeaders(_) |
+            EthMessage::BlockBodies(_) |
+            EthMessage::PooledTransactions(_) |
+            EthMessage::NodeData(_) |
+            EthMessage::Receipts(_) => BandwidthClass::Bulk,
+        }
+    }
+}
+
+/// Bandwidth category of an [`EthMessage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BandwidthClass {
+    /// The status handshake and requests, which are small.
+    Control,
+    /// Announcements of new blocks or transactions by hash.
+    Announcement,
+    /// Broadcasts of full blocks or transactions.
+    FullBodies,
+    /// Responses to requests, which can be up to [`MAX_MESSAGE_SIZE`].
+    Bulk,
 }
 
 impl Encodable for EthMessage {
@@ -476,8 +511,9 @@ where
 #[cfg(test)]
 mod tests {
     use crate::{
-        errors::EthStreamError, types::message::RequestPair, EthMessage, EthMessageID, GetNodeData,
-        NodeData, ProtocolMessage,
+        errors::EthStreamError, types::message::RequestPair, BandwidthClass, EthMessage,
+        EthMessageID, GetNodeData, NewBlock, NewPooledTransactionHashes66,
+        NewPooledTransactionHashes68, NodeData, ProtocolMessage, Transactions,
     };
     use alloy_rlp::{Decodable, Encodable};
     use reth_primitives::hex;
@@ -532,4 +568,24 @@ mod tests {
         assert_eq!(expected.length(), raw_pair.len());
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn broadcast_bandwidth_classes() {
+        let announcements = [
+            EthMessage::NewPooledTransactionHashes66(NewPooledTransactionHashes66::default()),
+            EthMessage::NewPooledTransactionHashes68(NewPooledTransactionHashes68::default()),
+        ];
+        for msg in announcements {
+            assert_eq!(msg.bandwidth_class(), BandwidthClass::Announcement);
+        }
+
+        let transactions = EthMessage::Transactions(Transactions::default());
+        assert_eq!(transactions.bandwidth_class(), BandwidthClass::FullBodies);
+        let new_block = EthMessage::NewBlock(Box::<NewBlock>::default());
+        assert_eq!(new_block.bandwidth_class(), BandwidthClass::FullBodies);
+
+        let node_data =
+            EthMessage::NodeData(RequestPair { request_id: 1, message: NodeData(vec![]) });
+        assert_eq!(node_data.bandwidth_class(), BandwidthClass::Bulk);
+    }
 }
@@ -7,7 +7,7 @@ pub mod version;
 pub use version::EthVersion;
 
 pub mod message;
-pub use message::{EthMessage, EthMessageID, ProtocolMessage};
+pub use message::{BandwidthClass, EthMessage, EthMessageID, ProtocolMessage};
 
 pub mod blocks;
 pub use blocks::*;
@@ -1,5 +1,6 @@
 //! Implements the `GetPooledTransactions` and `PooledTransactions` message types.
 
+use crate::EthVersion;
 use alloy_rlp::{RlpDecodableWrapper, RlpEncodableWrapper};
 use reth_codecs::derive_arbitrary;
 use reth_primitives::{PooledTransactionsElement, TransactionSigned, B256};
@@ -45,6 +46,18 @@ impl PooledTransactions {
     pub fn hashes(&self) -> impl Iterator<Item = &B256> + '_ {
         self.0.iter().map(|tx| tx.hash())
     }
+
+    /// Returns the lowest [`EthVersion`] whose rules permit all contained transaction types.
+    ///
+    /// Blob transactions were introduced with Cancun, which is only supported by peers speaking
+    /// [`EthVersion::Eth68`]. All other transaction types can be sent with any version.
+    pub fn min_eth_version(&self) -> EthVersion {
+        if self.0.iter().any(|tx| matches!(tx, PooledTransactionsElement::BlobTransaction(_))) {
+            EthVersion::Eth68
+        } else {
+            EthVersion::Eth66
+        }
+    }
 }
 
 impl From<Vec<TransactionSigned>> for PooledTransactions {
@@ -55,10 +68,11 @@ impl From<Vec<TransactionSigned>> for PooledTransactions {
 
 #[cfg(test)]
 mod tests {
-    use crate::{message::RequestPair, GetPooledTransactions, PooledTransactions};
+    use crate::{message::RequestPair, EthVersion, GetPooledTransactions, PooledTransactions};
     use alloy_rlp::{Decodable, Encodable};
     use reth_primitives::{
-        hex, Signature, Transaction, TransactionKind, TransactionSigned, TxEip1559, TxLegacy, U256,
+        hex, BlobTransactionSidecar, PooledTransactionsElement, Signature, Transaction,
+        TransactionKind, TransactionSigned, TxEip1559, TxEip4844, TxLegacy, B256, U256,
     };
     use std::str::FromStr;
 
@@ -507,4 +521,31 @@ mod tests {
         assert_eq!(encoded_str.len(), expected_str.len());
         assert_eq!(encoded_str, expected_str);
     }
+
+    #[test]
+    fn pooled_transactions_min_eth_version() {
+        let legacy = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy::default()),
+            Signature::default(),
+        );
+        let mut txs = PooledTransactions::from(vec![legacy]);
+        assert_eq!(txs.min_eth_version(), EthVersion::Eth66);
+        assert_eq!(PooledTransactions::default().min_eth_version(), EthVersion::Eth66);
+
+        let blob = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip4844(TxEip4844 {
+                blob_versioned_hashes: vec![B256::with_last_byte(1)],
+                ..Default::default()
+            }),
+            Signature::default(),
+        );
+        txs.0.push(
+            PooledTransactionsElement::try_from_blob_transaction(
+                blob,
+                BlobTransactionSidecar::default(),
+            )
+            .unwrap(),
+        );
+        assert_eq!(txs.min_eth_version(), EthVersion::Eth68);
+    }
 }
@@ -260,6 +260,13 @@ impl ActiveSession {
             PeerMessage::PooledTransactions(msg) => {
                 if msg.is_valid_for_version(self.conn.version()) {
                     self.queued_outgoing.push_back(EthMessage::from(msg).into());
+                } else {
+                    debug!(
+                        target: "net::session",
+                        version=?self.conn.version(),
+                        remote_peer_id=?self.remote_peer_id,
+                        "Dropping pooled transaction hashes invalid for session version"
+                    );
                 }
             }
             PeerMessage::EthRequest(req) => {
@@ -11,6 +11,7 @@ use reth_rpc_types::{
     withdrawal::Withdrawal,
     ExecutionPayloadV1,
 };
+use std::cmp::Ordering;
 
 /// Represents a built payload type that contains a built [SealedBlock] and can be converted into
 /// engine API execution payloads.
@@ -21,6 +22,27 @@ pub trait BuiltPayload: Send + Sync + std::fmt::Debug {
     /// Returns the fees collected for the built block
     fn fees(&self) -> U256;
 
+    /// Compares the value of the payload to another payload by their collected fees.
+    fn cmp_value(&self, other: &Self) -> Ordering
+    where
+        Self: Sized,
+    {
+        self.fees().cmp(&other.fees())
+    }
+
+    /// Returns `true` if both payloads contain the same transactions, regardless of their order.
+    fn same_transactions(&self, other: &Self) -> bool
+    where
+        Self: Sized,
+    {
+        let hashes = |payload: &Self| {
+            let mut hashes = payload.block().body.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+            hashes.sort_unstable();
+            hashes
+        };
+        hashes(self) == hashes(other)
+    }
+
     /// Converts the type into the response expected by `engine_getPayloadV1`
     fn into_v1_payload(self) -> ExecutionPayloadV1;
 
@@ -55,6 +77,17 @@ pub trait PayloadBuilderAttributes: Send + Sync + std::fmt::Debug {
     /// Returns the [PayloadId] for the running payload job.
     fn payload_id(&self) -> PayloadId;
 
+    /// Returns the attributes with equivalent representations canonicalized, so that equivalent
+    /// attributes have the same [PayloadId].
+    ///
+    /// By default the attributes are returned unchanged.
+    fn normalized(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
     /// Returns the parent block hash for the running payload job.
     fn parent(&self) -> B256;
 
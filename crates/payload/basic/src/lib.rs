@@ -12,18 +12,20 @@ use alloy_rlp::Encodable;
 use futures_core::ready;
 use futures_util::FutureExt;
 use reth_interfaces::RethResult;
-use reth_node_api::{BuiltPayload, PayloadBuilderAttributes};
+use reth_node_api::{BuiltPayload, EngineApiMessageVersion, PayloadBuilderAttributes};
 use reth_payload_builder::{
     database::CachedReads, error::PayloadBuilderError, EthBuiltPayload, KeepPayloadJobAlive,
-    PayloadId, PayloadJob, PayloadJobGenerator,
+    PayloadId, PayloadJob, PayloadJobDiagnostics, PayloadJobGenerator,
 };
 use reth_primitives::{
     bytes::BytesMut,
     constants::{EMPTY_WITHDRAWALS, ETHEREUM_BLOCK_GAS_LIMIT, RETH_CLIENT_VERSION, SLOT_DURATION},
-    proofs, BlockNumberOrTag, Bytes, ChainSpec, SealedBlock, Withdrawals, B256, U256,
+    proofs, Address, BlockNumberOrTag, Bytes, ChainSpec, ForkCondition, Hardfork, SealedBlock,
+    TransactionSigned, Withdrawals, B256, U256,
 };
 use reth_provider::{
-    BlockReaderIdExt, BlockSource, CanonStateNotification, ProviderError, StateProviderFactory,
+    BlockReaderIdExt, BlockSource, CanonStateNotification, HeaderProvider, ProviderError,
+    StateProviderFactory,
 };
 use reth_revm::state_change::{
     apply_beacon_root_contract_call, post_block_withdrawals_balance_increments,
@@ -35,11 +37,12 @@ use revm::{
     Database, DatabaseCommit, Evm, State,
 };
 use std::{
+    collections::{HashMap, HashSet},
     future::Future,
     pin::Pin,
     sync::{atomic::AtomicBool, Arc},
     task::{Context, Poll},
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
     sync::{oneshot, Semaphore},
@@ -132,6 +135,43 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
     }
 }
 
+impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + BlockReaderIdExt,
+{
+    /// Returns the block with the given hash to build on instead of the requested parent.
+    ///
+    /// Fails if the block is unknown, its state root is not `state_root` or its state is not
+    /// available.
+    fn override_parent_block(
+        &self,
+        block_hash: B256,
+        state_root: B256,
+    ) -> Result<SealedBlock, PayloadBuilderError> {
+        let block = self
+            .client
+            .find_block_by_hash(block_hash, BlockSource::Any)?
+            .ok_or(PayloadBuilderError::MissingParentBlock(block_hash))?;
+
+        if block.state_root != state_root || self.client.history_by_block_hash(block_hash).is_err()
+        {
+            return Err(PayloadBuilderError::MissingParentState { block: block_hash, state_root })
+        }
+
+        Ok(block.seal(block_hash))
+    }
+
+    /// Fails if the given parent block is not the canonical block at its height, e.g. because the
+    /// fork choice update references a block on a side chain.
+    fn ensure_canonical_parent(&self, parent: &SealedBlock) -> Result<(), PayloadBuilderError> {
+        let canonical = self.client.sealed_header(parent.number)?.map(|header| header.hash());
+        if canonical != Some(parent.hash()) {
+            return Err(PayloadBuilderError::NonCanonicalParent(parent.hash()))
+        }
+        Ok(())
+    }
+}
+
 // === impl BasicPayloadJobGenerator ===
 
 impl<Client, Pool, Tasks, Builder> PayloadJobGenerator
@@ -150,7 +190,10 @@ where
         &self,
         attributes: <Self::Job as PayloadJob>::PayloadAttributes,
     ) -> Result<Self::Job, PayloadBuilderError> {
-        let parent_block = if attributes.parent().is_zero() {
+        let parent_override = self.config.parent_overrides.get(&attributes.parent());
+        let parent_block = if let Some(&(block_hash, state_root)) = parent_override {
+            self.override_parent_block(block_hash, state_root)?
+        } else if attributes.parent().is_zero() {
             // use latest block if parent is zero: genesis block
             self.client
                 .block_by_number_or_tag(BlockNumberOrTag::Latest)?
@@ -163,7 +206,9 @@ where
                 .ok_or_else(|| PayloadBuilderError::MissingParentBlock(attributes.parent()))?;
 
             // we already know the hash, so we can seal it
-            block.seal(attributes.parent())
+            let block = block.seal(attributes.parent());
+            self.ensure_canonical_parent(&block)?;
+            block
         };
 
         let config = PayloadConfig::new(
@@ -171,21 +216,36 @@ where
             self.config.extradata.clone(),
             attributes,
             Arc::clone(&self.chain_spec),
-        );
+        )
+        .with_must_include(Arc::clone(&self.config.must_include));
 
         let until = self.job_deadline(config.attributes.timestamp());
         let deadline = Box::pin(tokio::time::sleep_until(until));
 
         let cached_reads = self.maybe_pre_cached(config.parent_block.hash());
 
+        let build_cutoff = self
+            .config
+            .max_build_time
+            .map(|max_build_time| Box::pin(tokio::time::sleep(max_build_time)));
+
         Ok(BasicPayloadJob {
             config,
             client: self.client.clone(),
             pool: self.pool.clone(),
             executor: self.executor.clone(),
             deadline,
+            build_cutoff,
+            frozen: false,
+            superseded: false,
             interval: tokio::time::interval(self.config.interval),
             best_payload: None,
+            best_payload_outdated: false,
+            best_source_tx_count: 0,
+            started_at: Instant::now(),
+            resolved: false,
+            improvements: 0,
+            iterations: 0,
             pending_block: None,
             cached_reads,
             payload_task_guard: self.payload_task_guard.clone(),
@@ -213,6 +273,17 @@ where
             self.pre_cached = Some(PrecachedState { block: committed.tip().hash(), cached });
         }
     }
+
+    /// Supports the versions of all forks that are scheduled by the chain spec.
+    fn supported_versions(&self) -> &[EngineApiMessageVersion] {
+        if self.chain_spec.fork(Hardfork::Cancun) != ForkCondition::Never {
+            &[EngineApiMessageVersion::V1, EngineApiMessageVersion::V2, EngineApiMessageVersion::V3]
+        } else if self.chain_spec.fork(Hardfork::Shanghai) != ForkCondition::Never {
+            &[EngineApiMessageVersion::V1, EngineApiMessageVersion::V2]
+        } else {
+            &[EngineApiMessageVersion::V1]
+        }
+    }
 }
 
 /// Pre-filled [CachedReads] for a specific block.
@@ -253,6 +324,14 @@ pub struct BasicPayloadJobGeneratorConfig {
     deadline: Duration,
     /// Maximum number of tasks to spawn for building a payload.
     max_payload_tasks: usize,
+    /// Maximum duration a job keeps improving its payload, after which the best payload is frozen.
+    ///
+    /// By default there is no limit besides the deadline.
+    max_build_time: Option<Duration>,
+    /// Block hash and state root to build payloads on, by the parent requested for them.
+    parent_overrides: HashMap<B256, (B256, B256)>,
+    /// Transactions to place first in every payload, regardless of their fees.
+    must_include: Arc<[TransactionSigned]>,
 }
 
 // === impl BasicPayloadJobGeneratorConfig ===
@@ -281,6 +360,34 @@ impl BasicPayloadJobGeneratorConfig {
         self
     }
 
+    /// Sets the maximum duration a job keeps improving its payload.
+    ///
+    /// Once this has passed, the job cancels any build in progress and stops building new
+    /// payloads, so resolving the job returns the best payload built until then right away.
+    pub fn max_build_time(mut self, max_build_time: Duration) -> Self {
+        self.max_build_time = Some(max_build_time);
+        self
+    }
+
+    /// Builds payloads requested on top of `parent` on top of the block with the given hash and
+    /// state root instead. Payloads requested on top of other parents are not affected.
+    ///
+    /// This is useful to simulate payloads against a historical state. Creating a job fails if the
+    /// state of the block is not available.
+    pub fn parent_override(mut self, parent: B256, block_hash: B256, state_root: B256) -> Self {
+        self.parent_overrides.insert(parent, (block_hash, state_root));
+        self
+    }
+
+    /// Sets transactions that every payload includes first, in the given order, regardless of
+    /// their fees, e.g. system transactions of a sequencer.
+    ///
+    /// Transactions that are invalid at the state the payload is built on are logged and skipped.
+    pub fn must_include(mut self, transactions: Vec<TransactionSigned>) -> Self {
+        self.must_include = transactions.into();
+        self
+    }
+
     /// Sets the data to include in the block's extra data field.
     ///
     /// Defaults to the current client version: `rlp(RETH_CLIENT_VERSION)`.
@@ -309,6 +416,9 @@ impl Default for BasicPayloadJobGeneratorConfig {
             // 12s slot time
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            max_build_time: None,
+            parent_overrides: HashMap::new(),
+            must_include: Arc::default(),
         }
     }
 }
@@ -329,10 +439,30 @@ where
     executor: Tasks,
     /// The deadline when this job should resolve.
     deadline: Pin<Box<Sleep>>,
+    /// When the job should stop improving its payload, if configured.
+    build_cutoff: Option<Pin<Box<Sleep>>>,
+    /// Whether the job stopped improving its payload because the build cutoff was reached or the
+    /// job was superseded.
+    frozen: bool,
+    /// Whether the job was superseded by a newer job for the same slot.
+    superseded: bool,
     /// The interval at which the job should build a new payload after the last.
     interval: Interval,
     /// The best payload so far.
     best_payload: Option<Builder::BuiltPayload>,
+    /// Whether the best payload credits a previous fee recipient, in which case it's only kept
+    /// until a payload for the current fee recipient has been built.
+    best_payload_outdated: bool,
+    /// How many pending pool transactions there were when the best payload started building.
+    best_source_tx_count: usize,
+    /// When this job was created.
+    started_at: Instant,
+    /// Whether the payload has been requested by the CL.
+    resolved: bool,
+    /// How many times the best payload has been replaced by a better one.
+    improvements: u64,
+    /// How many payload builds have been spawned by this job.
+    iterations: u64,
     /// Receiver for the block that is currently being built.
     pending_block: Option<PendingPayload<Builder::BuiltPayload>>,
     /// Restricts how many generator tasks can be executed at once.
@@ -370,6 +500,21 @@ where
             return Poll::Ready(Ok(()))
         }
 
+        // check if the build cutoff is reached
+        if let Some(cutoff) = this.build_cutoff.as_mut() {
+            if cutoff.as_mut().poll(cx).is_ready() {
+                trace!(target: "payload_builder", "payload build cutoff reached, freezing best payload");
+                this.build_cutoff = None;
+                this.frozen = true;
+                // cancels the build in progress
+                this.pending_block = None;
+            }
+        }
+
+        if this.frozen {
+            return Poll::Pending
+        }
+
         // check if the interval is reached
         while this.interval.poll_tick(cx).is_ready() {
             // start a new job if there is no pending block and we haven't reached the deadline
@@ -382,8 +527,12 @@ where
                 let _cancel = cancel.clone();
                 let guard = this.payload_task_guard.clone();
                 let payload_config = this.config.clone();
-                let best_payload = this.best_payload.clone();
+                // an outdated payload must not be compared against, it will be replaced regardless
+                let best_payload =
+                    if this.best_payload_outdated { None } else { this.best_payload.clone() };
+                let source_tx_count = this.pool.pool_size().pending;
                 this.metrics.inc_initiated_payload_builds();
+                this.iterations += 1;
                 let cached_reads = this.cached_reads.take().unwrap_or_default();
                 let builder = this.builder.clone();
                 this.executor.spawn_blocking(Box::pin(async move {
@@ -401,7 +550,7 @@ where
                     let _ = tx.send(result);
                 }));
 
-                this.pending_block = Some(PendingPayload { _cancel, payload: rx });
+                this.pending_block = Some(PendingPayload { _cancel, source_tx_count, payload: rx });
             }
         }
 
@@ -416,6 +565,9 @@ where
                             debug!(target: "payload_builder", value = %payload.fees(), "built better payload");
                             let payload = payload;
                             this.best_payload = Some(payload);
+                            this.best_payload_outdated = false;
+                            this.best_source_tx_count = fut.source_tx_count;
+                            this.improvements += 1;
                         }
                         BuildOutcome::Aborted { fees, cached_reads } => {
                             this.cached_reads = Some(cached_reads);
@@ -472,7 +624,98 @@ where
         Ok(self.config.attributes.clone())
     }
 
+    fn parent_hash(&self) -> B256 {
+        self.config.parent_block.hash()
+    }
+
+    fn improvements(&self) -> u64 {
+        self.improvements
+    }
+
+    fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Builds the next payloads with the new fee recipient.
+    ///
+    /// The build in progress, if any, credits the previous fee recipient, so it is cancelled. The
+    /// best payload built so far is still returned until the next build with the new fee recipient
+    /// finishes and replaces it, regardless of its fees.
+    fn set_fee_recipient(&mut self, recipient: Address) -> Result<(), PayloadBuilderError> {
+        self.config.initialized_block_env.coinbase = recipient;
+        self.best_payload_outdated = self.best_payload.is_some();
+        self.pending_block = None;
+        Ok(())
+    }
+
+    fn mark_superseded(&mut self) {
+        trace!(target: "payload_builder", id=%self.config.payload_id(), "payload job superseded, freezing best payload");
+        self.superseded = true;
+        self.frozen = true;
+        self.build_cutoff = None;
+        // cancels the build in progress
+        self.pending_block = None;
+    }
+
+    fn is_superseded(&self) -> bool {
+        self.superseded
+    }
+
+    fn source_tx_count(&self) -> usize {
+        self.best_source_tx_count
+    }
+
+    fn gas_limit(&self) -> u64 {
+        self.config.initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX)
+    }
+
+    fn current_state_root(&self) -> Option<B256> {
+        // don't build an empty payload just to report its state root
+        self.best_payload.as_ref().map(|payload| payload.block().state_root)
+    }
+
+    /// Sums up the tips of the best pending transactions that aren't included in the best payload
+    /// yet, as long as they fit into the gas left in the block.
+    fn improvement_headroom(&self) -> Option<U256> {
+        let block_env = &self.config.initialized_block_env;
+        let base_fee = block_env.basefee.to::<u64>();
+        let block_gas_limit: u64 = block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+
+        let (included, mut gas_left) = match &self.best_payload {
+            Some(payload) => {
+                let block = payload.block();
+                let included = block.body.iter().map(|tx| tx.hash()).collect::<HashSet<_>>();
+                (included, block_gas_limit.saturating_sub(block.gas_used))
+            }
+            None => (HashSet::new(), block_gas_limit),
+        };
+
+        let mut headroom = U256::ZERO;
+        for tx in self.pool.best_transactions_with_base_fee(base_fee) {
+            if included.contains(tx.hash()) || tx.gas_limit() > gas_left {
+                continue
+            }
+            let Some(tip) = tx.effective_tip_per_gas(base_fee) else { continue };
+            gas_left -= tx.gas_limit();
+            headroom += U256::from(tip) * U256::from(tx.gas_limit());
+        }
+        Some(headroom)
+    }
+
+    fn diagnostics(&self) -> PayloadJobDiagnostics {
+        // like `current_state_root`, this doesn't build an empty payload if there's none yet
+        PayloadJobDiagnostics {
+            parent_hash: self.parent_hash(),
+            iterations: self.iterations,
+            tx_count: self.best_payload.as_ref().map_or(0, |payload| payload.block().body.len()),
+            value: self.best_payload.as_ref().map(|payload| payload.fees()).unwrap_or_default(),
+            elapsed: Some(self.started_at.elapsed()),
+            resolved: self.resolved,
+        }
+    }
+
     fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        self.resolved = true;
         let best_payload = self.best_payload.take();
         let maybe_better = self.pending_block.take();
         let mut empty_payload = None;
@@ -588,6 +831,8 @@ where
 struct PendingPayload<P> {
     /// The marker to cancel the job on drop
     _cancel: Cancelled,
+    /// How many pending pool transactions there were when the build started.
+    source_tx_count: usize,
     /// The channel to send the result to.
     payload: oneshot::Receiver<Result<BuildOutcome<P>, PayloadBuilderError>>,
 }
@@ -637,6 +882,8 @@ pub struct PayloadConfig<Attributes> {
     pub attributes: Attributes,
     /// The chain spec.
     pub chain_spec: Arc<ChainSpec>,
+    /// Transactions to place first in the payload, regardless of their fees.
+    pub must_include: Arc<[TransactionSigned]>,
 }
 
 impl<Attributes> PayloadConfig<Attributes> {
@@ -644,6 +891,14 @@ impl<Attributes> PayloadConfig<Attributes> {
     pub fn extra_data(&self) -> Bytes {
         self.extra_data.clone()
     }
+
+    /// Sets the transactions to place first in the payload.
+    ///
+    /// See also [BasicPayloadJobGeneratorConfig::must_include].
+    pub fn with_must_include(mut self, must_include: Arc<[TransactionSigned]>) -> Self {
+        self.must_include = must_include;
+        self
+    }
 }
 
 impl<Attributes> PayloadConfig<Attributes>
@@ -668,6 +923,7 @@ where
             extra_data,
             attributes,
             chain_spec,
+            must_include: Arc::default(),
         }
     }
 
@@ -905,3 +1161,539 @@ fn duration_until(unix_timestamp_secs: u64) -> Duration {
     let timestamp = Duration::from_secs(unix_timestamp_secs);
     timestamp.saturating_sub(unix_now)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_payload_builder::{EthPayloadBuilderAttributes, ResolveOnce};
+    use reth_primitives::{Block, IntoRecoveredTransaction, EMPTY_ROOT_HASH, MAINNET};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::{
+        noop::NoopTransactionPool,
+        test_utils::{testing_pool, MockTransaction, TestPool},
+        TransactionOrigin,
+    };
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// A builder that builds a payload which is better than the previous one every time.
+    #[derive(Debug, Clone, Default)]
+    struct ImprovingPayloadBuilder {
+        builds: Arc<AtomicU64>,
+    }
+
+    impl PayloadBuilder<NoopTransactionPool, MockEthProvider> for ImprovingPayloadBuilder {
+        type Attributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn try_build(
+            &self,
+            args: BuildArguments<
+                NoopTransactionPool,
+                MockEthProvider,
+                EthPayloadBuilderAttributes,
+                EthBuiltPayload,
+            >,
+        ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError> {
+            let builds = self.builds.fetch_add(1, Ordering::Relaxed) + 1;
+
+            let mut block = Block::default();
+            block.header.gas_used = builds;
+            block.header.beneficiary = args.config.initialized_block_env.coinbase;
+            let payload = EthBuiltPayload::new(
+                args.config.attributes.payload_id(),
+                block.seal_slow(),
+                U256::from(builds),
+            );
+            Ok(BuildOutcome::Better { payload, cached_reads: args.cached_reads })
+        }
+
+        fn build_empty_payload(
+            _client: &MockEthProvider,
+            config: PayloadConfig<EthPayloadBuilderAttributes>,
+        ) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let payload = EthBuiltPayload::new(
+                config.attributes.payload_id(),
+                Block::default().seal_slow(),
+                U256::ZERO,
+            );
+            Ok(payload)
+        }
+    }
+
+    /// A builder that includes one more of the best pool transactions in every payload.
+    #[derive(Debug, Clone, Default)]
+    struct PoolPayloadBuilder {
+        builds: Arc<AtomicU64>,
+    }
+
+    impl PayloadBuilder<TestPool, MockEthProvider> for PoolPayloadBuilder {
+        type Attributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn try_build(
+            &self,
+            args: BuildArguments<
+                TestPool,
+                MockEthProvider,
+                EthPayloadBuilderAttributes,
+                EthBuiltPayload,
+            >,
+        ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError> {
+            let builds = self.builds.fetch_add(1, Ordering::Relaxed) + 1;
+
+            let mut block = Block::default();
+            block.body = args
+                .pool
+                .best_transactions()
+                .take(builds as usize)
+                .map(|tx| tx.to_recovered_transaction().into_signed())
+                .collect();
+            block.header.gas_used = block.body.iter().map(|tx| tx.gas_limit()).sum();
+            let payload = EthBuiltPayload::new(
+                args.config.attributes.payload_id(),
+                block.seal_slow(),
+                U256::from(builds),
+            );
+            Ok(BuildOutcome::Better { payload, cached_reads: args.cached_reads })
+        }
+
+        fn build_empty_payload(
+            _client: &MockEthProvider,
+            config: PayloadConfig<EthPayloadBuilderAttributes>,
+        ) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let payload = EthBuiltPayload::new(
+                config.attributes.payload_id(),
+                Block::default().seal_slow(),
+                U256::ZERO,
+            );
+            Ok(payload)
+        }
+    }
+
+    /// Returns a client with a parent block to build payloads on, and the attributes of a payload
+    /// on top of it.
+    fn test_client() -> (MockEthProvider, EthPayloadBuilderAttributes) {
+        let client = MockEthProvider::default();
+        let mut parent = Block::default();
+        parent.header.gas_limit = ETHEREUM_BLOCK_GAS_LIMIT;
+        let parent_hash = parent.header.hash_slow();
+        client.add_block(parent_hash, parent);
+        (client, test_attributes(parent_hash))
+    }
+
+    /// Returns the attributes of a payload on top of `parent`.
+    fn test_attributes(parent: B256) -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes {
+            id: PayloadId::new([1; 8]),
+            parent,
+            timestamp: 1,
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Withdrawals::default(),
+            parent_beacon_block_root: None,
+        }
+    }
+
+    /// Returns a mainnet generator with the given client, pool, configuration and builder.
+    fn test_generator<Pool, Builder>(
+        client: MockEthProvider,
+        pool: Pool,
+        config: BasicPayloadJobGeneratorConfig,
+        builder: Builder,
+    ) -> BasicPayloadJobGenerator<MockEthProvider, Pool, TokioTaskExecutor, Builder> {
+        BasicPayloadJobGenerator::with_builder(
+            client,
+            pool,
+            TokioTaskExecutor::default(),
+            config,
+            MAINNET.clone(),
+            builder,
+        )
+    }
+
+    /// Returns the configuration of a generator that starts a new build right after the last.
+    fn eager_config() -> BasicPayloadJobGeneratorConfig {
+        BasicPayloadJobGeneratorConfig::default().interval(Duration::from_millis(1))
+    }
+
+    /// Polls the job until `done` returns `true`.
+    ///
+    /// The clock of the tests is paused, so it's advanced whenever the job waits for its next
+    /// build, and not while a build is running.
+    async fn poll_until<Job: Future + Unpin>(job: &mut Job, done: impl Fn(&Job) -> bool) {
+        while !done(job) {
+            let _ = tokio::time::timeout(Duration::from_millis(50), &mut *job).await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_improving_after_max_build_time() {
+        let (client, attributes) = test_client();
+        let config = eager_config().max_build_time(Duration::from_millis(200));
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            config,
+            ImprovingPayloadBuilder::default(),
+        );
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        poll_until(&mut job, |job| job.improvements() > 0).await;
+        assert!(!job.frozen);
+
+        // the cutoff is noticed the next time the job is polled
+        tokio::time::advance(Duration::from_millis(200)).await;
+        let _ = (&mut job).now_or_never();
+        assert!(job.frozen);
+        let improvements = job.improvements();
+
+        // the job doesn't improve anymore
+        let _ = tokio::time::timeout(Duration::from_millis(200), &mut job).await;
+        assert_eq!(job.improvements(), improvements);
+
+        // the frozen payload is resolved right away
+        let best = job.best_payload().unwrap();
+        let (fut, _) = job.resolve();
+        let resolved = fut.now_or_never().unwrap().unwrap();
+        assert_eq!(resolved.block().hash(), best.block().hash());
+        assert_eq!(resolved.block().gas_used, improvements);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn superseded_job_stops_improving() {
+        let (client, attributes) = test_client();
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            eager_config(),
+            ImprovingPayloadBuilder::default(),
+        );
+        let mut job = generator.new_payload_job(attributes).unwrap();
+        assert!(!job.is_superseded());
+
+        poll_until(&mut job, |job| job.improvements() > 0).await;
+        job.mark_superseded();
+        assert!(job.is_superseded());
+        let improvements = job.improvements();
+        let best = job.best_payload().unwrap();
+
+        // the job doesn't improve anymore
+        let _ = tokio::time::timeout(Duration::from_millis(200), &mut job).await;
+        assert_eq!(job.improvements(), improvements);
+        assert_eq!(job.best_payload().unwrap().block().hash(), best.block().hash());
+
+        // but still serves its frozen payload
+        let (fut, _) = job.resolve();
+        let resolved = fut.now_or_never().unwrap().unwrap();
+        assert_eq!(resolved.block().hash(), best.block().hash());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn counts_build_iterations() {
+        let (client, attributes) = test_client();
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            eager_config(),
+            ImprovingPayloadBuilder::default(),
+        );
+        let mut job = generator.new_payload_job(attributes).unwrap();
+        assert_eq!(job.iterations(), 0);
+
+        let mut iterations = 0;
+        for _ in 0..3 {
+            poll_until(&mut job, |job| job.iterations() > iterations).await;
+            iterations = job.iterations();
+        }
+        // every improvement was the result of an attempt
+        assert!(job.improvements() <= job.iterations());
+    }
+
+    #[test]
+    fn supported_versions_follow_chain_spec() {
+        let generator = |chain_spec: ChainSpec| {
+            BasicPayloadJobGenerator::with_builder(
+                MockEthProvider::default(),
+                NoopTransactionPool::default(),
+                TokioTaskExecutor::default(),
+                BasicPayloadJobGeneratorConfig::default(),
+                Arc::new(chain_spec),
+                ImprovingPayloadBuilder::default(),
+            )
+        };
+
+        // mainnet schedules cancun
+        assert_eq!(
+            generator((**MAINNET).clone()).supported_versions(),
+            &[EngineApiMessageVersion::V1, EngineApiMessageVersion::V2, EngineApiMessageVersion::V3]
+        );
+
+        let mut pre_cancun = (**MAINNET).clone();
+        pre_cancun.hardforks.remove(&Hardfork::Cancun);
+        assert_eq!(
+            generator(pre_cancun).supported_versions(),
+            &[EngineApiMessageVersion::V1, EngineApiMessageVersion::V2]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn improvement_headroom_decreases() {
+        let (client, attributes) = test_client();
+        let pool = testing_pool();
+        for _ in 0..3 {
+            let tx = MockTransaction::eip1559().with_gas_limit(21_000);
+            pool.add_transaction(TransactionOrigin::External, tx).await.unwrap();
+        }
+
+        let generator =
+            test_generator(client, pool, eager_config(), PoolPayloadBuilder::default());
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        // nothing is included before the first payload, and the parent has no base fee
+        let tip = MockTransaction::eip1559().get_priority_fee().unwrap();
+        let mut headroom = job.improvement_headroom().unwrap();
+        assert_eq!(headroom, U256::from(3 * tip * 21_000));
+
+        while headroom > U256::ZERO {
+            let improvements = job.improvements();
+            poll_until(&mut job, |job| job.improvements() > improvements).await;
+            let next = job.improvement_headroom().unwrap();
+            assert!(next < headroom);
+            headroom = next;
+        }
+        assert_eq!(job.tx_count(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_diagnostics() {
+        let (client, attributes) = test_client();
+        let pool = testing_pool();
+        let tx = MockTransaction::eip1559().with_gas_limit(21_000);
+        pool.add_transaction(TransactionOrigin::External, tx).await.unwrap();
+
+        let generator =
+            test_generator(client, pool, eager_config(), PoolPayloadBuilder::default());
+        let mut job = generator.new_payload_job(attributes.clone()).unwrap();
+
+        let diagnostics = job.diagnostics();
+        assert_eq!(diagnostics.parent_hash, attributes.parent);
+        assert_eq!(diagnostics.iterations, 0);
+        assert_eq!(diagnostics.tx_count, 0);
+        assert_eq!(diagnostics.value, U256::ZERO);
+        assert!(!diagnostics.resolved);
+
+        poll_until(&mut job, |job| job.improvements() > 0).await;
+
+        let running = job.diagnostics();
+        assert_eq!(running.parent_hash, attributes.parent);
+        assert_eq!(running.iterations, job.iterations());
+        assert!(running.iterations > 0);
+        assert_eq!(running.tx_count, 1);
+        assert_eq!(running.value, job.best_payload().unwrap().fees());
+        assert!(running.elapsed.unwrap() >= diagnostics.elapsed.unwrap());
+        assert!(!running.resolved);
+
+        let _ = job.resolve();
+        assert!(job.diagnostics().resolved);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_source_tx_count() {
+        let (client, attributes) = test_client();
+        let pool = testing_pool();
+        for _ in 0..3 {
+            let tx = MockTransaction::eip1559().with_gas_limit(21_000);
+            pool.add_transaction(TransactionOrigin::External, tx).await.unwrap();
+        }
+
+        let generator =
+            test_generator(client, pool, eager_config(), PoolPayloadBuilder::default());
+        let mut job = generator.new_payload_job(attributes).unwrap();
+        assert_eq!(job.source_tx_count(), 0);
+
+        poll_until(&mut job, |job| job.improvements() >= 2).await;
+
+        assert!(job.tx_count() > 0);
+        assert!(job.source_tx_count() >= job.tx_count());
+        assert_eq!(job.source_tx_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn reports_gas_limit() {
+        let (client, attributes) = test_client();
+        let parent = client.blocks.lock()[&attributes.parent].header.clone();
+
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            eager_config(),
+            ImprovingPayloadBuilder::default(),
+        );
+        let (_, block_env) = attributes.cfg_and_block_env(&MAINNET, &parent);
+        let job = generator.new_payload_job(attributes).unwrap();
+
+        assert_eq!(job.gas_limit(), ETHEREUM_BLOCK_GAS_LIMIT);
+        assert_eq!(U256::from(job.gas_limit()), block_env.gas_limit);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_current_state_root() {
+        let (client, attributes) = test_client();
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            eager_config(),
+            ImprovingPayloadBuilder::default(),
+        );
+        let mut job = generator.new_payload_job(attributes).unwrap();
+        assert_eq!(job.current_state_root(), None);
+
+        poll_until(&mut job, |job| job.improvements() > 0).await;
+        let best = job.best_payload().unwrap();
+        assert_eq!(job.current_state_root(), Some(best.block().state_root));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn builds_with_overridden_fee_recipient() {
+        let (client, mut attributes) = test_client();
+        attributes.suggested_fee_recipient = Address::with_last_byte(1);
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            eager_config(),
+            ImprovingPayloadBuilder::default(),
+        );
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        poll_until(&mut job, |job| job.improvements() > 0).await;
+        assert_eq!(job.best_payload().unwrap().block().beneficiary, Address::with_last_byte(1));
+
+        job.set_fee_recipient(Address::with_last_byte(2)).unwrap();
+        // the previous best payload is kept until the rebuild finishes
+        assert_eq!(job.best_payload().unwrap().block().beneficiary, Address::with_last_byte(1));
+
+        let improvements = job.improvements();
+        poll_until(&mut job, |job| job.improvements() > improvements).await;
+        assert_eq!(job.best_payload().unwrap().block().beneficiary, Address::with_last_byte(2));
+    }
+
+    #[tokio::test]
+    async fn reports_parent_hash() {
+        let (client, attributes) = test_client();
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            BasicPayloadJobGeneratorConfig::default(),
+            ImprovingPayloadBuilder::default(),
+        );
+
+        let job = generator.new_payload_job(attributes.clone()).unwrap();
+        assert_eq!(job.parent_hash(), attributes.parent);
+        assert_eq!(ResolveOnce::new(job).parent_hash(), attributes.parent);
+    }
+
+    #[tokio::test]
+    async fn rejects_non_canonical_parent() {
+        let client = MockEthProvider::default();
+        let genesis = Block::default();
+        let genesis_hash = genesis.header.hash_slow();
+        client.add_block(genesis_hash, genesis);
+
+        let mut canonical = Block::default();
+        canonical.header.number = 1;
+        canonical.header.parent_hash = genesis_hash;
+        let canonical_hash = canonical.header.hash_slow();
+        client.add_block(canonical_hash, canonical);
+
+        // a known block on a side chain, without a canonical header
+        let mut side = Block::default();
+        side.header.number = 1;
+        side.header.parent_hash = genesis_hash;
+        side.header.gas_limit = 1;
+        let side_hash = side.header.hash_slow();
+        client.blocks.lock().insert(side_hash, side);
+
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            BasicPayloadJobGeneratorConfig::default(),
+            ImprovingPayloadBuilder::default(),
+        );
+
+        let err = generator.new_payload_job(test_attributes(side_hash)).unwrap_err();
+        assert!(matches!(err, PayloadBuilderError::NonCanonicalParent(hash) if hash == side_hash));
+
+        let job = generator.new_payload_job(test_attributes(canonical_hash)).unwrap();
+        assert_eq!(job.parent_hash(), canonical_hash);
+    }
+
+    #[tokio::test]
+    async fn builds_on_parent_override() {
+        let client = MockEthProvider::default();
+        let mut historical = Block::default();
+        historical.header.state_root = B256::with_last_byte(1);
+        let historical_hash = historical.header.hash_slow();
+        client.add_block(historical_hash, historical);
+
+        let mut head = Block::default();
+        head.header.number = 1;
+        head.header.parent_hash = historical_hash;
+        head.header.state_root = B256::with_last_byte(2);
+        let head_hash = head.header.hash_slow();
+        client.add_block(head_hash, head);
+
+        let generator = |state_root| {
+            test_generator(
+                client.clone(),
+                NoopTransactionPool::default(),
+                BasicPayloadJobGeneratorConfig::default().parent_override(
+                    head_hash,
+                    historical_hash,
+                    state_root,
+                ),
+                ImprovingPayloadBuilder::default(),
+            )
+        };
+        let attributes = test_attributes(head_hash);
+
+        let job = generator(B256::with_last_byte(1)).new_payload_job(attributes.clone()).unwrap();
+        assert_eq!(job.config.parent_block.hash(), historical_hash);
+        assert_eq!(job.parent_hash(), historical_hash);
+        assert_eq!(job.config.parent_block.number, 0);
+
+        let err = generator(B256::with_last_byte(2)).new_payload_job(attributes).unwrap_err();
+        assert!(matches!(
+            err,
+            PayloadBuilderError::MissingParentState { block, .. } if block == historical_hash
+        ));
+    }
+
+    #[tokio::test]
+    async fn parent_override_only_applies_to_its_parent() {
+        let (client, attributes) = test_client();
+        let mut head = Block::default();
+        head.header.number = 1;
+        head.header.parent_hash = attributes.parent;
+        let head_hash = head.header.hash_slow();
+        client.add_block(head_hash, head);
+
+        let generator = test_generator(
+            client,
+            NoopTransactionPool::default(),
+            BasicPayloadJobGeneratorConfig::default().parent_override(
+                head_hash,
+                attributes.parent,
+                EMPTY_ROOT_HASH,
+            ),
+            ImprovingPayloadBuilder::default(),
+        );
+
+        // jobs without an override build on the requested parent
+        let job = generator.new_payload_job(attributes.clone()).unwrap();
+        assert_eq!(job.parent_hash(), attributes.parent);
+
+        let job = generator.new_payload_job(test_attributes(head_hash)).unwrap();
+        assert_eq!(job.parent_hash(), attributes.parent);
+    }
+}
@@ -2,6 +2,7 @@
 
 use reth_interfaces::{provider::ProviderError, RethError};
 use reth_primitives::{revm_primitives::EVMError, B256};
+use reth_rpc_types::engine::PayloadId;
 use reth_transaction_pool::BlobStoreError;
 use tokio::sync::oneshot;
 
@@ -11,6 +12,23 @@ pub enum PayloadBuilderError {
     /// Thrown whe the parent block is missing.
     #[error("missing parent block {0}")]
     MissingParentBlock(B256),
+    /// Thrown when the state of the parent block to build on is not available.
+    #[error("missing state {state_root} of parent block {block}")]
+    MissingParentState {
+        /// Hash of the parent block.
+        block: B256,
+        /// State root the payload was requested to build on.
+        state_root: B256,
+    },
+    /// Thrown when the parent block to build on is known but not part of the canonical chain.
+    #[error("parent block {0} is not canonical")]
+    NonCanonicalParent(B256),
+    /// Thrown when there's no payload job for the given identifier.
+    #[error("missing payload job {0}")]
+    MissingPayloadJob(PayloadId),
+    /// Thrown when the payload job doesn't support overriding the fee recipient.
+    #[error("payload job doesn't support overriding the fee recipient")]
+    FeeRecipientOverrideUnsupported,
     /// An oneshot channels has been closed.
     #[error("sender has been dropped")]
     ChannelClosed,
@@ -1,5 +1,7 @@
 use futures_util::Stream;
 use reth_node_api::EngineTypes;
+use reth_rpc_types::engine::PayloadId;
+use std::time::SystemTime;
 use tokio::sync::broadcast;
 use tokio_stream::{
     wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
@@ -37,3 +39,37 @@ impl<Engine: EngineTypes + 'static> PayloadEvents<Engine> {
         event_stream.next().await
     }
 }
+
+/// The stage of a payload job's lifecycle that a [`PayloadLifecycleEvent`] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadLifecycleKind {
+    /// A new job was created in response to a fork choice update with payload attributes.
+    Created,
+    /// The job replaced its best payload with a better one.
+    Improved,
+    /// The payload was requested by the CL.
+    Resolved,
+    /// The job terminated on its own, e.g. because its deadline was reached or it failed.
+    Cancelled,
+}
+
+/// A lifecycle event of a single payload job.
+///
+/// Events are emitted by the [`PayloadBuilderService`](crate::PayloadBuilderService) task, so all
+/// events for the same [`PayloadId`] are observed in the order they happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PayloadLifecycleEvent {
+    /// The identifier of the payload job.
+    pub id: PayloadId,
+    /// What happened to the job.
+    pub kind: PayloadLifecycleKind,
+    /// When the event was emitted.
+    pub timestamp: SystemTime,
+}
+
+impl PayloadLifecycleEvent {
+    /// Creates a new event of the given kind for the job, timestamped with the current time.
+    pub fn new(id: PayloadId, kind: PayloadLifecycleKind) -> Self {
+        Self { id, kind, timestamp: SystemTime::now() }
+    }
+}
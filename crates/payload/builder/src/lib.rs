@@ -104,8 +104,10 @@ pub mod database;
 pub mod error;
 mod events;
 mod metrics;
+mod min_gas;
 mod optimism;
 mod payload;
+mod resolve_once;
 mod service;
 mod traits;
 
@@ -114,8 +116,14 @@ pub mod noop;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
+pub use events::{PayloadLifecycleEvent, PayloadLifecycleKind};
+pub use min_gas::MinGasPayloadJob;
 pub use optimism::OptimismPayloadBuilderAttributes;
 pub use payload::{EthBuiltPayload, EthPayloadBuilderAttributes};
 pub use reth_rpc_types::engine::PayloadId;
+pub use resolve_once::ResolveOnce;
 pub use service::{PayloadBuilderHandle, PayloadBuilderService, PayloadStore};
-pub use traits::{KeepPayloadJobAlive, PayloadJob, PayloadJobGenerator};
+pub use traits::{
+    KeepPayloadJobAlive, NormalizedAttributes, PayloadJob, PayloadJobDiagnostics,
+    PayloadJobGenerator,
+};
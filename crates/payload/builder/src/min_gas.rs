@@ -0,0 +1,302 @@
+//! A [PayloadJob] wrapper that holds back payloads below a gas used floor.
+
+use crate::{
+    error::PayloadBuilderError, traits::KeepPayloadJobAlive, PayloadJob, PayloadJobDiagnostics,
+};
+use futures_util::FutureExt;
+use reth_node_api::BuiltPayload;
+use reth_primitives::{Address, B256, U256};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::watch;
+use tracing::debug;
+
+type PayloadFuture<P> = Pin<Box<dyn Future<Output = Result<P, PayloadBuilderError>> + Send + Sync>>;
+
+/// A [PayloadJob] that avoids resolving near-empty payloads.
+///
+/// If the best payload of the wrapped job uses less than `min_gas_used` gas when the payload is
+/// requested, the job keeps building for up to `max_wait` and the request resolves as soon as a
+/// payload meets the floor, or with the best payload built until then.
+///
+/// Better payloads are detected through [PayloadJob::improvements], so the wrapped job needs to
+/// report its improvements for the floor to be checked again.
+#[derive(Debug)]
+pub struct MinGasPayloadJob<J: PayloadJob> {
+    /// The wrapped job.
+    job: J,
+    /// The gas the resolved payload should use at least.
+    min_gas_used: u64,
+    /// How long to keep building for a payload that meets the floor.
+    max_wait: Duration,
+    /// The best payload of the job, observed by pending resolve requests.
+    best_payload: Option<watch::Sender<J::BuiltPayload>>,
+    /// The improvements of the job at the time the best payload was last observed.
+    improvements: u64,
+}
+
+impl<J: PayloadJob> MinGasPayloadJob<J> {
+    /// Wraps the given job.
+    pub fn new(job: J, min_gas_used: u64, max_wait: Duration) -> Self {
+        Self { job, min_gas_used, max_wait, best_payload: None, improvements: 0 }
+    }
+
+    /// Returns the gas the resolved payload should use at least.
+    pub fn min_gas_used(&self) -> u64 {
+        self.min_gas_used
+    }
+
+    /// Returns the wrapped job.
+    pub fn into_inner(self) -> J {
+        self.job
+    }
+}
+
+impl<J> Future for MinGasPayloadJob<J>
+where
+    J: PayloadJob + Unpin,
+{
+    type Output = Result<(), PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let res = this.job.poll_unpin(cx);
+
+        if let Some(best_payload) = this.best_payload.as_ref().filter(|tx| !tx.is_closed()) {
+            let improvements = this.job.improvements();
+            if improvements != this.improvements {
+                this.improvements = improvements;
+                if let Ok(payload) = this.job.best_payload() {
+                    best_payload.send_replace(payload);
+                }
+            }
+        }
+
+        res
+    }
+}
+
+impl<J> PayloadJob for MinGasPayloadJob<J>
+where
+    J: PayloadJob + Unpin,
+    J::BuiltPayload: 'static,
+{
+    type PayloadAttributes = J::PayloadAttributes;
+    type ResolvePayloadFuture = PayloadFuture<J::BuiltPayload>;
+    type BuiltPayload = J::BuiltPayload;
+
+    fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        self.job.best_payload()
+    }
+
+    fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
+        self.job.payload_attributes()
+    }
+
+    fn parent_hash(&self) -> B256 {
+        self.job.parent_hash()
+    }
+
+    fn improvements(&self) -> u64 {
+        self.job.improvements()
+    }
+
+    fn iterations(&self) -> u64 {
+        self.job.iterations()
+    }
+
+    fn tx_count(&self) -> usize {
+        self.job.tx_count()
+    }
+
+    fn source_tx_count(&self) -> usize {
+        self.job.source_tx_count()
+    }
+
+    fn gas_limit(&self) -> u64 {
+        self.job.gas_limit()
+    }
+
+    fn current_state_root(&self) -> Option<B256> {
+        self.job.current_state_root()
+    }
+
+    fn improvement_headroom(&self) -> Option<U256> {
+        self.job.improvement_headroom()
+    }
+
+    fn set_fee_recipient(&mut self, recipient: Address) -> Result<(), PayloadBuilderError> {
+        self.job.set_fee_recipient(recipient)
+    }
+
+    fn mark_superseded(&mut self) {
+        self.job.mark_superseded()
+    }
+
+    fn is_superseded(&self) -> bool {
+        self.job.is_superseded()
+    }
+
+    fn diagnostics(&self) -> PayloadJobDiagnostics {
+        self.job.diagnostics()
+    }
+
+    fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        let best_payload = match self.job.best_payload() {
+            Ok(payload) if payload.block().gas_used < self.min_gas_used => payload,
+            _ => {
+                // the floor is met, or there is no payload to wait on
+                let (fut, keep_alive) = self.job.resolve();
+                return (Box::pin(fut), keep_alive)
+            }
+        };
+
+        debug!(
+            target: "payload_builder",
+            gas_used = best_payload.block().gas_used,
+            min_gas_used = self.min_gas_used,
+            "best payload below gas floor, waiting for a better payload"
+        );
+
+        self.improvements = self.job.improvements();
+        let mut rx = match &self.best_payload {
+            Some(tx) => {
+                tx.send_replace(best_payload);
+                tx.subscribe()
+            }
+            None => {
+                let (tx, rx) = watch::channel(best_payload);
+                self.best_payload = Some(tx);
+                rx
+            }
+        };
+
+        let min_gas_used = self.min_gas_used;
+        let max_wait = self.max_wait;
+        let fut = async move {
+            let _ = tokio::time::timeout(
+                max_wait,
+                rx.wait_for(|payload| payload.block().gas_used >= min_gas_used),
+            )
+            .await;
+            let payload = rx.borrow().clone();
+            Ok(payload)
+        };
+
+        (Box::pin(fut), KeepPayloadJobAlive::Yes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EthBuiltPayload, EthPayloadBuilderAttributes, PayloadId};
+    use futures_util::poll;
+    use reth_primitives::{Block, Withdrawals, B256, U256};
+    use std::collections::VecDeque;
+
+    /// A job that uses the next gas value of its schedule for its best payload on every poll.
+    #[derive(Debug)]
+    struct GasPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        gas_used: u64,
+        schedule: VecDeque<u64>,
+        improvements: u64,
+    }
+
+    impl GasPayloadJob {
+        fn new(gas_used: u64, schedule: impl IntoIterator<Item = u64>) -> Self {
+            let attr = EthPayloadBuilderAttributes {
+                id: PayloadId::new([1; 8]),
+                parent: B256::ZERO,
+                timestamp: 1,
+                suggested_fee_recipient: Default::default(),
+                prev_randao: B256::ZERO,
+                withdrawals: Withdrawals::default(),
+                parent_beacon_block_root: None,
+            };
+            Self { attr, gas_used, schedule: schedule.into_iter().collect(), improvements: 0 }
+        }
+    }
+
+    impl Future for GasPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            if let Some(gas_used) = this.schedule.pop_front() {
+                this.gas_used = gas_used;
+                this.improvements += 1;
+            }
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for GasPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let mut block = Block::default();
+            block.header.gas_used = self.gas_used;
+            Ok(EthBuiltPayload::new(self.attr.id, block.seal_slow(), U256::ZERO))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn improvements(&self) -> u64 {
+            self.improvements
+        }
+
+        fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (futures_util::future::ready(self.best_payload()), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_above_floor() {
+        let mut job =
+            MinGasPayloadJob::new(GasPayloadJob::new(100, []), 100, Duration::from_secs(10));
+
+        let (fut, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::No);
+        assert_eq!(fut.await.unwrap().block().gas_used, 100);
+    }
+
+    #[tokio::test]
+    async fn resolve_below_floor_waits_for_better_payload() {
+        let mut job =
+            MinGasPayloadJob::new(GasPayloadJob::new(0, [50, 150]), 100, Duration::from_secs(10));
+
+        let (mut fut, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::Yes);
+        assert!(poll!(&mut fut).is_pending());
+
+        // still below the floor
+        assert!(poll!(&mut job).is_pending());
+        assert!(poll!(&mut fut).is_pending());
+
+        assert!(poll!(&mut job).is_pending());
+        assert_eq!(fut.await.unwrap().block().gas_used, 150);
+    }
+
+    #[tokio::test]
+    async fn resolve_below_floor_deadline() {
+        let mut job =
+            MinGasPayloadJob::new(GasPayloadJob::new(0, [50]), 100, Duration::from_millis(10));
+
+        let (fut, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::Yes);
+
+        assert!(poll!(&mut job).is_pending());
+        assert_eq!(fut.await.unwrap().block().gas_used, 50);
+    }
+}
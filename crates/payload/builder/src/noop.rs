@@ -1,6 +1,6 @@
 //! A payload builder service task that does nothing.
 
-use crate::{service::PayloadServiceCommand, PayloadBuilderHandle};
+use crate::{error::PayloadBuilderError, service::PayloadServiceCommand, PayloadBuilderHandle};
 use futures_util::{ready, StreamExt};
 use reth_node_api::{EngineTypes, PayloadBuilderAttributes};
 use std::{
@@ -52,7 +52,14 @@ where
                 PayloadServiceCommand::BestPayload(_, tx) => tx.send(None).ok(),
                 PayloadServiceCommand::PayloadAttributes(_, tx) => tx.send(None).ok(),
                 PayloadServiceCommand::Resolve(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::TimeToFirstPayload(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::IsBuilding(_, tx) => tx.send(None).ok(),
+                PayloadServiceCommand::OnResolved(_, _) => None,
+                PayloadServiceCommand::SetFeeRecipient(id, _, tx) => {
+                    tx.send(Err(PayloadBuilderError::MissingPayloadJob(id))).ok()
+                }
                 PayloadServiceCommand::Subscribe(_) => None,
+                PayloadServiceCommand::SubscribeLifecycle(_) => None,
             };
         }
     }
@@ -212,6 +212,13 @@ impl PayloadBuilderAttributes for EthPayloadBuilderAttributes {
         self.id
     }
 
+    /// Re-derives the [PayloadId] with absent withdrawals and an empty withdrawals list treated as
+    /// equivalent, since both are represented as empty [Withdrawals].
+    fn normalized(mut self) -> Self {
+        self.id = normalized_payload_id(&self);
+        self
+    }
+
     fn parent(&self) -> B256 {
         self.parent
     }
@@ -306,3 +313,111 @@ pub(crate) fn payload_id(parent: &B256, attributes: &PayloadAttributes) -> Paylo
     let out = hasher.finalize();
     PayloadId::new(out.as_slice()[..8].try_into().expect("sufficient length"))
 }
+
+/// Generates the payload id of the canonical representation of the [EthPayloadBuilderAttributes].
+///
+/// This matches [payload_id] of the original [PayloadAttributes], except that empty withdrawals
+/// are not hashed.
+fn normalized_payload_id(attributes: &EthPayloadBuilderAttributes) -> PayloadId {
+    use sha2::Digest;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(attributes.parent.as_slice());
+    hasher.update(&attributes.timestamp.to_be_bytes()[..]);
+    hasher.update(attributes.prev_randao.as_slice());
+    hasher.update(attributes.suggested_fee_recipient.as_slice());
+    if !attributes.withdrawals.is_empty() {
+        let mut buf = Vec::new();
+        attributes.withdrawals.encode(&mut buf);
+        hasher.update(buf);
+    }
+
+    if let Some(parent_beacon_block) = attributes.parent_beacon_block_root {
+        hasher.update(parent_beacon_block);
+    }
+
+    let out = hasher.finalize();
+    PayloadId::new(out.as_slice()[..8].try_into().expect("sufficient length"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NormalizedAttributes;
+    use reth_primitives::{Block, Signature, Transaction, TransactionSigned, TxLegacy};
+    use reth_rpc_types::Withdrawal;
+    use std::cmp::Ordering;
+
+    fn attributes(withdrawals: Option<Vec<Withdrawal>>) -> PayloadAttributes {
+        PayloadAttributes {
+            timestamp: 1,
+            prev_randao: B256::with_last_byte(1),
+            suggested_fee_recipient: Address::with_last_byte(1),
+            withdrawals,
+            parent_beacon_block_root: None,
+        }
+    }
+
+    #[test]
+    fn normalize_empty_withdrawals() {
+        let parent = B256::with_last_byte(2);
+        let absent = EthPayloadBuilderAttributes::new(parent, attributes(None));
+        let empty = EthPayloadBuilderAttributes::new(parent, attributes(Some(vec![])));
+        assert_ne!(absent.payload_id(), empty.payload_id());
+
+        let absent = NormalizedAttributes::new(absent);
+        let empty = NormalizedAttributes::new(empty);
+        assert_eq!(absent, empty);
+        assert_eq!(absent.payload_id(), empty.payload_id());
+    }
+
+    #[test]
+    fn normalize_keeps_canonical_id() {
+        let parent = B256::with_last_byte(2);
+        let withdrawal = Withdrawal {
+            index: 1,
+            validator_index: 2,
+            address: Address::with_last_byte(3),
+            amount: 4,
+        };
+
+        for withdrawals in [None, Some(vec![withdrawal])] {
+            let attr = EthPayloadBuilderAttributes::new(parent, attributes(withdrawals));
+            assert_eq!(NormalizedAttributes::new(attr.clone()).payload_id(), attr.payload_id());
+        }
+    }
+
+    fn built_payload(fees: u64, txs: Vec<TransactionSigned>) -> EthBuiltPayload {
+        let block = Block { body: txs, ..Default::default() };
+        EthBuiltPayload::new(PayloadId::new([1; 8]), block.seal_slow(), U256::from(fees))
+    }
+
+    fn transaction(nonce: u64) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy { nonce, ..Default::default() }),
+            Signature::default(),
+        )
+    }
+
+    #[test]
+    fn cmp_payload_value() {
+        let low = built_payload(1, vec![]);
+        let high = built_payload(2, vec![]);
+
+        assert_eq!(high.cmp_value(&low), Ordering::Greater);
+        assert_eq!(low.cmp_value(&high), Ordering::Less);
+        assert_eq!(low.cmp_value(&built_payload(1, vec![transaction(0)])), Ordering::Equal);
+    }
+
+    #[test]
+    fn same_payload_transactions() {
+        let payload = built_payload(1, vec![transaction(0), transaction(1)]);
+
+        assert!(payload.same_transactions(&payload));
+        assert!(payload.same_transactions(&built_payload(2, vec![transaction(1), transaction(0)])));
+        assert!(!payload.same_transactions(&built_payload(1, vec![transaction(0)])));
+        let different = built_payload(1, vec![transaction(0), transaction(2)]);
+        assert!(!payload.same_transactions(&different));
+        let duplicated = built_payload(1, vec![transaction(0), transaction(0)]);
+        assert!(!payload.same_transactions(&duplicated));
+    }
+}
@@ -0,0 +1,225 @@
+//! A [PayloadJob] wrapper that resolves the payload of the wrapped job at most once.
+
+use crate::{
+    error::PayloadBuilderError, traits::KeepPayloadJobAlive, PayloadJob, PayloadJobDiagnostics,
+};
+use futures_util::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use reth_primitives::{Address, B256, U256};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+type PayloadFuture<P> = Pin<Box<dyn Future<Output = Result<P, PayloadBuilderError>> + Send + Sync>>;
+
+/// The shared resolution of the wrapped job.
+type SharedResolve<P> = Shared<BoxFuture<'static, Result<P, Arc<PayloadBuilderError>>>>;
+
+/// A [PayloadJob] that resolves the wrapped job at most once.
+///
+/// Every [PayloadJob::resolve] call after the first returns a future that yields the same payload
+/// as the first one, instead of resolving the wrapped job again. This prevents racing
+/// resolutions from returning different, possibly stale, payloads.
+///
+/// Since the resolution is shared, errors of the wrapped job are returned as
+/// [PayloadBuilderError::Other].
+pub struct ResolveOnce<J: PayloadJob> {
+    /// The wrapped job.
+    job: J,
+    /// The resolution of the first [PayloadJob::resolve] call.
+    resolved: Option<(SharedResolve<J::BuiltPayload>, KeepPayloadJobAlive)>,
+}
+
+impl<J: PayloadJob> ResolveOnce<J> {
+    /// Wraps the given job.
+    pub fn new(job: J) -> Self {
+        Self { job, resolved: None }
+    }
+
+    /// Returns `true` if the job was already resolved.
+    pub fn is_resolved(&self) -> bool {
+        self.resolved.is_some()
+    }
+
+    /// Returns the wrapped job.
+    pub fn into_inner(self) -> J {
+        self.job
+    }
+}
+
+impl<J: PayloadJob + std::fmt::Debug> std::fmt::Debug for ResolveOnce<J> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolveOnce")
+            .field("job", &self.job)
+            .field("resolved", &self.is_resolved())
+            .finish()
+    }
+}
+
+impl<J> Future for ResolveOnce<J>
+where
+    J: PayloadJob + Unpin,
+{
+    type Output = Result<(), PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().job.poll_unpin(cx)
+    }
+}
+
+impl<J> PayloadJob for ResolveOnce<J>
+where
+    J: PayloadJob + Unpin,
+    J::BuiltPayload: 'static,
+{
+    type PayloadAttributes = J::PayloadAttributes;
+    type ResolvePayloadFuture = PayloadFuture<J::BuiltPayload>;
+    type BuiltPayload = J::BuiltPayload;
+
+    fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        self.job.best_payload()
+    }
+
+    fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
+        self.job.payload_attributes()
+    }
+
+    fn parent_hash(&self) -> B256 {
+        self.job.parent_hash()
+    }
+
+    fn improvements(&self) -> u64 {
+        self.job.improvements()
+    }
+
+    fn iterations(&self) -> u64 {
+        self.job.iterations()
+    }
+
+    fn tx_count(&self) -> usize {
+        self.job.tx_count()
+    }
+
+    fn source_tx_count(&self) -> usize {
+        self.job.source_tx_count()
+    }
+
+    fn gas_limit(&self) -> u64 {
+        self.job.gas_limit()
+    }
+
+    fn current_state_root(&self) -> Option<B256> {
+        self.job.current_state_root()
+    }
+
+    fn improvement_headroom(&self) -> Option<U256> {
+        self.job.improvement_headroom()
+    }
+
+    fn set_fee_recipient(&mut self, recipient: Address) -> Result<(), PayloadBuilderError> {
+        self.job.set_fee_recipient(recipient)
+    }
+
+    fn mark_superseded(&mut self) {
+        self.job.mark_superseded()
+    }
+
+    fn is_superseded(&self) -> bool {
+        self.job.is_superseded()
+    }
+
+    fn diagnostics(&self) -> PayloadJobDiagnostics {
+        let mut diagnostics = self.job.diagnostics();
+        diagnostics.resolved |= self.is_resolved();
+        diagnostics
+    }
+
+    fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        let (resolved, keep_alive) = self.resolved.get_or_insert_with(|| {
+            let (fut, keep_alive) = self.job.resolve();
+            (fut.map(|res| res.map_err(Arc::new)).boxed().shared(), keep_alive)
+        });
+
+        let fut = resolved.clone().map(|res| res.map_err(PayloadBuilderError::other));
+        (Box::pin(fut), *keep_alive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EthBuiltPayload, EthPayloadBuilderAttributes, PayloadId};
+    use reth_node_api::BuiltPayload;
+    use reth_primitives::{Block, Withdrawals, B256, U256};
+
+    /// A job that builds a better payload every time it's resolved.
+    #[derive(Debug)]
+    struct CountingPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        resolved: u64,
+    }
+
+    impl Future for CountingPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for CountingPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let mut block = Block::default();
+            block.header.gas_used = self.resolved;
+            Ok(EthBuiltPayload::new(self.attr.id, block.seal_slow(), U256::ZERO))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            self.resolved += 1;
+            (futures_util::future::ready(self.best_payload()), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_twice() {
+        let attr = EthPayloadBuilderAttributes {
+            id: PayloadId::new([1; 8]),
+            parent: B256::ZERO,
+            timestamp: 1,
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Withdrawals::default(),
+            parent_beacon_block_root: None,
+        };
+        let mut job = ResolveOnce::new(CountingPayloadJob { attr, resolved: 0 });
+        assert!(!job.is_resolved());
+
+        let (first, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::No);
+        let (second, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::No);
+        assert!(job.is_resolved());
+
+        let second = second.await.unwrap();
+        let first = first.await.unwrap();
+        assert_eq!(first.block(), second.block());
+        assert_eq!(first.block().gas_used, 1);
+
+        // the wrapped job was resolved only once
+        assert_eq!(job.into_inner().resolved, 1);
+    }
+}
@@ -5,30 +5,48 @@
 
 use crate::{
     error::PayloadBuilderError,
-    events::{Events, PayloadEvents},
+    events::{Events, PayloadEvents, PayloadLifecycleEvent, PayloadLifecycleKind},
     metrics::PayloadBuilderServiceMetrics,
     traits::PayloadJobGenerator,
     KeepPayloadJobAlive, PayloadJob,
 };
-use futures_util::{future::FutureExt, Stream, StreamExt};
+use futures_util::{
+    future::{self, FutureExt},
+    stream, Stream, StreamExt,
+};
 use reth_node_api::{BuiltPayload, EngineTypes, PayloadBuilderAttributes};
+use reth_primitives::{Address, Bytes, B256, U256};
 use reth_provider::CanonStateNotification;
 use reth_rpc_types::engine::PayloadId;
 use std::{
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
     fmt,
     future::Future,
     pin::Pin,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
+    time::Duration,
 };
-use tokio::sync::{
-    broadcast, mpsc,
-    oneshot::{self, error::RecvError},
+use tokio::{
+    sync::{
+        broadcast, mpsc,
+        oneshot::{self, error::RecvError},
+    },
+    time::{self, Instant},
 };
-use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 use tracing::{debug, info, trace, warn};
 
 type PayloadFuture<P> = Pin<Box<dyn Future<Output = Result<P, PayloadBuilderError>> + Send + Sync>>;
 
+/// Sender half of a [`PayloadBuilderHandle::on_resolved`] notification.
+type ResolvedListener<P> = oneshot::Sender<Result<Arc<P>, PayloadBuilderError>>;
+
+/// Resolved payloads by identifier and when they were resolved, see
+/// [`PayloadBuilderService::with_resolved_payload_ttl`].
+type ResolvedPayloads<P> = Arc<Mutex<HashMap<PayloadId, (Instant, P)>>>;
+
 /// A communication channel to the [PayloadBuilderService] that can retrieve payloads.
 #[derive(Debug)]
 pub struct PayloadStore<Engine: EngineTypes> {
@@ -62,6 +80,47 @@ where
         self.inner.best_payload(id).await
     }
 
+    /// Resolves all given payload jobs concurrently, waiting at most until the `deadline`.
+    ///
+    /// Jobs that have not resolved by the deadline yield the best payload built before resolving
+    /// started. The results are returned in the same order as the `ids`.
+    pub async fn resolve_all(
+        &self,
+        ids: &[PayloadId],
+        deadline: Instant,
+    ) -> Vec<Option<Result<Engine::BuiltPayload, PayloadBuilderError>>> {
+        self.inner.resolve_all(ids, deadline).await
+    }
+
+    /// Returns the best payloads built so far for the given identifiers, sorted by descending
+    /// value.
+    ///
+    /// Identifiers without an active job, or whose best payload can't be built, are omitted.
+    ///
+    /// Note: this merely returns the best payloads so far and does not resolve the jobs.
+    pub async fn best_payloads_ranked(
+        &self,
+        ids: &[PayloadId],
+    ) -> Vec<(PayloadId, Arc<Engine::BuiltPayload>)> {
+        self.inner.best_payloads_ranked(ids).await
+    }
+
+    /// Waits until the payload job for the given identifier has built a payload worth at least
+    /// `min`, and returns that payload.
+    ///
+    /// Returns `None` if the `deadline` passes first, or if there's no active job for the given
+    /// identifier.
+    ///
+    /// Note: this does not resolve the job.
+    pub async fn wait_for_value(
+        &self,
+        id: PayloadId,
+        min: U256,
+        deadline: Instant,
+    ) -> Option<Arc<Engine::BuiltPayload>> {
+        self.inner.wait_for_value(id, min, deadline).await
+    }
+
     /// Returns the payload attributes associated with the given identifier.
     ///
     /// Note: this returns the attributes of the payload and does not resolve the job.
@@ -130,6 +189,23 @@ where
         }
     }
 
+    /// Resolves all given payload jobs concurrently, waiting at most until the `deadline`.
+    ///
+    /// Jobs that have not resolved by the deadline yield the best payload built before resolving
+    /// started.
+    async fn resolve_all(
+        &self,
+        ids: &[PayloadId],
+        deadline: Instant,
+    ) -> Vec<Option<Result<Engine::BuiltPayload, PayloadBuilderError>>> {
+        // snapshot the best payloads first, since resolving may terminate the jobs
+        let best = future::join_all(ids.iter().map(|id| self.best_payload(*id))).await;
+        let resolved =
+            future::join_all(ids.iter().map(|id| time::timeout_at(deadline, self.resolve(*id))))
+                .await;
+        resolved.into_iter().zip(best).map(|(resolved, best)| resolved.unwrap_or(best)).collect()
+    }
+
     /// Returns the best payload for the given identifier.
     async fn best_payload(
         &self,
@@ -140,6 +216,47 @@ where
         rx.await.ok()?
     }
 
+    /// Returns the best payloads built so far for the given identifiers, sorted by descending
+    /// value. Payloads of the same value keep the order of the `ids`.
+    async fn best_payloads_ranked(
+        &self,
+        ids: &[PayloadId],
+    ) -> Vec<(PayloadId, Arc<Engine::BuiltPayload>)> {
+        let best = future::join_all(ids.iter().map(|id| self.best_payload(*id))).await;
+        let mut ranked = ids
+            .iter()
+            .zip(best)
+            .filter_map(|(id, best)| Some((*id, Arc::new(best?.ok()?))))
+            .collect::<Vec<_>>();
+        ranked.sort_by_key(|(_, payload)| Reverse(payload.fees()));
+        ranked
+    }
+
+    /// Waits until the payload job for the given identifier has built a payload worth at least
+    /// `min`, or the `deadline` passes.
+    async fn wait_for_value(
+        &self,
+        id: PayloadId,
+        min: U256,
+        deadline: Instant,
+    ) -> Option<Arc<Engine::BuiltPayload>> {
+        // subscribe before checking the best payload, so no improvement is missed in between
+        let mut updates = Box::pin(self.lifecycle_events().filter(move |event| {
+            future::ready(event.id == id && event.kind != PayloadLifecycleKind::Created)
+        }));
+        let wait = async {
+            loop {
+                // the job is gone once it was resolved or cancelled
+                let payload = self.best_payload(id).await?.ok()?;
+                if payload.fees() >= min {
+                    return Some(Arc::new(payload))
+                }
+                updates.next().await?;
+            }
+        };
+        time::timeout_at(deadline, wait).await.ok().flatten()
+    }
+
     /// Returns the payload attributes associated with the given identifier.
     ///
     /// Note: this returns the attributes of the payload and does not resolve the job.
@@ -184,6 +301,131 @@ where
         let _ = self.to_service.send(PayloadServiceCommand::Subscribe(tx));
         Ok(PayloadEvents { receiver: rx.await? })
     }
+
+    /// Returns how long after the creation of the payload job its first non-empty payload was
+    /// built.
+    ///
+    /// Returns `None` if the job hasn't built a non-empty payload yet, or if there's no active job
+    /// for the given identifier.
+    pub async fn time_to_first_payload(&self, id: PayloadId) -> Option<Duration> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::TimeToFirstPayload(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Returns whether the payload job for the given identifier is still building.
+    ///
+    /// Returns `Some(true)` while the job is improving its payload, `Some(false)` once it has
+    /// been resolved and `None` if there's no job for the given identifier.
+    ///
+    /// Note: only a limited number of resolved jobs is remembered, so for jobs that were resolved
+    /// long ago this returns `None`.
+    pub async fn is_building(&self, id: PayloadId) -> Option<bool> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::IsBuilding(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Overrides the fee recipient of the given payload job, so that all payloads it builds from
+    /// now on credit `recipient`, see [PayloadJob::set_fee_recipient].
+    pub async fn set_fee_recipient(
+        &self,
+        id: PayloadId,
+        recipient: Address,
+    ) -> Result<(), PayloadBuilderError> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service
+            .send(PayloadServiceCommand::SetFeeRecipient(id, recipient, tx))
+            .map_err(|_| PayloadBuilderError::ChannelClosed)?;
+        rx.await?
+    }
+
+    /// Cancels all payload jobs building on the given parent block, e.g. after a reorg abandoned
+    /// it, see [PayloadJob::parent_hash].
+    ///
+    /// Returns the number of cancelled jobs.
+    pub async fn cancel_by_parent(&self, parent: B256) -> usize {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::CancelByParent(parent, tx)).is_err() {
+            return 0
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Attaches an opaque attestation, e.g. an off-chain attestation of the payload value for
+    /// relay submission, to the given payload job. A previously attached attestation is replaced.
+    ///
+    /// The attestation can be retrieved with [PayloadBuilderHandle::attestation] while the job is
+    /// building and after it has been resolved.
+    pub async fn attach_attestation(
+        &self,
+        id: PayloadId,
+        attestation: Bytes,
+    ) -> Result<(), PayloadBuilderError> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service
+            .send(PayloadServiceCommand::AttachAttestation(id, attestation, tx))
+            .map_err(|_| PayloadBuilderError::ChannelClosed)?;
+        rx.await?
+    }
+
+    /// Sets the build cutoff of the given payload job, e.g. aligned to the slot boundary. A
+    /// previously set deadline is replaced.
+    ///
+    /// Once the deadline has passed, the job is no longer driven and its best payload is frozen,
+    /// but it can still be queried and resolved.
+    pub async fn set_deadline(
+        &self,
+        id: PayloadId,
+        deadline: Instant,
+    ) -> Result<(), PayloadBuilderError> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service
+            .send(PayloadServiceCommand::SetDeadline(id, deadline, tx))
+            .map_err(|_| PayloadBuilderError::ChannelClosed)?;
+        rx.await?
+    }
+
+    /// Returns the attestation attached to the given payload job, see
+    /// [PayloadBuilderHandle::attach_attestation].
+    ///
+    /// Note: attestations are only remembered for a limited number of resolved jobs, and are
+    /// dropped with jobs that finish or are cancelled without being resolved.
+    pub async fn attestation(&self, id: PayloadId) -> Option<Bytes> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::Attestation(id, tx)).ok()?;
+        rx.await.ok()?
+    }
+
+    /// Returns a receiver that yields the payload of the given job once it is resolved, or the
+    /// error if resolving or building the payload fails.
+    ///
+    /// The receiver is closed without a value if there's no active job for the given identifier,
+    /// or if the job finishes without being resolved.
+    pub fn on_resolved(
+        &self,
+        id: PayloadId,
+    ) -> oneshot::Receiver<Result<Arc<Engine::BuiltPayload>, PayloadBuilderError>> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::OnResolved(id, tx));
+        rx
+    }
+
+    /// Returns a stream of [PayloadLifecycleEvent]s for all payload jobs of the service.
+    ///
+    /// The subscription is registered with the service right away, so all events caused by
+    /// requests sent through any handle after this call are included. Events for the same
+    /// [PayloadId] are yielded in the order they occurred.
+    ///
+    /// Note: events are dropped if the stream falls too far behind the service.
+    pub fn lifecycle_events(&self) -> impl Stream<Item = PayloadLifecycleEvent> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::SubscribeLifecycle(tx));
+        stream::once(rx)
+            .filter_map(|rx| future::ready(rx.ok()))
+            .flat_map(BroadcastStream::new)
+            .filter_map(|event| future::ready(event.ok()))
+    }
 }
 
 impl<Engine> Clone for PayloadBuilderHandle<Engine>
@@ -215,6 +457,21 @@ where
     generator: Gen,
     /// All active payload jobs.
     payload_jobs: Vec<(Gen::Job, PayloadId)>,
+    /// Creation time of all active payload jobs, and how long it took them to build their first
+    /// non-empty payload.
+    job_timings: HashMap<PayloadId, (Instant, Option<Duration>)>,
+    /// Recently resolved payload jobs, oldest first, at most [`RESOLVED_JOBS_HISTORY`].
+    resolved_jobs: VecDeque<PayloadId>,
+    /// Listeners that are notified once the payload job with the given identifier is resolved.
+    resolved_listeners: HashMap<PayloadId, Vec<ResolvedListener<Engine::BuiltPayload>>>,
+    /// Attestations attached to active or recently resolved payload jobs.
+    attestations: HashMap<PayloadId, Bytes>,
+    /// Build cutoffs of active payload jobs, after which they are no longer polled.
+    deadlines: HashMap<PayloadId, Instant>,
+    /// How long resolved payloads are cached to serve repeated resolve requests, if at all.
+    resolved_payload_ttl: Option<Duration>,
+    /// Cached resolved payloads, only populated if [`Self::resolved_payload_ttl`] is set.
+    resolved_payloads: ResolvedPayloads<Engine::BuiltPayload>,
     /// Copy of the sender half, so new [`PayloadBuilderHandle`] can be created on demand.
     service_tx: mpsc::UnboundedSender<PayloadServiceCommand<Engine>>,
     /// Receiver half of the command channel.
@@ -225,10 +482,17 @@ where
     chain_events: St,
     /// Payload events handler, used to broadcast and subscribe to payload events.
     payload_events: broadcast::Sender<Events<Engine>>,
+    /// Lifecycle events of all payload jobs.
+    lifecycle_events: broadcast::Sender<PayloadLifecycleEvent>,
 }
 
 const PAYLOAD_EVENTS_BUFFER_SIZE: usize = 20;
 
+const PAYLOAD_LIFECYCLE_EVENTS_BUFFER_SIZE: usize = 100;
+
+/// Number of resolved payload jobs that are remembered, see [`PayloadBuilderHandle::is_building`].
+const RESOLVED_JOBS_HISTORY: usize = 64;
+
 // === impl PayloadBuilderService ===
 
 impl<Gen, St, Engine> PayloadBuilderService<Gen, St, Engine>
@@ -246,21 +510,40 @@ where
     pub fn new(generator: Gen, chain_events: St) -> (Self, PayloadBuilderHandle<Engine>) {
         let (service_tx, command_rx) = mpsc::unbounded_channel();
         let (payload_events, _) = broadcast::channel(PAYLOAD_EVENTS_BUFFER_SIZE);
+        let (lifecycle_events, _) = broadcast::channel(PAYLOAD_LIFECYCLE_EVENTS_BUFFER_SIZE);
 
         let service = Self {
             generator,
             payload_jobs: Vec::new(),
+            job_timings: HashMap::new(),
+            resolved_jobs: VecDeque::with_capacity(RESOLVED_JOBS_HISTORY),
+            resolved_listeners: HashMap::new(),
+            attestations: HashMap::new(),
+            deadlines: HashMap::new(),
+            resolved_payload_ttl: None,
+            resolved_payloads: Default::default(),
             service_tx,
             command_rx: UnboundedReceiverStream::new(command_rx),
             metrics: Default::default(),
             chain_events,
             payload_events,
+            lifecycle_events,
         };
 
         let handle = service.handle();
         (service, handle)
     }
 
+    /// Caches resolved payloads for the given `ttl`, so repeated resolve requests for the same
+    /// payload within that window, e.g. duplicate `engine_getPayload` calls of the CL, are served
+    /// the identical payload, even if the job has been terminated in the meantime.
+    ///
+    /// A payload is cached once its first resolve request completed.
+    pub fn with_resolved_payload_ttl(mut self, ttl: Duration) -> Self {
+        self.resolved_payload_ttl = Some(ttl);
+        self
+    }
+
     /// Notifies the service on new attribute event.
     pub fn on_new_attributes(
         &self,
@@ -278,11 +561,160 @@ where
         PayloadBuilderHandle::new(self.service_tx.clone())
     }
 
+    /// Emits a lifecycle event for the given payload job.
+    fn on_lifecycle_event(&self, id: PayloadId, kind: PayloadLifecycleKind) {
+        self.lifecycle_events.send(PayloadLifecycleEvent::new(id, kind)).ok();
+    }
+
+    /// Records the time to the first non-empty payload of the given job, if it has built one.
+    fn on_job_polled(&mut self, job: &Gen::Job, id: PayloadId) {
+        let Some((created, first_payload @ None)) = self.job_timings.get_mut(&id) else { return };
+        if job.tx_count() > 0 {
+            *first_payload = Some(created.elapsed());
+        }
+    }
+
+    /// Returns how long it took the given job to build its first non-empty payload.
+    fn time_to_first_payload(&self, id: PayloadId) -> Option<Duration> {
+        self.job_timings.get(&id).and_then(|(_, first_payload)| *first_payload)
+    }
+
     /// Returns true if the given payload is currently being built.
     fn contains_payload(&self, id: PayloadId) -> bool {
         self.payload_jobs.iter().any(|(_, job_id)| *job_id == id)
     }
 
+    /// Returns whether the given payload job is still building, or `None` if it's unknown.
+    fn is_building(&self, id: PayloadId) -> Option<bool> {
+        if self.resolved_jobs.contains(&id) {
+            return Some(false)
+        }
+        self.contains_payload(id).then_some(true)
+    }
+
+    /// Overrides the fee recipient of the given payload job.
+    fn set_fee_recipient(
+        &mut self,
+        id: PayloadId,
+        recipient: Address,
+    ) -> Result<(), PayloadBuilderError> {
+        let (job, _) = self
+            .payload_jobs
+            .iter_mut()
+            .find(|(_, job_id)| *job_id == id)
+            .ok_or(PayloadBuilderError::MissingPayloadJob(id))?;
+        job.set_fee_recipient(recipient)
+    }
+
+    /// Attaches the attestation to the given payload job.
+    fn attach_attestation(
+        &mut self,
+        id: PayloadId,
+        attestation: Bytes,
+    ) -> Result<(), PayloadBuilderError> {
+        if !self.contains_payload(id) {
+            return Err(PayloadBuilderError::MissingPayloadJob(id))
+        }
+        self.attestations.insert(id, attestation);
+        Ok(())
+    }
+
+    /// Sets the build cutoff of the given payload job.
+    fn set_deadline(
+        &mut self,
+        id: PayloadId,
+        deadline: Instant,
+    ) -> Result<(), PayloadBuilderError> {
+        if !self.contains_payload(id) {
+            return Err(PayloadBuilderError::MissingPayloadJob(id))
+        }
+        self.deadlines.insert(id, deadline);
+        Ok(())
+    }
+
+    /// Returns `true` if the build cutoff of the given payload job has passed.
+    fn is_past_deadline(&self, id: PayloadId) -> bool {
+        self.deadlines.get(&id).is_some_and(|deadline| *deadline <= Instant::now())
+    }
+
+    /// Drops the attestation of the given payload job unless the job has been resolved.
+    fn drop_unresolved_attestation(&mut self, id: PayloadId) {
+        if !self.resolved_jobs.contains(&id) {
+            self.attestations.remove(&id);
+        }
+    }
+
+    /// Marks all payload jobs building on the given parent for the slot with the given timestamp
+    /// as superseded, see [PayloadJob::mark_superseded].
+    fn mark_superseded(&mut self, parent: B256, timestamp: u64) {
+        for (job, id) in &mut self.payload_jobs {
+            if job.parent_hash() != parent || job.is_superseded() {
+                continue
+            }
+            if job.payload_attributes().is_ok_and(|attr| attr.timestamp() == timestamp) {
+                job.mark_superseded();
+                trace!(%id, %parent, "payload job superseded by a newer job for the same slot");
+            }
+        }
+    }
+
+    /// Cancels all payload jobs building on the given parent and returns how many were cancelled.
+    fn cancel_by_parent(&mut self, parent: B256) -> usize {
+        let mut cancelled = Vec::new();
+        self.payload_jobs.retain(|(job, id)| {
+            if job.parent_hash() != parent {
+                return true
+            }
+            cancelled.push(*id);
+            false
+        });
+        for id in &cancelled {
+            self.job_timings.remove(id);
+            self.deadlines.remove(id);
+            self.resolved_listeners.remove(id);
+            self.drop_unresolved_attestation(*id);
+            self.on_lifecycle_event(*id, PayloadLifecycleKind::Cancelled);
+            trace!(%id, %parent, "cancelled payload job building on abandoned parent");
+        }
+        self.metrics.set_active_jobs(self.payload_jobs.len());
+        cancelled.len()
+    }
+
+    /// Registers a listener for the resolved payload of the given job. The listener is dropped
+    /// right away if there's no such job.
+    fn add_resolved_listener(
+        &mut self,
+        id: PayloadId,
+        listener: ResolvedListener<Engine::BuiltPayload>,
+    ) {
+        if self.contains_payload(id) {
+            self.resolved_listeners.entry(id).or_default().push(listener);
+        }
+    }
+
+    /// Notifies all listeners of the given payload job that the job failed, see
+    /// [`PayloadBuilderHandle::on_resolved`].
+    fn on_job_failed(&mut self, id: PayloadId, err: &PayloadBuilderError) {
+        for listener in self.resolved_listeners.remove(&id).unwrap_or_default() {
+            let _ = listener.send(Err(PayloadBuilderError::Other(err.to_string().into())));
+        }
+    }
+
+    /// Remembers that the given payload job has been resolved.
+    fn on_job_resolved(&mut self, id: PayloadId) {
+        if self.resolved_jobs.contains(&id) {
+            return
+        }
+        if self.resolved_jobs.len() == RESOLVED_JOBS_HISTORY {
+            if let Some(forgotten) = self.resolved_jobs.pop_front() {
+                if !self.contains_payload(forgotten) {
+                    self.attestations.remove(&forgotten);
+                }
+            }
+        }
+        self.resolved_jobs.push_back(id);
+    }
+
     /// Returns the best payload for the given identifier that has been built so far.
     fn best_payload(
         &self,
@@ -305,11 +737,24 @@ where
     fn resolve(&mut self, id: PayloadId) -> Option<PayloadFuture<Engine::BuiltPayload>> {
         trace!(%id, "resolving payload job");
 
+        if let Some(ttl) = self.resolved_payload_ttl {
+            let mut resolved_payloads = self.resolved_payloads.lock().unwrap();
+            resolved_payloads.retain(|_, (resolved_at, _)| resolved_at.elapsed() < ttl);
+            if let Some((_, payload)) = resolved_payloads.get(&id) {
+                trace!(%id, "serving cached resolved payload");
+                return Some(Box::pin(future::ready(Ok(payload.clone()))))
+            }
+        }
+
         let job = self.payload_jobs.iter().position(|(_, job_id)| *job_id == id)?;
         let (fut, keep_alive) = self.payload_jobs[job].0.resolve();
+        self.on_lifecycle_event(id, PayloadLifecycleKind::Resolved);
+        self.on_job_resolved(id);
 
         if keep_alive == KeepPayloadJobAlive::No {
             let (_, id) = self.payload_jobs.remove(job);
+            self.job_timings.remove(&id);
+            self.deadlines.remove(&id);
             trace!(%id, "terminated resolved job");
         }
 
@@ -317,6 +762,9 @@ where
         // the future in a new future that will update the metrics.
         let resolved_metrics = self.metrics.clone();
         let payload_events = self.payload_events.clone();
+        let listeners = self.resolved_listeners.remove(&id).unwrap_or_default();
+        let resolved_payloads =
+            self.resolved_payload_ttl.is_some().then(|| self.resolved_payloads.clone());
 
         let fut = async move {
             let res = fut.await;
@@ -326,7 +774,21 @@ where
                 resolved_metrics
                     .set_resolved_revenue(payload.block().number, f64::from(payload.fees()));
             }
-            res.map(|p| p.into())
+            let res = res.map(|p| p.into());
+            if let (Some(resolved_payloads), Ok(payload)) = (resolved_payloads, &res) {
+                resolved_payloads.lock().unwrap().insert(id, (Instant::now(), payload.clone()));
+            }
+            if !listeners.is_empty() {
+                let shared = match &res {
+                    Ok(payload) => Ok(Arc::new(payload.clone())),
+                    Err(err) => Err(err.to_string()),
+                };
+                for listener in listeners {
+                    let res = shared.clone().map_err(|err| PayloadBuilderError::Other(err.into()));
+                    let _ = listener.send(res);
+                }
+            }
+            res
         };
 
         Some(Box::pin(fut))
@@ -383,20 +845,41 @@ where
             // we don't care about the order of the jobs, so we can just swap_remove them
             for idx in (0..this.payload_jobs.len()).rev() {
                 let (mut job, id) = this.payload_jobs.swap_remove(idx);
+                if this.is_past_deadline(id) {
+                    // the best payload is frozen, don't build any further
+                    this.payload_jobs.push((job, id));
+                    continue
+                }
+                let improvements = job.improvements();
 
                 // drain better payloads from the job
-                match job.poll_unpin(cx) {
+                let poll = job.poll_unpin(cx);
+                if job.improvements() > improvements {
+                    this.on_lifecycle_event(id, PayloadLifecycleKind::Improved);
+                }
+                match poll {
                     Poll::Ready(Ok(_)) => {
                         this.metrics.set_active_jobs(this.payload_jobs.len());
+                        this.job_timings.remove(&id);
+                        this.deadlines.remove(&id);
+                        this.resolved_listeners.remove(&id);
+                        this.drop_unresolved_attestation(id);
+                        this.on_lifecycle_event(id, PayloadLifecycleKind::Cancelled);
                         trace!(%id, "payload job finished");
                     }
                     Poll::Ready(Err(err)) => {
                         warn!(?err, ?id, "Payload builder job failed; resolving payload");
                         this.metrics.inc_failed_jobs();
                         this.metrics.set_active_jobs(this.payload_jobs.len());
+                        this.job_timings.remove(&id);
+                        this.deadlines.remove(&id);
+                        this.drop_unresolved_attestation(id);
+                        this.on_job_failed(id, &err);
+                        this.on_lifecycle_event(id, PayloadLifecycleKind::Cancelled);
                     }
                     Poll::Pending => {
                         // still pending, put it back
+                        this.on_job_polled(&job, id);
                         this.payload_jobs.push((job, id));
                     }
                 }
@@ -409,6 +892,7 @@ where
             while let Poll::Ready(Some(cmd)) = this.command_rx.poll_next_unpin(cx) {
                 match cmd {
                     PayloadServiceCommand::BuildNewPayload(attr, tx) => {
+                        let attr = this.generator.normalize_attributes(attr).into_inner();
                         let id = attr.payload_id();
                         let mut res = Ok(id);
 
@@ -417,12 +901,18 @@ where
                         } else {
                             // no job for this payload yet, create one
                             let parent = attr.parent();
+                            let timestamp = attr.timestamp();
                             match this.generator.new_payload_job(attr) {
                                 Ok(job) => {
                                     info!(%id, %parent, "New payload job created");
                                     this.metrics.inc_initiated_jobs();
                                     new_job = true;
+                                    this.mark_superseded(parent, timestamp);
                                     this.payload_jobs.push((job, id));
+                                    this.job_timings.insert(id, (Instant::now(), None));
+                                    this.resolved_jobs.retain(|resolved| *resolved != id);
+                                    this.attestations.remove(&id);
+                                    this.on_lifecycle_event(id, PayloadLifecycleKind::Created);
                                 }
                                 Err(err) => {
                                     this.metrics.inc_failed_jobs();
@@ -446,10 +936,37 @@ where
                     PayloadServiceCommand::Resolve(id, tx) => {
                         let _ = tx.send(this.resolve(id));
                     }
+                    PayloadServiceCommand::TimeToFirstPayload(id, tx) => {
+                        let _ = tx.send(this.time_to_first_payload(id));
+                    }
+                    PayloadServiceCommand::IsBuilding(id, tx) => {
+                        let _ = tx.send(this.is_building(id));
+                    }
+                    PayloadServiceCommand::OnResolved(id, tx) => {
+                        this.add_resolved_listener(id, tx);
+                    }
+                    PayloadServiceCommand::SetFeeRecipient(id, recipient, tx) => {
+                        let _ = tx.send(this.set_fee_recipient(id, recipient));
+                    }
+                    PayloadServiceCommand::CancelByParent(parent, tx) => {
+                        let _ = tx.send(this.cancel_by_parent(parent));
+                    }
+                    PayloadServiceCommand::AttachAttestation(id, attestation, tx) => {
+                        let _ = tx.send(this.attach_attestation(id, attestation));
+                    }
+                    PayloadServiceCommand::Attestation(id, tx) => {
+                        let _ = tx.send(this.attestations.get(&id).cloned());
+                    }
+                    PayloadServiceCommand::SetDeadline(id, deadline, tx) => {
+                        let _ = tx.send(this.set_deadline(id, deadline));
+                    }
                     PayloadServiceCommand::Subscribe(tx) => {
                         let new_rx = this.payload_events.subscribe();
                         let _ = tx.send(new_rx);
                     }
+                    PayloadServiceCommand::SubscribeLifecycle(tx) => {
+                        let _ = tx.send(this.lifecycle_events.subscribe());
+                    }
                 }
             }
 
@@ -479,8 +996,26 @@ pub enum PayloadServiceCommand<Engine: EngineTypes> {
     ),
     /// Resolve the payload and return the payload
     Resolve(PayloadId, oneshot::Sender<Option<PayloadFuture<Engine::BuiltPayload>>>),
+    /// Get the time it took the payload job to build its first non-empty payload
+    TimeToFirstPayload(PayloadId, oneshot::Sender<Option<Duration>>),
+    /// Check whether the payload job is still building
+    IsBuilding(PayloadId, oneshot::Sender<Option<bool>>),
+    /// Get notified once the payload job is resolved
+    OnResolved(PayloadId, ResolvedListener<Engine::BuiltPayload>),
+    /// Override the fee recipient of the payload job
+    SetFeeRecipient(PayloadId, Address, oneshot::Sender<Result<(), PayloadBuilderError>>),
+    /// Cancel all payload jobs building on the given parent
+    CancelByParent(B256, oneshot::Sender<usize>),
+    /// Attach an attestation to the payload job
+    AttachAttestation(PayloadId, Bytes, oneshot::Sender<Result<(), PayloadBuilderError>>),
+    /// Get the attestation attached to the payload job
+    Attestation(PayloadId, oneshot::Sender<Option<Bytes>>),
+    /// Set the build cutoff of the payload job
+    SetDeadline(PayloadId, Instant, oneshot::Sender<Result<(), PayloadBuilderError>>),
     /// Payload service events
     Subscribe(oneshot::Sender<broadcast::Receiver<Events<Engine>>>),
+    /// Payload job lifecycle events
+    SubscribeLifecycle(oneshot::Sender<broadcast::Receiver<PayloadLifecycleEvent>>),
 }
 
 impl<Engine> fmt::Debug for PayloadServiceCommand<Engine>
@@ -499,7 +1034,547 @@ where
                 f.debug_tuple("PayloadAttributes").field(&f0).field(&f1).finish()
             }
             PayloadServiceCommand::Resolve(f0, _f1) => f.debug_tuple("Resolve").field(&f0).finish(),
+            PayloadServiceCommand::TimeToFirstPayload(f0, f1) => {
+                f.debug_tuple("TimeToFirstPayload").field(&f0).field(&f1).finish()
+            }
+            PayloadServiceCommand::IsBuilding(f0, f1) => {
+                f.debug_tuple("IsBuilding").field(&f0).field(&f1).finish()
+            }
+            PayloadServiceCommand::OnResolved(f0, f1) => {
+                f.debug_tuple("OnResolved").field(&f0).field(&f1).finish()
+            }
+            PayloadServiceCommand::SetFeeRecipient(f0, f1, f2) => {
+                f.debug_tuple("SetFeeRecipient").field(&f0).field(&f1).field(&f2).finish()
+            }
+            PayloadServiceCommand::CancelByParent(f0, f1) => {
+                f.debug_tuple("CancelByParent").field(&f0).field(&f1).finish()
+            }
+            PayloadServiceCommand::AttachAttestation(f0, f1, f2) => {
+                f.debug_tuple("AttachAttestation").field(&f0).field(&f1).field(&f2).finish()
+            }
+            PayloadServiceCommand::Attestation(f0, f1) => {
+                f.debug_tuple("Attestation").field(&f0).field(&f1).finish()
+            }
+            PayloadServiceCommand::SetDeadline(f0, f1, f2) => {
+                f.debug_tuple("SetDeadline").field(&f0).field(&f1).field(&f2).finish()
+            }
             PayloadServiceCommand::Subscribe(f0) => f.debug_tuple("Subscribe").field(&f0).finish(),
+            PayloadServiceCommand::SubscribeLifecycle(f0) => {
+                f.debug_tuple("SubscribeLifecycle").field(&f0).finish()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EthBuiltPayload, EthPayloadBuilderAttributes};
+    use reth_node_api::{
+        validate_version_specific_fields, AttributesValidationError, EngineApiMessageVersion,
+        PayloadOrAttributes,
+    };
+    use reth_primitives::{Block, ChainSpec, TransactionSigned, Withdrawals, B256, U256};
+    use reth_rpc_types::engine::PayloadAttributes;
+
+    #[derive(Debug, Clone, serde::Deserialize)]
+    struct TestEngineTypes;
+
+    impl EngineTypes for TestEngineTypes {
+        type PayloadAttributes = PayloadAttributes;
+        type PayloadBuilderAttributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn validate_version_specific_fields(
+            chain_spec: &ChainSpec,
+            version: EngineApiMessageVersion,
+            payload_or_attrs: PayloadOrAttributes<'_, PayloadAttributes>,
+        ) -> Result<(), AttributesValidationError> {
+            validate_version_specific_fields(chain_spec, version, payload_or_attrs)
+        }
+    }
+
+    fn test_attributes(id: u8, parent: B256, timestamp: u64) -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes {
+            id: PayloadId::new([id; 8]),
+            parent,
+            timestamp,
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Withdrawals::default(),
+            parent_beacon_block_root: None,
+        }
+    }
+
+    /// Spawns a service with the given generator and no chain events.
+    fn spawn_service<Gen>(generator: Gen) -> PayloadBuilderHandle<TestEngineTypes>
+    where
+        Gen: PayloadJobGenerator,
+        Gen::Job: PayloadJob<PayloadAttributes = EthPayloadBuilderAttributes>,
+        <Gen::Job as PayloadJob>::BuiltPayload: Into<EthBuiltPayload>,
+        PayloadBuilderService<Gen, stream::Empty<CanonStateNotification>, TestEngineTypes>:
+            Future<Output = ()> + Send + 'static,
+    {
+        let (service, handle) = PayloadBuilderService::new(generator, stream::empty());
+        tokio::spawn(service);
+        handle
+    }
+
+    /// A job that improves its payload once, on the first poll.
+    #[derive(Debug)]
+    struct ImprovingPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        improvements: u64,
+    }
+
+    impl Future for ImprovingPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            this.improvements = 1;
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for ImprovingPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let mut block = Block::default();
+            if self.improvements > 0 {
+                block.body.push(TransactionSigned::default());
+            }
+            let fees = U256::from(self.improvements);
+            Ok(EthBuiltPayload::new(self.attr.id, block.seal_slow(), fees))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn improvements(&self) -> u64 {
+            self.improvements
+        }
+
+        fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (future::ready(self.best_payload()), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[derive(Debug)]
+    struct ImprovingPayloadJobGenerator;
+
+    impl PayloadJobGenerator for ImprovingPayloadJobGenerator {
+        type Job = ImprovingPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            Ok(ImprovingPayloadJob { attr, improvements: 0 })
+        }
+    }
+
+    #[tokio::test]
+    async fn lifecycle_events_create_improve_resolve() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let mut events = Box::pin(handle.lifecycle_events());
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let id = handle.new_payload(attr).await.unwrap();
+        let store = PayloadStore::from(handle);
+        store.resolve(id).await.unwrap().unwrap();
+
+        let mut kinds = Vec::new();
+        for _ in 0..3 {
+            let event = events.next().await.unwrap();
+            assert_eq!(event.id, id);
+            kinds.push(event.kind);
+        }
+        assert_eq!(
+            kinds,
+            vec![
+                PayloadLifecycleKind::Created,
+                PayloadLifecycleKind::Improved,
+                PayloadLifecycleKind::Resolved
+            ]
+        );
+    }
+
+    /// A job that resolves immediately or never, depending on `slow`. Its payload is worth the
+    /// timestamp of its attributes.
+    #[derive(Debug)]
+    struct DelayedPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        slow: bool,
+    }
+
+    impl Future for DelayedPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Poll::Pending
+        }
+    }
+
+    impl PayloadJob for DelayedPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture = PayloadFuture<EthBuiltPayload>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let fees = U256::from(self.attr.timestamp);
+            Ok(EthBuiltPayload::new(self.attr.id, Block::default().seal_slow(), fees))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            let fut: Self::ResolvePayloadFuture = if self.slow {
+                Box::pin(future::pending())
+            } else {
+                Box::pin(future::ready(self.best_payload()))
+            };
+            (fut, KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[derive(Debug)]
+    struct DelayedPayloadJobGenerator;
+
+    impl PayloadJobGenerator for DelayedPayloadJobGenerator {
+        type Job = DelayedPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            let slow = attr.timestamp > 1;
+            Ok(DelayedPayloadJob { attr, slow })
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_all_returns_by_deadline() {
+        let handle = spawn_service(DelayedPayloadJobGenerator);
+        let fast =
+            handle.new_payload(test_attributes(1, B256::with_last_byte(1), 1)).await.unwrap();
+        let slow =
+            handle.new_payload(test_attributes(2, B256::with_last_byte(2), 2)).await.unwrap();
+        let store = PayloadStore::from(handle);
+
+        let deadline = Instant::now() + std::time::Duration::from_millis(100);
+        let payloads = store.resolve_all(&[fast, slow], deadline).await;
+        assert!(Instant::now() < deadline + std::time::Duration::from_millis(50));
+
+        let ids = payloads.into_iter().map(|p| p.unwrap().unwrap().id()).collect::<Vec<_>>();
+        assert_eq!(ids, vec![fast, slow]);
+    }
+
+    #[tokio::test]
+    async fn ranks_best_payloads_by_value() {
+        let handle = spawn_service(DelayedPayloadJobGenerator);
+        let low = handle.new_payload(test_attributes(1, B256::with_last_byte(1), 2)).await.unwrap();
+        let high =
+            handle.new_payload(test_attributes(2, B256::with_last_byte(2), 5)).await.unwrap();
+        let mid = handle.new_payload(test_attributes(3, B256::with_last_byte(3), 3)).await.unwrap();
+        let unknown = PayloadId::new([4; 8]);
+        let store = PayloadStore::from(handle);
+
+        let ranked = store
+            .best_payloads_ranked(&[low, unknown, high, mid])
+            .await
+            .into_iter()
+            .map(|(id, payload)| (id, payload.fees()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranked, vec![(high, U256::from(5)), (mid, U256::from(3)), (low, U256::from(2))]);
+    }
+
+    #[tokio::test]
+    async fn waits_for_payload_value() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let id = handle.new_payload(attr).await.unwrap();
+        let store = PayloadStore::from(handle);
+
+        // the job is worth more after its first improvement
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let payload = store.wait_for_value(id, U256::from(1), deadline).await.unwrap();
+        assert_eq!(payload.fees(), U256::from(1));
+
+        // the job never improves further
+        let deadline = Instant::now() + Duration::from_millis(50);
+        assert!(store.wait_for_value(id, U256::from(2), deadline).await.is_none());
+
+        let unknown = PayloadId::new([2; 8]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert!(store.wait_for_value(unknown, U256::ZERO, deadline).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_time_to_first_payload() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let mut events = Box::pin(handle.lifecycle_events());
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let id = handle.new_payload(attr).await.unwrap();
+        while events.next().await.unwrap().kind != PayloadLifecycleKind::Improved {}
+
+        let elapsed = handle.time_to_first_payload(id).await.unwrap();
+        assert!(elapsed > Duration::ZERO);
+        assert_eq!(handle.time_to_first_payload(PayloadId::new([2; 8])).await, None);
+    }
+
+    #[tokio::test]
+    async fn reports_whether_job_is_building() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        assert_eq!(handle.is_building(attr.id).await, None);
+
+        let id = handle.new_payload(attr).await.unwrap();
+        assert_eq!(handle.is_building(id).await, Some(true));
+
+        handle.resolve(id).await.unwrap().unwrap();
+        assert_eq!(handle.is_building(id).await, Some(false));
+    }
+
+    #[tokio::test]
+    async fn cancels_jobs_by_parent() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let (abandoned, kept) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let first = handle.new_payload(test_attributes(1, abandoned, 1)).await.unwrap();
+        let second = handle.new_payload(test_attributes(2, abandoned, 1)).await.unwrap();
+        let other = handle.new_payload(test_attributes(3, kept, 1)).await.unwrap();
+
+        assert_eq!(handle.cancel_by_parent(abandoned).await, 2);
+        assert_eq!(handle.is_building(first).await, None);
+        assert_eq!(handle.is_building(second).await, None);
+        assert_eq!(handle.is_building(other).await, Some(true));
+
+        assert_eq!(handle.cancel_by_parent(abandoned).await, 0);
+    }
+
+    #[tokio::test]
+    async fn notifies_when_job_is_resolved() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+
+        // unknown jobs are never resolved
+        assert!(handle.on_resolved(attr.id).await.is_err());
+
+        let id = handle.new_payload(attr).await.unwrap();
+        let (first, second) = (handle.on_resolved(id), handle.on_resolved(id));
+
+        let resolved = handle.resolve(id).await.unwrap().unwrap();
+        assert_eq!(first.await.unwrap().unwrap().block(), resolved.block());
+        assert_eq!(second.await.unwrap().unwrap().block(), resolved.block());
+    }
+
+    #[tokio::test]
+    async fn set_fee_recipient_of_unknown_or_unsupported_job() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let recipient = Address::with_last_byte(1);
+        assert!(matches!(
+            handle.set_fee_recipient(attr.id, recipient).await,
+            Err(PayloadBuilderError::MissingPayloadJob(id)) if id == attr.id
+        ));
+
+        let id = handle.new_payload(attr).await.unwrap();
+        assert!(matches!(
+            handle.set_fee_recipient(id, recipient).await,
+            Err(PayloadBuilderError::FeeRecipientOverrideUnsupported)
+        ));
+    }
+
+    #[tokio::test]
+    async fn attaches_attestation() {
+        let handle = spawn_service(ImprovingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let attestation = Bytes::from_static(b"attested value");
+        assert!(matches!(
+            handle.attach_attestation(attr.id, attestation.clone()).await,
+            Err(PayloadBuilderError::MissingPayloadJob(id)) if id == attr.id
+        ));
+
+        let id = handle.new_payload(attr).await.unwrap();
+        assert_eq!(handle.attestation(id).await, None);
+        handle.attach_attestation(id, attestation.clone()).await.unwrap();
+        assert_eq!(handle.attestation(id).await, Some(attestation.clone()));
+
+        // still available once the job is resolved and terminated
+        handle.resolve(id).await.unwrap().unwrap();
+        assert_eq!(handle.is_building(id).await, Some(false));
+        assert_eq!(handle.attestation(id).await, Some(attestation));
+        assert_eq!(handle.attestation(PayloadId::new([2; 8])).await, None);
+    }
+
+    /// A job that improves its payload on every tick of its interval. Its payload is worth the
+    /// number of improvements.
+    #[derive(Debug)]
+    struct TickingPayloadJob {
+        attr: EthPayloadBuilderAttributes,
+        interval: time::Interval,
+        improvements: u64,
+        superseded: bool,
+    }
+
+    impl Future for TickingPayloadJob {
+        type Output = Result<(), PayloadBuilderError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            while !this.superseded && this.interval.poll_tick(cx).is_ready() {
+                this.improvements += 1;
+            }
+            Poll::Pending
         }
     }
+
+    impl PayloadJob for TickingPayloadJob {
+        type PayloadAttributes = EthPayloadBuilderAttributes;
+        type ResolvePayloadFuture =
+            futures_util::future::Ready<Result<EthBuiltPayload, PayloadBuilderError>>;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn best_payload(&self) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let fees = U256::from(self.improvements);
+            Ok(EthBuiltPayload::new(self.attr.id, Block::default().seal_slow(), fees))
+        }
+
+        fn payload_attributes(&self) -> Result<EthPayloadBuilderAttributes, PayloadBuilderError> {
+            Ok(self.attr.clone())
+        }
+
+        fn improvements(&self) -> u64 {
+            self.improvements
+        }
+
+        fn mark_superseded(&mut self) {
+            self.superseded = true;
+        }
+
+        fn is_superseded(&self) -> bool {
+            self.superseded
+        }
+
+        fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+            (future::ready(self.best_payload()), KeepPayloadJobAlive::No)
+        }
+    }
+
+    #[derive(Debug)]
+    struct TickingPayloadJobGenerator;
+
+    impl PayloadJobGenerator for TickingPayloadJobGenerator {
+        type Job = TickingPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            let interval = time::interval(Duration::from_millis(1));
+            Ok(TickingPayloadJob { attr, interval, improvements: 0, superseded: false })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn freezes_payload_after_deadline() {
+        let handle = spawn_service(TickingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let deadline = Instant::now() + Duration::from_millis(50);
+        assert!(matches!(
+            handle.set_deadline(attr.id, deadline).await,
+            Err(PayloadBuilderError::MissingPayloadJob(id)) if id == attr.id
+        ));
+
+        let id = handle.new_payload(attr).await.unwrap();
+        handle.set_deadline(id, deadline).await.unwrap();
+
+        time::advance(Duration::from_millis(60)).await;
+        let frozen = handle.best_payload(id).await.unwrap().unwrap();
+        assert!(frozen.fees() > U256::ZERO);
+
+        // no improvements after the deadline, but the payload is still served
+        time::advance(Duration::from_millis(50)).await;
+        assert_eq!(handle.best_payload(id).await.unwrap().unwrap().fees(), frozen.fees());
+        assert_eq!(handle.resolve(id).await.unwrap().unwrap().fees(), frozen.fees());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn supersedes_jobs_for_the_same_slot() {
+        let handle = spawn_service(TickingPayloadJobGenerator);
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let next_slot = test_attributes(2, B256::with_last_byte(1), 2);
+        let newer = EthPayloadBuilderAttributes {
+            id: PayloadId::new([3; 8]),
+            suggested_fee_recipient: Address::with_last_byte(1),
+            ..attr.clone()
+        };
+        let old = handle.new_payload(attr).await.unwrap();
+        let other = handle.new_payload(next_slot).await.unwrap();
+        time::advance(Duration::from_millis(20)).await;
+        let new = handle.new_payload(newer).await.unwrap();
+
+        // the old job stops improving, but still serves its payload
+        let frozen = handle.best_payload(old).await.unwrap().unwrap().fees();
+        let other_fees = handle.best_payload(other).await.unwrap().unwrap().fees();
+        assert!(frozen > U256::ZERO);
+        time::advance(Duration::from_millis(50)).await;
+        assert_eq!(handle.best_payload(old).await.unwrap().unwrap().fees(), frozen);
+        assert_eq!(handle.resolve(old).await.unwrap().unwrap().fees(), frozen);
+
+        // jobs for other slots and the newer job keep improving
+        assert!(handle.best_payload(other).await.unwrap().unwrap().fees() > other_fees);
+        assert!(handle.best_payload(new).await.unwrap().unwrap().fees() > U256::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn serves_repeated_resolve_from_cache() {
+        let (service, handle) = PayloadBuilderService::<_, _, TestEngineTypes>::new(
+            ImprovingPayloadJobGenerator,
+            stream::empty(),
+        );
+        tokio::spawn(service.with_resolved_payload_ttl(Duration::from_millis(500)));
+
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let id = handle.new_payload(attr).await.unwrap();
+        let store = PayloadStore::from(handle);
+
+        // the job is terminated after it's resolved, so the second payload must be cached
+        let first = store.resolve(id).await.unwrap().unwrap();
+        let second = store.resolve(id).await.unwrap().unwrap();
+        assert_eq!(second.id(), first.id());
+        assert_eq!(second.block(), first.block());
+
+        time::advance(Duration::from_millis(600)).await;
+        assert!(store.resolve(id).await.is_none());
+    }
+
+    #[test]
+    fn tx_count_grows_with_improvements() {
+        let attr = test_attributes(1, B256::with_last_byte(1), 1);
+        let mut job = ImprovingPayloadJobGenerator.new_payload_job(attr).unwrap();
+        assert_eq!(job.tx_count(), 0);
+
+        // the job improves on the first poll
+        assert!((&mut job).now_or_never().is_none());
+        assert_eq!(job.tx_count(), 1);
+    }
 }
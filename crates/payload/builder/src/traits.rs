@@ -1,9 +1,11 @@
 //! Trait abstractions used by the payload crate.
 
 use crate::error::PayloadBuilderError;
-use reth_node_api::{BuiltPayload, PayloadBuilderAttributes};
+use reth_node_api::{BuiltPayload, EngineApiMessageVersion, PayloadBuilderAttributes};
+use reth_primitives::{Address, B256, U256};
 use reth_provider::CanonStateNotification;
-use std::future::Future;
+use reth_rpc_types::engine::PayloadId;
+use std::{future::Future, time::Duration};
 
 /// A type that can build a payload.
 ///
@@ -35,6 +37,106 @@ pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> + Send +
     /// Returns the payload attributes for the payload being built.
     fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError>;
 
+    /// Returns the hash of the parent block this job is building on.
+    ///
+    /// If the parent is no longer canonical, e.g. after a reorg, the job can be cancelled. By
+    /// default this is the parent of the [`PayloadJob::payload_attributes`], or the zero hash if
+    /// they are unavailable.
+    fn parent_hash(&self) -> B256 {
+        self.payload_attributes().map(|attributes| attributes.parent()).unwrap_or_default()
+    }
+
+    /// Returns how many times the best payload of this job has been replaced by a better one.
+    ///
+    /// The [`PayloadBuilderService`](crate::PayloadBuilderService) compares this before and after
+    /// polling the job to emit [`PayloadLifecycleKind::Improved`](crate::PayloadLifecycleKind)
+    /// events. Jobs that don't track this never report improvements.
+    fn improvements(&self) -> u64 {
+        0
+    }
+
+    /// Returns how many times this job has attempted to build a better payload, regardless of
+    /// whether the attempt succeeded.
+    ///
+    /// Together with [`PayloadJob::improvements`] this can be used to monitor the throughput of
+    /// the builder. Jobs that don't track this always report `0`.
+    fn iterations(&self) -> u64 {
+        0
+    }
+
+    /// Returns the number of transactions in the best payload built so far.
+    ///
+    /// This can be used to monitor how full the block is without resolving the job.
+    fn tx_count(&self) -> usize {
+        self.best_payload().map(|payload| payload.block().body.len()).unwrap_or_default()
+    }
+
+    /// Returns how many pool transactions were available to the build of the best payload built
+    /// so far, which is at least the number of transactions included in it.
+    ///
+    /// This can be used to reproduce how the payload was built. Jobs that don't track this report
+    /// the number of included transactions, see [`PayloadJob::tx_count`].
+    fn source_tx_count(&self) -> usize {
+        self.tx_count()
+    }
+
+    /// Returns the gas limit of the payloads built by this job.
+    ///
+    /// Together with the gas used by the best payload this can be used to monitor how full the
+    /// block is. The default implementation reports the gas limit of the best payload.
+    fn gas_limit(&self) -> u64 {
+        self.best_payload().map(|payload| payload.block().gas_limit).unwrap_or_default()
+    }
+
+    /// Returns the state root of the best payload built so far, or `None` if no payload has been
+    /// built yet.
+    fn current_state_root(&self) -> Option<B256> {
+        self.best_payload().ok().map(|payload| payload.block().state_root)
+    }
+
+    /// Returns an estimate of how much more value the payload could gain from the pending
+    /// transactions that aren't included in the best payload built so far.
+    ///
+    /// This can be used to decide whether it's worth waiting for a better payload. Jobs that
+    /// can't estimate this return `None`.
+    fn improvement_headroom(&self) -> Option<U256> {
+        None
+    }
+
+    /// Overrides the fee recipient of all payloads built by this job from now on.
+    ///
+    /// Jobs that don't support this return
+    /// [`PayloadBuilderError::FeeRecipientOverrideUnsupported`].
+    fn set_fee_recipient(&mut self, _recipient: Address) -> Result<(), PayloadBuilderError> {
+        Err(PayloadBuilderError::FeeRecipientOverrideUnsupported)
+    }
+
+    /// Marks this job as superseded by a newer job for the same slot.
+    ///
+    /// A superseded job stops improving its payload, but its best payload can still be
+    /// retrieved and resolved. Jobs that don't support this ignore it and keep building.
+    fn mark_superseded(&mut self) {}
+
+    /// Returns `true` if this job has been superseded, see [`PayloadJob::mark_superseded`].
+    fn is_superseded(&self) -> bool {
+        false
+    }
+
+    /// Returns a snapshot of the job's current state, e.g. for logging during incident analysis.
+    ///
+    /// The default implementation doesn't know when the job was started or whether it was
+    /// resolved, and reports no elapsed time and an unresolved job.
+    fn diagnostics(&self) -> PayloadJobDiagnostics {
+        PayloadJobDiagnostics {
+            parent_hash: self.parent_hash(),
+            iterations: self.iterations(),
+            tx_count: self.tx_count(),
+            value: self.best_payload().map(|payload| payload.fees()).unwrap_or_default(),
+            elapsed: None,
+            resolved: false,
+        }
+    }
+
     /// Called when the payload is requested by the CL.
     ///
     /// This is invoked on [`engine_getPayloadV2`](https://github.com/ethereum/execution-apis/blob/main/src/engine/shanghai.md#engine_getpayloadv2) and [`engine_getPayloadV1`](https://github.com/ethereum/execution-apis/blob/main/src/engine/paris.md#engine_getpayloadv1).
@@ -57,6 +159,23 @@ pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> + Send +
     fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive);
 }
 
+/// A snapshot of the state of a [`PayloadJob`], see [`PayloadJob::diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadJobDiagnostics {
+    /// The hash of the parent block the job is building on.
+    pub parent_hash: B256,
+    /// How many times the job has attempted to build a better payload.
+    pub iterations: u64,
+    /// The number of transactions in the best payload built so far.
+    pub tx_count: usize,
+    /// The fees collected by the best payload built so far.
+    pub value: U256,
+    /// How long the job has been running, if known.
+    pub elapsed: Option<Duration>,
+    /// Whether the payload has been requested by the CL.
+    pub resolved: bool,
+}
+
 /// Whether the payload job should be kept alive or terminated after the payload was requested by
 /// the CL.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,6 +186,36 @@ pub enum KeepPayloadJobAlive {
     No,
 }
 
+/// Payload attributes with equivalent representations canonicalized, see
+/// [`PayloadJobGenerator::normalize_attributes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedAttributes<A>(A);
+
+impl<A: PayloadBuilderAttributes> NormalizedAttributes<A> {
+    /// Canonicalizes the given attributes, see [`PayloadBuilderAttributes::normalized`].
+    pub fn new(attr: A) -> Self {
+        Self(attr.normalized())
+    }
+
+    /// Returns the [`PayloadId`] of the normalized attributes.
+    pub fn payload_id(&self) -> PayloadId {
+        self.0.payload_id()
+    }
+
+    /// Returns the normalized attributes.
+    pub fn into_inner(self) -> A {
+        self.0
+    }
+}
+
+impl<A> std::ops::Deref for NormalizedAttributes<A> {
+    type Target = A;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 /// A type that knows how to create new jobs for creating payloads.
 pub trait PayloadJobGenerator: Send + Sync {
     /// The type that manages the lifecycle of a payload.
@@ -74,6 +223,17 @@ pub trait PayloadJobGenerator: Send + Sync {
     /// This type is a future that yields better payloads.
     type Job: PayloadJob;
 
+    /// Canonicalizes equivalent representations of the attributes, e.g. an empty and an absent
+    /// withdrawals list, before the payload id is used to deduplicate jobs.
+    ///
+    /// By default this uses [`PayloadBuilderAttributes::normalized`].
+    fn normalize_attributes(
+        &self,
+        attr: <Self::Job as PayloadJob>::PayloadAttributes,
+    ) -> NormalizedAttributes<<Self::Job as PayloadJob>::PayloadAttributes> {
+        NormalizedAttributes::new(attr)
+    }
+
     /// Creates the initial payload and a new [`PayloadJob`] that yields better payloads over time.
     ///
     /// This is called when the CL requests a new payload job via a fork choice update.
@@ -94,4 +254,12 @@ pub trait PayloadJobGenerator: Send + Sync {
     fn on_new_state(&mut self, new_state: CanonStateNotification) {
         let _ = new_state;
     }
+
+    /// Returns the versions of `engine_getPayload` the payloads of this generator can be served
+    /// with, e.g. [`EngineApiMessageVersion::V3`] only if it builds Cancun payloads.
+    ///
+    /// By default all versions are supported.
+    fn supported_versions(&self) -> &[EngineApiMessageVersion] {
+        &[EngineApiMessageVersion::V1, EngineApiMessageVersion::V2, EngineApiMessageVersion::V3]
+    }
 }
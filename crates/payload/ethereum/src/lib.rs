@@ -197,6 +197,7 @@ mod builder {
             parent_block,
             attributes,
             chain_spec,
+            must_include,
             ..
         } = config;
 
@@ -228,6 +229,71 @@ mod builder {
         )?;
 
         let mut receipts = Vec::new();
+
+        // execute the transactions that must be included first, regardless of their fees
+        for tx in must_include.iter() {
+            // check if the job was cancelled, if so we can exit early
+            if cancel.is_cancelled() {
+                return Ok(BuildOutcome::Cancelled)
+            }
+
+            let Some(tx) = tx.clone().into_ecrecovered() else {
+                warn!(target: "payload_builder", tx=?tx.hash, "skipping must-include transaction with invalid signature");
+                continue
+            };
+
+            // blob transactions are broadcast without their sidecar, which is only available in the
+            // pool
+            if tx.is_eip4844() {
+                warn!(target: "payload_builder", tx=?tx.hash, "skipping must-include blob transaction");
+                continue
+            }
+
+            if cumulative_gas_used + tx.gas_limit() > block_gas_limit {
+                warn!(target: "payload_builder", tx=?tx.hash, ?cumulative_gas_used, "skipping must-include transaction exceeding the block gas limit");
+                continue
+            }
+
+            let mut evm = revm::Evm::builder()
+                .with_db(&mut db)
+                .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
+                    initialized_cfg.clone(),
+                    initialized_block_env.clone(),
+                    tx_env_with_recovered(&tx),
+                ))
+                .build();
+
+            let ResultAndState { result, state } = match evm.transact() {
+                Ok(res) => res,
+                Err(EVMError::Transaction(err)) => {
+                    warn!(target: "payload_builder", ?err, tx=?tx.hash, "skipping invalid must-include transaction");
+                    continue
+                }
+                Err(err) => return Err(PayloadBuilderError::EvmExecutionError(err)),
+            };
+            // drop evm so db is released.
+            drop(evm);
+            // commit changes
+            db.commit(state);
+
+            let gas_used = result.gas_used();
+            cumulative_gas_used += gas_used;
+
+            receipts.push(Some(Receipt {
+                tx_type: tx.tx_type(),
+                success: result.is_success(),
+                cumulative_gas_used,
+                logs: result.logs().into_iter().map(Into::into).collect(),
+            }));
+
+            let miner_fee = tx
+                .effective_tip_per_gas(Some(base_fee))
+                .expect("fee is always valid; execution succeeded");
+            total_fees += U256::from(miner_fee) * U256::from(gas_used);
+
+            executed_txs.push(tx.into_signed());
+        }
+
         while let Some(pool_tx) = best_txs.next() {
             // ensure we still have capacity for this transaction
             if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
@@ -422,4 +488,76 @@ mod builder {
 
         Ok(BuildOutcome::Better { payload, cached_reads })
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use reth_basic_payload_builder::Cancelled;
+        use reth_payload_builder::{database::CachedReads, PayloadId};
+        use reth_primitives::{
+            sign_message, Address, Bytes, Transaction, TransactionKind, TransactionSigned,
+            TxLegacy, Withdrawals, B256, MAINNET,
+        };
+        use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+        use reth_transaction_pool::noop::NoopTransactionPool;
+        use std::sync::Arc;
+
+        /// Returns a transfer with the given nonce, signed by a fixed key.
+        fn transfer(nonce: u64) -> TransactionSigned {
+            let transaction = Transaction::Legacy(TxLegacy {
+                chain_id: Some(1),
+                nonce,
+                gas_price: 1,
+                gas_limit: 21_000,
+                to: TransactionKind::Call(Address::with_last_byte(1)),
+                value: U256::from(1),
+                input: Default::default(),
+            });
+            let signature =
+                sign_message(B256::with_last_byte(1), transaction.signature_hash()).unwrap();
+            TransactionSigned::from_transaction_and_signature(transaction, signature)
+        }
+
+        #[test]
+        fn must_include_transactions_come_first() {
+            let tx = transfer(0);
+            // the nonce is too high, so this is invalid at the parent state
+            let invalid = transfer(5);
+
+            let client = MockEthProvider::default();
+            let sender = tx.recover_signer().unwrap();
+            client.add_account(sender, ExtendedAccount::new(0, U256::from(1_000_000_000u64)));
+
+            let parent = Header { gas_limit: 30_000_000, ..Default::default() };
+            let parent = Block { header: parent, ..Default::default() }.seal_slow();
+            let attributes = EthPayloadBuilderAttributes {
+                id: PayloadId::new([1; 8]),
+                parent: parent.hash(),
+                timestamp: 1,
+                suggested_fee_recipient: Address::ZERO,
+                prev_randao: B256::ZERO,
+                withdrawals: Withdrawals::default(),
+                parent_beacon_block_root: None,
+            };
+            let config =
+                PayloadConfig::new(Arc::new(parent), Bytes::default(), attributes, MAINNET.clone())
+                    .with_must_include(vec![invalid, tx.clone()].into());
+            let args = BuildArguments::new(
+                client,
+                NoopTransactionPool::default(),
+                CachedReads::default(),
+                config,
+                Cancelled::default(),
+                None,
+            );
+
+            let BuildOutcome::Better { payload, .. } =
+                default_ethereum_payload_builder(args).unwrap()
+            else {
+                panic!("expected a better payload")
+            };
+            assert_eq!(payload.block().body, vec![tx]);
+            assert_eq!(payload.block().gas_used, 21_000);
+        }
+    }
 }
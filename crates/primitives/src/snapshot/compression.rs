@@ -1,7 +1,7 @@
-use strum::AsRefStr;
+use strum::{AsRefStr, EnumString};
 
 /// Snapshot compression types.
-#[derive(Debug, Copy, Clone, Default, AsRefStr)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, AsRefStr, EnumString)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum Compression {
     /// LZ4 compression algorithm.
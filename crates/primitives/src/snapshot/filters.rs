@@ -1,6 +1,6 @@
-use strum::AsRefStr;
+use strum::{AsRefStr, EnumString};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// Snapshot filters.
 pub enum Filters {
     /// Snapshot uses filters with [InclusionFilter] and [PerfectHashingFunction].
@@ -16,7 +16,7 @@ impl Filters {
     }
 }
 
-#[derive(Debug, Copy, Clone, AsRefStr)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, AsRefStr, EnumString)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 /// Snapshot inclusion filter. Also see [Filters].
 pub enum InclusionFilter {
@@ -25,7 +25,7 @@ pub enum InclusionFilter {
     Cuckoo,
 }
 
-#[derive(Debug, Copy, Clone, AsRefStr)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, AsRefStr, EnumString)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 /// Snapshot perfect hashing  function. Also see [Filters].
 pub enum PerfectHashingFunction {
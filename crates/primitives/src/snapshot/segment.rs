@@ -1,6 +1,6 @@
 use crate::{
     snapshot::{Compression, Filters, InclusionFilter},
-    BlockNumber, TxNumber,
+    BlockNumber, TxNumber, B256,
 };
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
@@ -46,6 +46,7 @@ impl SnapshotSegment {
                 super::PerfectHashingFunction::Fmph,
             ),
             compression: Compression::Lz4,
+            portable: false,
         };
 
         match self {
@@ -91,7 +92,7 @@ impl SnapshotSegment {
         };
 
         // ATTENTION: if changing the name format, be sure to reflect those changes in
-        // [`Self::parse_filename`.]
+        // [`Self::parse_filename`] and [`Self::parse_filename_configuration`].
         format!("{prefix}_{}_{}", filters_name, compression.as_ref())
     }
 
@@ -131,6 +132,38 @@ impl SnapshotSegment {
 
         Some((segment, block_start..=block_end, tx_start..=tx_end))
     }
+
+    /// Parses the filters and compression of a filename created by
+    /// [`Self::filename_with_configuration`], in the format
+    /// "snapshot_{segment}_{block_start}_{block_end}_{tx_start}_{tx_end}_{filters}_{compression}".
+    ///
+    /// Returns `None` if the filename doesn't encode them, or if its segment and ranges can't be
+    /// parsed with [`Self::parse_filename`].
+    pub fn parse_filename_configuration(name: &OsStr) -> Option<(Filters, Compression)> {
+        Self::parse_filename(name)?;
+
+        let mut parts = name.to_str()?.split('_').skip(6);
+        let filters = match parts.next()? {
+            "none" => Filters::WithoutFilters,
+            filters => {
+                let (inclusion_filter, phf) = filters.split_once('-')?;
+                Filters::WithFilters(inclusion_filter.parse().ok()?, phf.parse().ok()?)
+            }
+        };
+        let compression = parts.next()?.parse().ok()?;
+
+        if parts.next().is_some() {
+            return None
+        }
+
+        Some((filters, compression))
+    }
+
+    /// Returns the transaction range covered by the snapshot with the given filename, without
+    /// opening the file. See [`Self::parse_filename`] for the expected format.
+    pub fn tx_range_from_filename(name: &OsStr) -> Option<RangeInclusive<TxNumber>> {
+        Self::parse_filename(name).map(|(_, _, tx_range)| tx_range)
+    }
 }
 
 /// A segment header that contains information common to all segments. Used for storage.
@@ -142,6 +175,12 @@ pub struct SegmentHeader {
     tx_range: RangeInclusive<TxNumber>,
     /// Segment type
     segment: SnapshotSegment,
+    /// Whether the snapshot was generated in portable mode, see [`SegmentConfig::portable`].
+    portable: bool,
+    /// Merkle root over the rows of the snapshot, if it was computed.
+    rows_root: Option<B256>,
+    /// Whether the last column of every row stores a checksum of the row.
+    row_crcs: bool,
 }
 
 impl SegmentHeader {
@@ -151,7 +190,39 @@ impl SegmentHeader {
         tx_range: RangeInclusive<TxNumber>,
         segment: SnapshotSegment,
     ) -> Self {
-        Self { block_range, tx_range, segment }
+        Self { block_range, tx_range, segment, portable: false, rows_root: None, row_crcs: false }
+    }
+
+    /// Sets whether the snapshot was generated in portable mode.
+    pub fn with_portable(mut self, portable: bool) -> Self {
+        self.portable = portable;
+        self
+    }
+
+    /// Returns `true` if the snapshot was generated in portable mode.
+    pub fn is_portable(&self) -> bool {
+        self.portable
+    }
+
+    /// Sets the Merkle root over the rows of the snapshot.
+    pub fn set_rows_root(&mut self, rows_root: Option<B256>) {
+        self.rows_root = rows_root;
+    }
+
+    /// Returns the Merkle root over the rows of the snapshot, if it was computed.
+    pub fn rows_root(&self) -> Option<B256> {
+        self.rows_root
+    }
+
+    /// Sets whether the last column of every row stores a checksum of the row.
+    pub fn set_row_crcs(&mut self, row_crcs: bool) {
+        self.row_crcs = row_crcs;
+    }
+
+    /// Returns `true` if the last column of every row stores the CRC32 of the other columns of the
+    /// row, as 4 little-endian bytes.
+    pub fn has_row_crcs(&self) -> bool {
+        self.row_crcs
     }
 
     /// Returns the transaction range.
@@ -164,6 +235,11 @@ impl SegmentHeader {
         &self.block_range
     }
 
+    /// Returns the segment type.
+    pub fn segment(&self) -> SnapshotSegment {
+        self.segment
+    }
+
     /// Returns the first block number of the segment.
     pub fn block_start(&self) -> BlockNumber {
         *self.block_range.start()
@@ -195,11 +271,16 @@ pub struct SegmentConfig {
     pub filters: Filters,
     /// Compression used on the segment
     pub compression: Compression,
+    /// Whether the segment is written in portable mode, which guarantees that all numeric
+    /// metadata of the jar is stored in little-endian byte order, independent of the architecture
+    /// it was generated on.
+    pub portable: bool,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use strum::IntoEnumIterator;
 
     #[test]
     fn test_filename() {
@@ -279,9 +360,53 @@ mod tests {
                 SnapshotSegment::parse_filename(OsStr::new(filename)),
                 Some((segment, block_range, tx_range))
             );
+            assert_eq!(
+                SnapshotSegment::parse_filename_configuration(OsStr::new(filename)),
+                configuration.map(|(compression, filters)| (filters, compression))
+            );
+        }
+
+        let filename = SnapshotSegment::Transactions.filename_with_configuration(
+            Filters::WithoutFilters,
+            Compression::Uncompressed,
+            &(0..=499),
+            &(0..=999),
+        );
+        assert_eq!(
+            SnapshotSegment::parse_filename_configuration(OsStr::new(&filename)),
+            Some((Filters::WithoutFilters, Compression::Uncompressed))
+        );
+        for name in [
+            "snapshot_headers_2_30_0_1_cuckoo_lz4",
+            "snapshot_headers_2_30_0_1_cuckoo-fmph_brotli",
+            "snapshot_headers_2_30_0_1_none_lz4_extra",
+            "snapshot_headers_30_2_0_1_none_lz4",
+        ] {
+            assert_eq!(SnapshotSegment::parse_filename_configuration(OsStr::new(name)), None);
         }
 
         assert_eq!(SnapshotSegment::parse_filename(OsStr::new("snapshot_headers_2_30_3_2")), None);
         assert_eq!(SnapshotSegment::parse_filename(OsStr::new("snapshot_headers_2_30_1")), None);
     }
+
+    #[test]
+    fn test_tx_range_from_filename() {
+        for segment in SnapshotSegment::iter() {
+            let filename = segment.filename(&(500..=999), &(1_000..=2_345));
+            assert_eq!(
+                SnapshotSegment::tx_range_from_filename(OsStr::new(&filename)),
+                Some(1_000..=2_345)
+            );
+        }
+
+        let filename = SnapshotSegment::Transactions.filename_with_configuration(
+            Filters::WithoutFilters,
+            Compression::Lz4,
+            &(0..=499),
+            &(0..=0),
+        );
+        assert_eq!(SnapshotSegment::tx_range_from_filename(OsStr::new(&filename)), Some(0..=0));
+
+        assert_eq!(SnapshotSegment::tx_range_from_filename(OsStr::new("snapshot_headers_2")), None);
+    }
 }
@@ -0,0 +1,97 @@
+//! Index from the versioned hashes of blob transactions to their position in a
+//! [`SnapshotSegment::Transactions`](reth_primitives::SnapshotSegment::Transactions) jar.
+//!
+//! Sidecars aren't stored in the jar itself. The index resolves a versioned hash to the number of
+//! the transaction that carries the blob and the offset of the blob in that transaction's
+//! sidecar, which is the position of the versioned hash in the transaction.
+//!
+//! The index is stored alongside the jar, see [`blob_index_path`], as a list of fixed-size
+//! entries sorted by versioned hash: the versioned hash, followed by the transaction number and
+//! the sidecar offset, both as little-endian `u64`.
+
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{fs, TxNumber, B256};
+use std::path::{Path, PathBuf};
+
+/// File extension of the blob index of a jar.
+const BLOB_INDEX_FILE_EXTENSION: &str = "blobs";
+
+/// Size of an encoded entry: versioned hash, transaction number and sidecar offset.
+const ENTRY_SIZE: usize = 32 + 8 + 8;
+
+/// Returns the path of the blob index of the jar at `jar_path`.
+pub fn blob_index_path(jar_path: impl AsRef<Path>) -> PathBuf {
+    jar_path.as_ref().with_extension(BLOB_INDEX_FILE_EXTENSION)
+}
+
+/// Maps the versioned hashes of the blobs in a jar to their transaction number and sidecar offset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobIndex {
+    /// Entries sorted by versioned hash.
+    entries: Vec<(B256, TxNumber, usize)>,
+}
+
+impl BlobIndex {
+    /// Creates an index from `(versioned hash, transaction number, sidecar offset)` entries.
+    pub fn new(entries: impl IntoIterator<Item = (B256, TxNumber, usize)>) -> Self {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_unstable();
+        Self { entries }
+    }
+
+    /// Loads the blob index of the jar at `jar_path`.
+    pub fn load(jar_path: impl AsRef<Path>) -> ProviderResult<Self> {
+        let path = blob_index_path(jar_path);
+        let bytes = fs::read(&path)?;
+        if bytes.len() % ENTRY_SIZE != 0 {
+            return Err(ProviderError::SnapshotBlobIndexCorrupted(path))
+        }
+
+        let entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| {
+                let number = |range: std::ops::Range<usize>| {
+                    u64::from_le_bytes(entry[range].try_into().expect("8 bytes"))
+                };
+                (B256::from_slice(&entry[..32]), number(32..40), number(40..48) as usize)
+            })
+            .collect::<Vec<_>>();
+        if !entries.windows(2).all(|pair| pair[0] <= pair[1]) {
+            return Err(ProviderError::SnapshotBlobIndexCorrupted(path))
+        }
+        Ok(Self { entries })
+    }
+
+    /// Writes the index alongside the jar at `jar_path`.
+    pub fn write(&self, jar_path: impl AsRef<Path>) -> ProviderResult<()> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * ENTRY_SIZE);
+        for (versioned_hash, tx_number, sidecar_offset) in &self.entries {
+            bytes.extend_from_slice(versioned_hash.as_slice());
+            bytes.extend_from_slice(&tx_number.to_le_bytes());
+            bytes.extend_from_slice(&(*sidecar_offset as u64).to_le_bytes());
+        }
+        Ok(fs::write(blob_index_path(jar_path), bytes)?)
+    }
+
+    /// Returns the transaction number and sidecar offset of the blob with the given versioned
+    /// hash, or `None` if it isn't in the jar.
+    ///
+    /// If multiple transactions carry the same blob, the one with the lowest number is returned.
+    pub fn get(&self, versioned_hash: &B256) -> Option<(TxNumber, usize)> {
+        let idx = self.entries.partition_point(|(hash, _, _)| hash < versioned_hash);
+        self.entries
+            .get(idx)
+            .filter(|(hash, _, _)| hash == versioned_hash)
+            .map(|(_, tx_number, sidecar_offset)| (*tx_number, *sidecar_offset))
+    }
+
+    /// Returns the number of indexed blobs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no blobs are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
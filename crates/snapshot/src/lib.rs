@@ -7,9 +7,12 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+pub mod blob_index;
 mod error;
+pub mod merkle;
 pub mod segments;
 mod snapshotter;
+pub mod tools;
 
 pub use error::SnapshotterError;
 pub use snapshotter::{
@@ -0,0 +1,211 @@
+//! Merkle trees over the rows of a snapshot jar.
+//!
+//! The leaves of the tree are the keccak256 hashes of the rows, in row order. Every level pairs
+//! adjacent nodes and hashes their concatenation; a last node without a sibling is promoted to the
+//! next level unchanged.
+
+use reth_primitives::{keccak256, B256};
+
+/// Returns the leaf of the given row.
+pub fn leaf_hash(row: &[u8]) -> B256 {
+    keccak256(row)
+}
+
+/// Returns the parent node of the given nodes.
+pub(crate) fn node_hash(left: &B256, right: &B256) -> B256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left.as_slice());
+    buf[32..].copy_from_slice(right.as_slice());
+    keccak256(buf)
+}
+
+/// Computes the root of the Merkle tree over rows that are pushed one at a time.
+///
+/// Only the roots of the complete subtrees built so far are kept, so memory use is logarithmic in
+/// the number of rows.
+#[derive(Debug, Clone, Default)]
+pub struct RowsRootBuilder {
+    /// Roots of the complete subtrees with their height, in row order. Heights are strictly
+    /// decreasing.
+    peaks: Vec<(u32, B256)>,
+}
+
+impl RowsRootBuilder {
+    /// Returns a new [`RowsRootBuilder`] without any rows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next row.
+    pub fn push_row(&mut self, row: &[u8]) {
+        self.push_leaf(leaf_hash(row))
+    }
+
+    /// Adds the leaf of the next row.
+    pub fn push_leaf(&mut self, leaf: B256) {
+        let (mut height, mut node) = (0, leaf);
+        while let Some(&(peak_height, peak)) = self.peaks.last() {
+            if peak_height != height {
+                break
+            }
+            self.peaks.pop();
+            node = node_hash(&peak, &node);
+            height += 1;
+        }
+        self.peaks.push((height, node));
+    }
+
+    /// Returns the root of the tree over all rows added so far, or `None` if there are none.
+    pub fn root(&self) -> Option<B256> {
+        // Folding the subtrees from the right matches promoting nodes without a sibling.
+        let mut peaks = self.peaks.iter().rev();
+        let (_, mut root) = *peaks.next()?;
+        for (_, peak) in peaks {
+            root = node_hash(peak, &root);
+        }
+        Some(root)
+    }
+}
+
+impl Extend<B256> for RowsRootBuilder {
+    fn extend<T: IntoIterator<Item = B256>>(&mut self, leaves: T) {
+        for leaf in leaves {
+            self.push_leaf(leaf);
+        }
+    }
+}
+
+/// Proof that a row is a leaf of the tree with a given root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Index of the proven leaf.
+    pub index: u64,
+    /// Number of leaves of the tree.
+    pub leaves: u64,
+    /// Siblings of the nodes on the path from the leaf to the root, from the bottom up. Promoted
+    /// nodes don't have a sibling.
+    pub siblings: Vec<B256>,
+}
+
+impl MerkleProof {
+    /// Returns the proof for the leaf at `index`, or `None` if there is no such leaf.
+    pub fn new(mut level: Vec<B256>, index: u64) -> Option<Self> {
+        let leaves = level.len() as u64;
+        if index >= leaves {
+            return None
+        }
+
+        let mut siblings = Vec::new();
+        let mut position = index as usize;
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [node] => *node,
+                    _ => unreachable!("chunks are not empty"),
+                })
+                .collect();
+            position /= 2;
+        }
+
+        Some(Self { index, leaves, siblings })
+    }
+
+    /// Returns the root of the tree the given row is proven to be part of, or `None` if the proof
+    /// is malformed.
+    pub fn root(&self, row: &[u8]) -> Option<B256> {
+        if self.index >= self.leaves {
+            return None
+        }
+
+        let mut siblings = self.siblings.iter();
+        let (mut node, mut position, mut width) = (leaf_hash(row), self.index, self.leaves);
+        while width > 1 {
+            // the last node of a level with an odd width is promoted
+            if position % 2 == 1 || position + 1 < width {
+                let sibling = siblings.next()?;
+                node = if position % 2 == 0 {
+                    node_hash(&node, sibling)
+                } else {
+                    node_hash(sibling, &node)
+                };
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+
+        siblings.next().is_none().then_some(node)
+    }
+
+    /// Returns `true` if the proof shows that the given row is part of the tree with `root`.
+    pub fn verify(&self, row: &[u8], root: B256) -> bool {
+        self.root(row) == Some(root)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Computes the root level by level, keeping every level in memory.
+    pub(crate) fn naive_root(mut level: Vec<B256>) -> Option<B256> {
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        level.pop()
+    }
+
+    #[test]
+    fn streaming_root_matches_naive_root() {
+        assert_eq!(RowsRootBuilder::new().root(), None);
+
+        for rows in 1..=17u8 {
+            let leaves = (0..rows).map(|row| leaf_hash(&[row])).collect::<Vec<_>>();
+
+            let mut builder = RowsRootBuilder::new();
+            for row in 0..rows {
+                builder.push_row(&[row]);
+            }
+            assert_eq!(builder.root(), naive_root(leaves), "rows: {rows}");
+        }
+    }
+
+    #[test]
+    fn single_row_root() {
+        let mut builder = RowsRootBuilder::new();
+        builder.push_row(b"row");
+        assert_eq!(builder.root(), Some(leaf_hash(b"row")));
+    }
+
+    #[test]
+    fn proofs() {
+        for rows in 1..=9u8 {
+            let leaves = (0..rows).map(|row| leaf_hash(&[row])).collect::<Vec<_>>();
+            let root = naive_root(leaves.clone()).unwrap();
+
+            for row in 0..rows {
+                let proof = MerkleProof::new(leaves.clone(), row as u64).unwrap();
+                assert!(proof.verify(&[row], root), "rows: {rows}, row: {row}");
+                assert!(!proof.verify(&[row + 1], root));
+            }
+            assert_eq!(MerkleProof::new(leaves.clone(), rows as u64), None);
+        }
+
+        let leaves = (0..5u8).map(|row| leaf_hash(&[row])).collect::<Vec<_>>();
+        let root = naive_root(leaves.clone()).unwrap();
+        let mut proof = MerkleProof::new(leaves, 1).unwrap();
+        proof.siblings.push(B256::ZERO);
+        assert!(!proof.verify(&[1], root));
+    }
+}
@@ -20,7 +20,7 @@ pub struct Headers {
 impl Headers {
     /// Creates new instance of [Headers] snapshot segment.
     pub fn new(compression: Compression, filters: Filters) -> Self {
-        Self { config: SegmentConfig { compression, filters } }
+        Self { config: SegmentConfig { compression, filters, portable: false } }
     }
 }
 
@@ -0,0 +1,25 @@
+use reth_primitives::{BlockNumber, SnapshotSegment};
+use std::{fmt::Debug, ops::RangeInclusive, time::Duration};
+
+/// Metrics of a single snapshot file written by a segment, see [`SegmentMetricsSink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentMetrics {
+    /// The segment of the snapshot.
+    pub segment: SnapshotSegment,
+    /// The block range of the snapshot.
+    pub block_range: RangeInclusive<BlockNumber>,
+    /// The time it took to write the snapshot, including building its filters.
+    pub duration: Duration,
+    /// The number of rows in the snapshot.
+    pub rows: usize,
+    /// The size in bytes of the (compressed) data file.
+    pub compressed_bytes: u64,
+    /// The time it took to build the inclusion filter and perfect hashing function.
+    pub filter_build_time: Duration,
+}
+
+/// Receives the [`SegmentMetrics`] of every snapshot file written by a segment.
+pub trait SegmentMetricsSink: Debug + Send + Sync {
+    /// Records the metrics of a written snapshot file.
+    fn record(&self, metrics: SegmentMetrics);
+}
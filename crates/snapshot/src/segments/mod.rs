@@ -9,22 +9,39 @@ pub use headers::Headers;
 mod receipts;
 pub use receipts::Receipts;
 
+mod metrics;
+pub use metrics::{SegmentMetrics, SegmentMetricsSink};
+
+use crc::{Crc, CRC_32_ISO_HDLC};
 use reth_db::{
     cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, RawKey, RawTable,
 };
-use reth_interfaces::provider::ProviderResult;
-use reth_nippy_jar::NippyJar;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{ColumnResult, NippyJar};
 use reth_primitives::{
+    fs::{self, FsPathError},
     snapshot::{
         Compression, Filters, InclusionFilter, PerfectHashingFunction, SegmentConfig, SegmentHeader,
     },
     BlockNumber, SnapshotSegment,
 };
-use reth_provider::{DatabaseProviderRO, TransactionsProviderExt};
-use std::{ops::RangeInclusive, path::Path};
+use reth_provider::{BlockReader, DatabaseProviderRO, TransactionsProviderExt};
+use std::{iter, ops::RangeInclusive, path::Path};
 
 pub(crate) type Rows<const COLUMNS: usize> = [Vec<Vec<u8>>; COLUMNS];
 
+const ROW_CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// Returns the checksum of a row with the given column values, see
+/// [`SegmentHeader::has_row_crcs`].
+pub(crate) fn row_crc<'a>(columns: impl IntoIterator<Item = &'a [u8]>) -> [u8; 4] {
+    let mut digest = ROW_CRC.digest();
+    for column in columns {
+        digest.update(column);
+    }
+    digest.finalize().to_le_bytes()
+}
+
 /// A segment represents a snapshotting of some portion of the data.
 pub trait Segment: Default {
     /// Snapshot data using the provided range. The `directory` parameter determines the snapshot
@@ -55,6 +72,70 @@ pub trait Segment: Default {
     }
 }
 
+/// Returns `true` if none of the blocks in the given range have any transactions.
+pub(crate) fn is_empty_tx_range<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    block_range: &RangeInclusive<BlockNumber>,
+) -> ProviderResult<bool> {
+    let first = provider
+        .block_body_indices(*block_range.start())?
+        .ok_or(ProviderError::BlockBodyIndicesNotFound(*block_range.start()))?;
+    let last = provider
+        .block_body_indices(*block_range.end())?
+        .ok_or(ProviderError::BlockBodyIndicesNotFound(*block_range.end()))?;
+    Ok(first.first_tx_num() == last.next_tx_num())
+}
+
+/// Runs `write` with a temporary directory named `temp_name` inside `directory`, and only moves
+/// the written files into `directory` once `write` succeeded.
+///
+/// The temporary directory is removed either way, so a failed write leaves no partial files
+/// behind.
+pub(crate) fn write_atomically<T>(
+    directory: impl AsRef<Path>,
+    temp_name: &str,
+    write: impl FnOnce(&Path) -> ProviderResult<T>,
+) -> ProviderResult<T> {
+    let directory = directory.as_ref();
+    let temp_dir = directory.join(temp_name);
+    fs::create_dir_all(&temp_dir)?;
+
+    let result = write(&temp_dir).and_then(|value| {
+        for entry in fs::read_dir(&temp_dir)? {
+            let entry = entry.map_err(|err| FsPathError::read_dir(err, &temp_dir))?;
+            fs::rename(entry.path(), directory.join(entry.file_name()))?;
+        }
+        Ok(value)
+    });
+    fs::remove_dir_all(&temp_dir)?;
+    result
+}
+
+/// Writes a [`NippyJar`] without any rows for a block range without transactions. The jar is
+/// neither compressed nor has filters, since there is nothing to compress or look up.
+///
+/// `with_header` can be used to modify the segment header before the jar is written.
+pub(crate) fn create_empty_jar<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    directory: impl AsRef<Path>,
+    segment: SnapshotSegment,
+    segment_config: SegmentConfig,
+    block_range: RangeInclusive<BlockNumber>,
+    columns: usize,
+    with_header: impl FnOnce(&mut SegmentHeader),
+) -> ProviderResult<NippyJar<SegmentHeader>> {
+    let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+    let mut nippy_jar = NippyJar::new(
+        columns,
+        &directory.as_ref().join(segment.filename(&block_range, &tx_range).as_str()),
+        SegmentHeader::new(block_range, tx_range, segment).with_portable(segment_config.portable),
+    );
+    with_header(nippy_jar.user_header_mut());
+
+    nippy_jar.freeze(vec![iter::empty::<ColumnResult<Vec<u8>>>(); columns], 0)?;
+    Ok(nippy_jar)
+}
+
 /// Returns a [`NippyJar`] according to the desired configuration. The `directory` parameter
 /// determines the snapshot file's save location.
 pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
@@ -70,7 +151,7 @@ pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
     let mut nippy_jar = NippyJar::new(
         COLUMNS,
         &directory.as_ref().join(segment.filename(&block_range, &tx_range).as_str()),
-        SegmentHeader::new(block_range, tx_range, segment),
+        SegmentHeader::new(block_range, tx_range, segment).with_portable(segment_config.portable),
     );
 
     nippy_jar = match segment_config.compression {
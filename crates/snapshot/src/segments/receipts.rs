@@ -17,7 +17,7 @@ pub struct Receipts {
 impl Receipts {
     /// Creates new instance of [Receipts] snapshot segment.
     pub fn new(compression: Compression, filters: Filters) -> Self {
-        Self { config: SegmentConfig { compression, filters } }
+        Self { config: SegmentConfig { compression, filters, portable: false } }
     }
 }
 
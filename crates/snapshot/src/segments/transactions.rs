@@ -1,29 +1,542 @@
-use crate::segments::{prepare_jar, Segment};
-use reth_db::{database::Database, snapshot::create_snapshot_T1, tables};
+use crate::{
+    blob_index::BlobIndex,
+    merkle::RowsRootBuilder,
+    segments::{
+        create_empty_jar, is_empty_tx_range, prepare_jar, row_crc, write_atomically, Segment,
+        SegmentMetrics, SegmentMetricsSink,
+    },
+};
+use reth_db::{
+    cursor::DbCursorRO, database::Database, tables, transaction::DbTx, RawKey, RawTable,
+    RawValue,
+};
 use reth_interfaces::provider::ProviderResult;
+use reth_nippy_jar::{ColumnResult, NippyJar, NippyJarCursor, NippyJarWriter};
 use reth_primitives::{
-    snapshot::{Compression, Filters, SegmentConfig, SegmentHeader},
-    BlockNumber, SnapshotSegment, TxNumber,
+    fs::FsPathError,
+    snapshot::{
+        Compression, Filters, InclusionFilter, PerfectHashingFunction, SegmentConfig,
+        SegmentHeader,
+    },
+    BlockNumber, SnapshotSegment, TransactionSignedNoHash, TxNumber,
 };
 use reth_provider::{DatabaseProviderRO, TransactionsProviderExt};
-use std::{ops::RangeInclusive, path::Path};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    iter,
+    ops::RangeInclusive,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::info;
 
 /// Snapshot segment responsible for [SnapshotSegment::Transactions] part of data.
 #[derive(Debug)]
 pub struct Transactions {
     config: SegmentConfig,
+    /// Whether to compute a Merkle root over the rows and store it in the segment header.
+    with_root: bool,
+    /// Whether to store a checksum of every row in an additional column.
+    with_crc: bool,
+    /// Upper bound in bytes on the rows buffered in memory while writing the jar.
+    max_memory: Option<usize>,
+    /// Whether to skip creating a jar for block ranges without transactions.
+    skip_empty: bool,
+    /// Whether to write a [`BlobIndex`] alongside the jar.
+    with_blob_index: bool,
+    /// Receives the metrics of every written jar, if any.
+    metrics_sink: Option<Arc<dyn SegmentMetricsSink>>,
 }
 
 impl Transactions {
     /// Creates new instance of [Transactions] snapshot segment.
     pub fn new(compression: Compression, filters: Filters) -> Self {
-        Self { config: SegmentConfig { compression, filters } }
+        Self {
+            config: SegmentConfig { compression, filters, portable: false },
+            with_root: false,
+            with_crc: false,
+            max_memory: None,
+            skip_empty: false,
+            with_blob_index: false,
+            metrics_sink: None,
+        }
+    }
+
+    /// Sets whether the snapshot is generated in portable mode, see [SegmentConfig::portable].
+    pub fn with_portable(mut self, portable: bool) -> Self {
+        self.config.portable = portable;
+        self
+    }
+
+    /// Sets whether to compute a Merkle root over the transaction rows, see
+    /// [`crate::merkle`]. The root is stored in the segment header.
+    pub fn with_root(mut self, with_root: bool) -> Self {
+        self.with_root = with_root;
+        self
+    }
+
+    /// Sets whether to store a CRC32 of every row in an additional column, so corrupted rows can be
+    /// detected when reading them, see [`SegmentHeader::has_row_crcs`].
+    pub fn with_crc(mut self, with_crc: bool) -> Self {
+        self.with_crc = with_crc;
+        self
+    }
+
+    /// Sets a hint for the memory used while writing the jar, see [`NippyJar::with_max_memory`].
+    /// Rows are flushed to disk more often to stay under the bound, which makes generation
+    /// slower.
+    pub fn with_max_memory(mut self, max_memory: Option<usize>) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// Sets whether to skip creating a jar for block ranges without any transactions. Otherwise,
+    /// a jar without rows is created for them.
+    pub fn with_skip_empty(mut self, skip_empty: bool) -> Self {
+        self.skip_empty = skip_empty;
+        self
+    }
+
+    /// Sets whether to write a [`BlobIndex`] alongside the jar, so the sidecars of blob
+    /// transactions can be located by their versioned hashes.
+    pub fn with_blob_index(mut self, with_blob_index: bool) -> Self {
+        self.with_blob_index = with_blob_index;
+        self
+    }
+
+    /// Sets the sink that receives the [`SegmentMetrics`] of every jar written by this segment.
+    /// Block ranges that are skipped aren't reported.
+    pub fn with_metrics_sink(mut self, sink: Arc<dyn SegmentMetricsSink>) -> Self {
+        self.metrics_sink = Some(sink);
+        self
+    }
+
+    /// Reports the metrics of the written `jar` to the metrics sink, if any.
+    fn record_metrics(
+        &self,
+        jar: &NippyJar<SegmentHeader>,
+        started_at: Instant,
+        filter_build_time: Duration,
+    ) -> ProviderResult<()> {
+        let Some(sink) = &self.metrics_sink else { return Ok(()) };
+        let compressed_bytes = std::fs::metadata(jar.data_path())
+            .map_err(|err| FsPathError::open(err, jar.data_path()))?
+            .len();
+        sink.record(SegmentMetrics {
+            segment: self.segment(),
+            block_range: jar.user_header().block_range().clone(),
+            duration: started_at.elapsed(),
+            rows: jar.rows(),
+            compressed_bytes,
+            filter_build_time,
+        });
+        Ok(())
+    }
+
+    /// Writes the jar of the given block range into `directory`.
+    fn write_jar<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: &Path,
+        block_range: RangeInclusive<BlockNumber>,
+        is_empty: bool,
+        started_at: Instant,
+    ) -> ProviderResult<()> {
+        if is_empty {
+            return self.write_empty_jar(provider, directory, block_range, self.config, started_at)
+        }
+
+        let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+        let tx_range_len = tx_range.clone().count();
+
+        let dataset = || {
+            self.dataset_for_compression::<DB, tables::Transactions>(
+                provider,
+                &tx_range,
+                tx_range_len,
+            )
+        };
+        let mut jar = if self.with_crc {
+            prepare_jar::<DB, 2>(
+                provider,
+                directory,
+                self.segment(),
+                self.config,
+                block_range,
+                tx_range_len,
+                || {
+                    let rows = dataset()?;
+                    let crcs = rows.iter().map(|row| row_crc([row.as_slice()]).to_vec()).collect();
+                    Ok([rows, crcs])
+                },
+            )?
+        } else {
+            prepare_jar::<DB, 1>(
+                provider,
+                directory,
+                self.segment(),
+                self.config,
+                block_range,
+                tx_range_len,
+                || Ok([dataset()?]),
+            )?
+        };
+        if let Some(max_memory) = self.max_memory {
+            jar = jar.with_max_memory(max_memory);
+        }
+
+        jar.user_header_mut().set_row_crcs(self.with_crc);
+
+        // Build the filters & PHF from the list of hashes. The PHF needs all keys at once, so
+        // the hashes are collected regardless of the memory hint.
+        let mut filter_build_time = Duration::ZERO;
+        if self.config.filters.has_filters() {
+            let filters_started_at = Instant::now();
+            let hashes = provider
+                .transaction_hashes_by_range(*tx_range.start()..(*tx_range.end() + 1))?
+                .into_iter()
+                .map(|(tx, _)| Ok(tx));
+            jar.prepare_index(hashes, tx_range_len)?;
+            filter_build_time = filters_started_at.elapsed();
+        }
+
+        // Stream the rows, so only the peaks of the tree and the checksum of the row being
+        // written are held in memory.
+        let mut root = RowsRootBuilder::new();
+        let mut cursor = provider.tx_ref().cursor_read::<RawTable<tables::Transactions>>()?;
+        let rows = cursor
+            .walk_range(RawKey::new(*tx_range.start())..=RawKey::new(*tx_range.end()))?
+            .map(|row| -> ColumnResult<Vec<u8>> {
+                let row = row?.1;
+                if self.with_root {
+                    root.push_row(row.raw_value());
+                }
+                Ok(row.into_value())
+            });
+        jar.freeze(self.row_columns(rows), tx_range_len as u64)?;
+
+        // The root is only known once all rows are written, so the configuration is committed
+        // again with it.
+        if self.with_root {
+            jar.user_header_mut().set_rows_root(root.root());
+            NippyJarWriter::new(&mut jar)?.commit()?;
+        }
+
+        if self.with_blob_index {
+            self.blob_index(provider, tx_range)?.write(jar.data_path())?;
+        }
+
+        self.record_metrics(&jar, started_at, filter_build_time)
+    }
+
+    /// Snapshots the given block range into a jar with the given filters in `filtered_dir`, and
+    /// into a jar without filters in `unfiltered_dir`, reading the transactions from the database
+    /// only once. The filters of the segment configuration are ignored.
+    ///
+    /// The rows are streamed into the unfiltered jar, and the filtered jar is written from the
+    /// unfiltered one, so only the transaction hashes of the range are held in memory. Both jars
+    /// share their file name, so the directories must differ.
+    pub fn snapshot_filter_variants<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        filtered_dir: impl AsRef<Path>,
+        unfiltered_dir: impl AsRef<Path>,
+        block_range: RangeInclusive<BlockNumber>,
+        inclusion_filter: InclusionFilter,
+        phf: PerfectHashingFunction,
+    ) -> ProviderResult<()> {
+        let started_at = Instant::now();
+        let is_empty = is_empty_tx_range(provider, &block_range)?;
+        if is_empty && self.skip_empty {
+            info!(
+                target: "snapshot",
+                ?block_range,
+                "Skipping transactions snapshot of block range without transactions"
+            );
+            return Ok(())
+        }
+
+        let filters = Filters::WithFilters(inclusion_filter, phf);
+        if is_empty {
+            let variants = [
+                (filtered_dir.as_ref(), filters),
+                (unfiltered_dir.as_ref(), Filters::WithoutFilters),
+            ];
+            for (directory, filters) in variants {
+                let config = SegmentConfig { filters, ..self.config };
+                write_atomically(directory, &self.temp_name(&block_range), |directory| {
+                    let block_range = block_range.clone();
+                    self.write_empty_jar(provider, directory, block_range, config, started_at)
+                })?;
+            }
+            return Ok(())
+        }
+
+        let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+        let mut cursor = provider.tx_ref().cursor_read::<RawTable<tables::Transactions>>()?;
+        let rows = cursor
+            .walk_range(RawKey::new(*tx_range.start())..=RawKey::new(*tx_range.end()))?
+            .map(|row| -> ProviderResult<_> {
+                let (key, row) = row?;
+                Ok((key.key()?, row))
+            });
+        self.write_filter_variants(
+            provider,
+            filtered_dir.as_ref(),
+            unfiltered_dir.as_ref(),
+            block_range,
+            filters,
+            rows,
+            started_at,
+        )
+    }
+
+    /// Writes the jars of [`Self::snapshot_filter_variants`] for a non-empty block range from its
+    /// raw transaction `rows`, pulling every row exactly once.
+    #[allow(clippy::too_many_arguments)]
+    fn write_filter_variants<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        filtered_dir: &Path,
+        unfiltered_dir: &Path,
+        block_range: RangeInclusive<BlockNumber>,
+        filters: Filters,
+        rows: impl Iterator<Item = ProviderResult<(TxNumber, RawValue<TransactionSignedNoHash>)>>,
+        started_at: Instant,
+    ) -> ProviderResult<()> {
+        let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
+        let total_rows = tx_range.clone().count();
+        let temp_name = self.temp_name(&block_range);
+        let filename = self.segment().filename(&block_range, &tx_range);
+
+        // The dictionary is trained once, on the same dataset for both jars.
+        let dictionary = matches!(self.config.compression, Compression::ZstdWithDictionary)
+            .then(|| {
+                self.dataset_for_compression::<DB, tables::Transactions>(
+                    provider,
+                    &tx_range,
+                    total_rows,
+                )
+            })
+            .transpose()?;
+
+        let (hashes, rows_root, blob_index) =
+            write_atomically(unfiltered_dir, &temp_name, |directory| {
+                let config = SegmentConfig { filters: Filters::WithoutFilters, ..self.config };
+                let mut jar = self.prepare_variant_jar(
+                    provider,
+                    directory,
+                    block_range.clone(),
+                    config,
+                    total_rows,
+                    dictionary.as_ref(),
+                )?;
+
+                let mut hashes = Vec::with_capacity(total_rows);
+                let mut root = RowsRootBuilder::new();
+                let mut blob_entries = Vec::new();
+                let rows = rows.map(|row| -> ProviderResult<_> {
+                    let (tx_number, row) = row?;
+                    let tx = row.value()?;
+                    hashes.push(tx.hash());
+                    if self.with_root {
+                        root.push_row(row.raw_value());
+                    }
+                    if self.with_blob_index {
+                        let versioned_hashes =
+                            tx.transaction.blob_versioned_hashes().unwrap_or_default();
+                        blob_entries.extend(
+                            versioned_hashes
+                                .into_iter()
+                                .enumerate()
+                                .map(|(sidecar_offset, hash)| (hash, tx_number, sidecar_offset)),
+                        );
+                    }
+                    Ok(row.into_value())
+                });
+                let rows = rows.map(|row| row.map_err(Into::into));
+                jar.freeze(self.row_columns(rows), total_rows as u64)?;
+
+                // The root is only known once all rows are written, so the configuration is
+                // committed again with it.
+                if self.with_root {
+                    jar.user_header_mut().set_rows_root(root.root());
+                    NippyJarWriter::new(&mut jar)?.commit()?;
+                }
+
+                let blob_index = BlobIndex::new(blob_entries);
+                if self.with_blob_index {
+                    blob_index.write(jar.data_path())?;
+                }
+                self.record_metrics(&jar, started_at, Duration::ZERO)?;
+                Ok((hashes, root.root(), blob_index))
+            })?;
+
+        let unfiltered = NippyJar::<SegmentHeader>::load(&unfiltered_dir.join(filename.as_str()))?;
+        write_atomically(filtered_dir, &temp_name, |directory| {
+            let config = SegmentConfig { filters, ..self.config };
+            let mut jar = self.prepare_variant_jar(
+                provider,
+                directory,
+                block_range,
+                config,
+                total_rows,
+                dictionary.as_ref(),
+            )?;
+            if self.with_root {
+                jar.user_header_mut().set_rows_root(rows_root);
+            }
+
+            let filters_started_at = Instant::now();
+            jar.prepare_index(hashes.into_iter().map(Ok), total_rows)?;
+            let filter_build_time = filters_started_at.elapsed();
+
+            let mut cursor = NippyJarCursor::new(&unfiltered)?;
+            let rows = iter::from_fn(|| match cursor.next_row() {
+                Ok(row) => row.map(|row| Ok(row[0].to_vec())),
+                Err(err) => Some(Err(err.into())),
+            });
+            jar.freeze(self.row_columns(rows), total_rows as u64)?;
+
+            if self.with_blob_index {
+                blob_index.write(jar.data_path())?;
+            }
+            self.record_metrics(&jar, started_at, filter_build_time)
+        })
+    }
+
+    /// Prepares a jar of [`Self::snapshot_filter_variants`], training its dictionary on
+    /// `dictionary` if the segment is compressed with one.
+    fn prepare_variant_jar<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: &Path,
+        block_range: RangeInclusive<BlockNumber>,
+        config: SegmentConfig,
+        total_rows: usize,
+        dictionary: Option<&Vec<Vec<u8>>>,
+    ) -> ProviderResult<NippyJar<SegmentHeader>> {
+        let dataset = || dictionary.cloned().unwrap_or_default();
+        let mut jar = if self.with_crc {
+            prepare_jar::<DB, 2>(
+                provider,
+                directory,
+                self.segment(),
+                config,
+                block_range,
+                total_rows,
+                || {
+                    let rows = dataset();
+                    let crcs = rows.iter().map(|row| row_crc([row.as_slice()]).to_vec()).collect();
+                    Ok([rows, crcs])
+                },
+            )?
+        } else {
+            prepare_jar::<DB, 1>(
+                provider,
+                directory,
+                self.segment(),
+                config,
+                block_range,
+                total_rows,
+                || Ok([dataset()]),
+            )?
+        };
+        if let Some(max_memory) = self.max_memory {
+            jar = jar.with_max_memory(max_memory);
+        }
+        jar.user_header_mut().set_row_crcs(self.with_crc);
+        Ok(jar)
+    }
+
+    /// Returns the columns of a jar holding `rows`. The checksum column, if any, is computed as
+    /// the rows are pulled, since the jar writer pulls the columns of every row in order.
+    fn row_columns<'a>(
+        &self,
+        rows: impl Iterator<Item = ColumnResult<Vec<u8>>> + 'a,
+    ) -> Vec<Box<dyn Iterator<Item = ColumnResult<Vec<u8>>> + 'a>> {
+        if !self.with_crc {
+            return vec![Box::new(rows)]
+        }
+
+        let crcs = Rc::new(RefCell::new(VecDeque::new()));
+        let pending = Rc::clone(&crcs);
+        let rows = rows.inspect(move |row| {
+            if let Ok(row) = row {
+                pending.borrow_mut().push_back(row_crc([row.as_slice()]).to_vec());
+            }
+        });
+        let crcs = iter::from_fn(move || crcs.borrow_mut().pop_front().map(Ok));
+        vec![Box::new(rows), Box::new(crcs)]
+    }
+
+    /// Writes an empty jar of the given block range with the given configuration into
+    /// `directory`.
+    fn write_empty_jar<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: &Path,
+        block_range: RangeInclusive<BlockNumber>,
+        config: SegmentConfig,
+        started_at: Instant,
+    ) -> ProviderResult<()> {
+        let columns = if self.with_crc { 2 } else { 1 };
+        let jar = create_empty_jar(
+            provider,
+            directory,
+            self.segment(),
+            config,
+            block_range,
+            columns,
+            |header| header.set_row_crcs(self.with_crc),
+        )?;
+        if self.with_blob_index {
+            BlobIndex::default().write(jar.data_path())?;
+        }
+        self.record_metrics(&jar, started_at, Duration::ZERO)
+    }
+
+    /// Name of the directory a jar of `block_range` is written to before it is moved into place.
+    fn temp_name(&self, block_range: &RangeInclusive<BlockNumber>) -> String {
+        format!(".tmp-{}-{}-{}", self.segment(), block_range.start(), block_range.end())
+    }
+
+    /// Collects the versioned hashes of all blob transactions in `tx_range`.
+    fn blob_index<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        tx_range: RangeInclusive<TxNumber>,
+    ) -> ProviderResult<BlobIndex> {
+        let mut entries = Vec::new();
+        let mut cursor = provider.tx_ref().cursor_read::<tables::Transactions>()?;
+        for row in cursor.walk_range(tx_range)? {
+            let (tx_number, tx) = row?;
+            let versioned_hashes = tx.transaction.blob_versioned_hashes().unwrap_or_default();
+            entries.extend(
+                versioned_hashes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(sidecar_offset, hash)| (hash, tx_number, sidecar_offset)),
+            );
+        }
+        Ok(BlobIndex::new(entries))
     }
 }
 
 impl Default for Transactions {
     fn default() -> Self {
-        Self { config: SnapshotSegment::Transactions.config() }
+        Self {
+            config: SnapshotSegment::Transactions.config(),
+            with_root: false,
+            with_crc: false,
+            max_memory: None,
+            skip_empty: false,
+            with_blob_index: false,
+            metrics_sink: None,
+        }
     }
 }
 
@@ -38,47 +551,480 @@ impl Segment for Transactions {
         directory: impl AsRef<Path>,
         block_range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<()> {
-        let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
-        let tx_range_len = tx_range.clone().count();
+        // All numeric metadata of the jar is written in little-endian byte order: the offsets are
+        // encoded explicitly as little-endian and the configuration, filters and PHF are encoded
+        // with `bincode`'s fixed little-endian encoding. Rows are stored in their `Compact`
+        // encoding, which doesn't depend on the architecture either. Portable mode records this
+        // guarantee in the segment header.
+        let started_at = Instant::now();
+        let is_empty = is_empty_tx_range(provider, &block_range)?;
+        if is_empty && self.skip_empty {
+            info!(
+                target: "snapshot",
+                ?block_range,
+                "Skipping transactions snapshot of block range without transactions"
+            );
+            return Ok(())
+        }
 
-        let mut jar = prepare_jar::<DB, 1>(
-            provider,
-            directory,
-            self.segment(),
-            self.config,
-            block_range,
-            tx_range_len,
-            || {
-                Ok([self.dataset_for_compression::<DB, tables::Transactions>(
-                    provider,
-                    &tx_range,
-                    tx_range_len,
-                )?])
-            },
-        )?;
+        write_atomically(directory, &self.temp_name(&block_range), |directory| {
+            self.write_jar::<DB>(provider, directory, block_range, is_empty, started_at)
+        })
+    }
+}
 
-        // Generate list of hashes for filters & PHF
-        let mut hashes = None;
-        if self.config.filters.has_filters() {
-            hashes = Some(
-                provider
-                    .transaction_hashes_by_range(*tx_range.start()..(*tx_range.end() + 1))?
-                    .into_iter()
-                    .map(|(tx, _)| Ok(tx)),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blob_index::blob_index_path,
+        merkle::{leaf_hash, tests::naive_root},
+        tools::test_utils::TestDB,
+    };
+    use bincode::Options;
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_primitives::{
+        snapshot::{InclusionFilter, PerfectHashingFunction},
+        Signature, Transaction, TransactionSigned, TxEip4844, B256,
+    };
+    use reth_stages::test_utils::TestStageDB;
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        sync::Mutex,
+    };
+
+    /// Counts the bytes allocated by each thread, so the peak heap usage of a snapshot can be
+    /// measured while other tests run in parallel.
+    struct CountingAllocator;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    thread_local! {
+        static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+        static PEAK: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATED.try_with(|allocated| {
+                allocated.set(allocated.get() + layout.size());
+                let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+            });
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            // Memory freed by another thread than the one allocating it is only subtracted there.
+            let _ = ALLOCATED
+                .try_with(|allocated| allocated.set(allocated.get().saturating_sub(layout.size())));
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    /// Returns the peak number of bytes allocated by the current thread while running `f`, on
+    /// top of the ones allocated before.
+    fn peak_allocated(f: impl FnOnce()) -> usize {
+        let allocated = ALLOCATED.with(Cell::get);
+        PEAK.with(|peak| peak.set(allocated));
+        f();
+        PEAK.with(Cell::get) - allocated
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingSink(Mutex<Vec<SegmentMetrics>>);
+
+    impl SegmentMetricsSink for RecordingSink {
+        fn record(&self, metrics: SegmentMetrics) {
+            self.0.lock().unwrap().push(metrics);
+        }
+    }
+
+    #[test]
+    fn portable_jar() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Uncompressed, Filters::WithoutFilters)
+            .with_portable(true)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+
+        let path = dir.path().join(SnapshotSegment::Transactions.filename(&(0..=3), &(0..=7)));
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        assert!(jar.user_header().is_portable());
+
+        // A reader on a big-endian host can't rely on its native byte order, so decode everything
+        // with an explicitly little-endian byte order instead.
+        let config = std::fs::read(jar.config_path()).unwrap();
+        let (_version, header): (u64, SegmentHeader) = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .with_little_endian()
+            .allow_trailing_bytes()
+            .deserialize(&config)
+            .unwrap();
+        assert_eq!(&header, jar.user_header());
+
+        let offsets = std::fs::read(jar.offsets_path()).unwrap();
+        let offset_size = offsets[0] as usize;
+        let offset = |row: usize| {
+            let start = 1 + row * offset_size;
+            let mut buf = [0u8; 8];
+            buf[..offset_size].copy_from_slice(&offsets[start..start + offset_size]);
+            u64::from_le_bytes(buf) as usize
+        };
+
+        let data = std::fs::read(jar.data_path()).unwrap();
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        for row in 0..8 {
+            let expected = cursor.row_by_number(row).unwrap().unwrap()[0].to_vec();
+            assert_eq!(&data[offset(row)..offset(row + 1)], expected.as_slice());
+        }
+    }
+
+    #[test]
+    fn rows_root() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+            .with_root(true)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+
+        let path = dir.path().join(SnapshotSegment::Transactions.filename(&(0..=3), &(0..=7)));
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+
+        let leaves = (0..8)
+            .map(|id| {
+                let row = provider
+                    .tx_ref()
+                    .get::<RawTable<tables::Transactions>>(RawKey::new(id))
+                    .unwrap()
+                    .unwrap();
+                leaf_hash(row.raw_value())
+            })
+            .collect();
+        assert_eq!(jar.user_header().rows_root(), naive_root(leaves));
+        assert!(jar.user_header().rows_root().is_some());
+
+        // the root is only computed on request
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+        let path = dir.path().join(SnapshotSegment::Transactions.filename(&(0..=3), &(0..=7)));
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        assert_eq!(jar.user_header().rows_root(), None);
+    }
+
+    #[test]
+    fn bounded_memory() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+        let path = |dir: &tempfile::TempDir| {
+            dir.path().join(SnapshotSegment::Transactions.filename(&(0..=3), &(0..=7)))
+        };
+
+        let unbounded = tempfile::tempdir().unwrap();
+        let unbounded_peak = peak_allocated(|| {
+            Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+                .with_crc(true)
+                .snapshot::<TestDB>(&provider, unbounded.path(), 0..=3)
+                .unwrap()
+        });
+
+        // Only fits the offsets of a single row at a time
+        let bounded = tempfile::tempdir().unwrap();
+        let bounded_peak = peak_allocated(|| {
+            Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+                .with_crc(true)
+                .with_max_memory(Some(24))
+                .snapshot::<TestDB>(&provider, bounded.path(), 0..=3)
+                .unwrap()
+        });
+
+        // Without a bound, the writer reserves 1MB each for its data and offsets buffers.
+        assert!(
+            bounded_peak + 1_000_000 < unbounded_peak,
+            "bounded snapshot peaked at {bounded_peak} bytes, unbounded at {unbounded_peak} bytes"
+        );
+
+        let unbounded = NippyJar::<SegmentHeader>::load(&path(&unbounded)).unwrap();
+        let bounded = NippyJar::<SegmentHeader>::load(&path(&bounded)).unwrap();
+        assert_eq!(bounded.user_header(), unbounded.user_header());
+        assert_eq!(
+            std::fs::read(bounded.offsets_path()).unwrap(),
+            std::fs::read(unbounded.offsets_path()).unwrap()
+        );
+        assert_eq!(
+            std::fs::read(bounded.data_path()).unwrap(),
+            std::fs::read(unbounded.data_path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn metrics_sink() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        let sink = Arc::new(RecordingSink::default());
+        let segment = Transactions::new(
+            Compression::Lz4,
+            Filters::WithFilters(InclusionFilter::Cuckoo, PerfectHashingFunction::Fmph),
+        )
+        .with_metrics_sink(sink.clone());
+
+        let dir = tempfile::tempdir().unwrap();
+        segment.snapshot::<TestDB>(&provider, dir.path(), 0..=1).unwrap();
+        segment.snapshot::<TestDB>(&provider, dir.path(), 2..=3).unwrap();
+
+        let records = sink.0.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        for (metrics, (block_range, tx_range)) in
+            records.iter().zip([(0..=1, 0..=3), (2..=3, 4..=7)])
+        {
+            let path =
+                dir.path().join(SnapshotSegment::Transactions.filename(&block_range, &tx_range));
+            assert_eq!(metrics.segment, SnapshotSegment::Transactions);
+            assert_eq!(metrics.block_range, block_range);
+            assert_eq!(metrics.rows, 4);
+            assert!(metrics.compressed_bytes > 0);
+            assert_eq!(metrics.compressed_bytes, std::fs::metadata(&path).unwrap().len());
+            assert!(metrics.filter_build_time <= metrics.duration);
+        }
+    }
+
+    #[test]
+    fn no_partial_files_on_error() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let files = || {
+            let mut files = std::fs::read_dir(dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        // only the files of the jar remain after a successful write
+        Transactions::new(Compression::Uncompressed, Filters::WithoutFilters)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=1)
+            .unwrap();
+        let written = files();
+        let filename = SnapshotSegment::Transactions.filename(&(0..=1), &(0..=3));
+        assert!(!written.is_empty());
+        assert!(written.iter().all(|file| file.starts_with(filename.as_str())));
+
+        // fail after the first row of the next range was written
+        let result = write_atomically(dir.path(), ".tmp-failing", |directory| {
+            let filename = SnapshotSegment::Transactions.filename(&(2..=3), &(4..=7));
+            let mut jar = NippyJar::new(
+                1,
+                &directory.join(filename.as_str()),
+                SegmentHeader::new(2..=3, 4..=7, SnapshotSegment::Transactions),
             );
+            let rows: Vec<ColumnResult<Vec<u8>>> = vec![Ok(vec![1]), Err("injected".into())];
+            jar.freeze(vec![rows], 2)?;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(files(), written);
+    }
+
+    #[test]
+    fn empty_blocks() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 0..1);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+        let filename = SnapshotSegment::Transactions.filename(&(0..=3), &(0..=0));
+
+        // a jar without rows is created by default
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::ZstdWithDictionary, Filters::WithoutFilters)
+            .with_crc(true)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+        let jar = NippyJar::<SegmentHeader>::load(&dir.path().join(&filename)).unwrap();
+        assert_eq!(jar.rows(), 0);
+        assert_eq!(jar.columns(), 2);
+        assert_eq!(jar.user_header().block_range(), &(0..=3));
+        assert!(jar.user_header().has_row_crcs());
+
+        // or skipped on request
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::ZstdWithDictionary, Filters::WithoutFilters)
+            .with_skip_empty(true)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+        assert!(!dir.path().join(&filename).exists());
+    }
+
+    #[test]
+    fn blob_index() {
+        let mut blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        let versioned_hashes = [B256::with_last_byte(1), B256::with_last_byte(2)];
+        let blob_tx = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip4844(TxEip4844 {
+                blob_versioned_hashes: versioned_hashes.to_vec(),
+                ..Default::default()
+            }),
+            Signature::default(),
+        );
+        // the blob transaction is the last of the second block
+        blocks[1].body.push(blob_tx);
+
+        let db = TestStageDB::default();
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+            .with_blob_index(true)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+
+        let path = dir.path().join(SnapshotSegment::Transactions.filename(&(0..=3), &(0..=8)));
+        let index = BlobIndex::load(&path).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get(&versioned_hashes[0]), Some((4, 0)));
+        assert_eq!(index.get(&versioned_hashes[1]), Some((4, 1)));
+        assert_eq!(index.get(&B256::with_last_byte(3)), None);
+
+        // the sidecar offset points into the blobs of the transaction stored in the jar
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        let (tx_number, sidecar_offset) = index.get(&versioned_hashes[1]).unwrap();
+        let row = cursor.row_by_number(tx_number as usize).unwrap().unwrap()[0].to_vec();
+        let expected = provider
+            .tx_ref()
+            .get::<RawTable<tables::Transactions>>(RawKey::new(tx_number))
+            .unwrap()
+            .unwrap();
+        assert_eq!(row, expected.raw_value());
+        let blobs = expected.value().unwrap().transaction.blob_versioned_hashes().unwrap();
+        assert_eq!(blobs[sidecar_offset], versioned_hashes[1]);
+
+        // the index is only written on request
+        let dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+            .snapshot::<TestDB>(&provider, dir.path(), 0..=3)
+            .unwrap();
+        let path = dir.path().join(SnapshotSegment::Transactions.filename(&(0..=3), &(0..=8)));
+        assert!(!blob_index_path(&path).exists());
+    }
+    #[test]
+    fn filter_variants() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        // both jars are written from a single read of the range
+        let sink = Arc::new(RecordingSink::default());
+        let filtered_dir = tempfile::tempdir().unwrap();
+        let unfiltered_dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+            .with_crc(true)
+            .with_metrics_sink(sink.clone())
+            .snapshot_filter_variants::<TestDB>(
+                &provider,
+                filtered_dir.path(),
+                unfiltered_dir.path(),
+                0..=3,
+                InclusionFilter::Cuckoo,
+                PerfectHashingFunction::Fmph,
+            )
+            .unwrap();
+        assert_eq!(sink.0.lock().unwrap().len(), 2);
+
+        let filename = SnapshotSegment::Transactions.filename(&(0..=3), &(0..=7));
+        let filtered =
+            NippyJar::<SegmentHeader>::load(&filtered_dir.path().join(&filename)).unwrap();
+        let unfiltered =
+            NippyJar::<SegmentHeader>::load(&unfiltered_dir.path().join(&filename)).unwrap();
+        assert!(filtered.filter_size() > 0);
+        assert_eq!(unfiltered.filter_size(), 0);
+        assert_eq!(filtered.user_header(), unfiltered.user_header());
+        assert!(filtered.user_header().has_row_crcs());
+
+        let filtered = filtered.load_filters().unwrap();
+        let mut filtered_cursor = NippyJarCursor::new(&filtered).unwrap();
+        let mut unfiltered_cursor = NippyJarCursor::new(&unfiltered).unwrap();
+        for (hash, id) in provider.transaction_hashes_by_range(0..8).unwrap() {
+            let expected = provider
+                .tx_ref()
+                .get::<RawTable<tables::Transactions>>(RawKey::new(id))
+                .unwrap()
+                .unwrap();
+
+            let row = unfiltered_cursor.row_by_number(id as usize).unwrap().unwrap();
+            assert_eq!(row[0], expected.raw_value());
+            assert_eq!(row[1], row_crc([expected.raw_value()]));
+            let row = filtered_cursor.row_by_number(id as usize).unwrap().unwrap();
+            assert_eq!(row[0], expected.raw_value());
+            let row = filtered_cursor.row_by_key(hash.as_slice()).unwrap().unwrap();
+            assert_eq!(row[0], expected.raw_value());
         }
+    }
+    #[test]
+    fn filter_variants_read_rows_once() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
 
-        create_snapshot_T1::<tables::Transactions, TxNumber, SegmentHeader>(
-            provider.tx_ref(),
-            tx_range,
-            None,
-            // We already prepared the dictionary beforehand
-            None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
-            hashes,
-            tx_range_len,
-            &mut jar,
-        )?;
+        let mut cursor =
+            provider.tx_ref().cursor_read::<RawTable<tables::Transactions>>().unwrap();
+        let mut pulled = 0;
+        let rows = cursor.walk_range(RawKey::new(0)..=RawKey::new(7)).unwrap();
+        let rows = rows.map(|row| -> ProviderResult<_> {
+            pulled += 1;
+            let (key, row) = row?;
+            Ok((key.key()?, row))
+        });
 
-        Ok(())
+        let filtered_dir = tempfile::tempdir().unwrap();
+        let unfiltered_dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+            .with_root(true)
+            .with_crc(true)
+            .write_filter_variants::<TestDB>(
+                &provider,
+                filtered_dir.path(),
+                unfiltered_dir.path(),
+                0..=3,
+                Filters::WithFilters(InclusionFilter::Cuckoo, PerfectHashingFunction::Fmph),
+                rows,
+                Instant::now(),
+            )
+            .unwrap();
+        assert_eq!(pulled, 8);
+
+        let filename = SnapshotSegment::Transactions.filename(&(0..=3), &(0..=7));
+        let filtered =
+            NippyJar::<SegmentHeader>::load(&filtered_dir.path().join(&filename)).unwrap();
+        let unfiltered =
+            NippyJar::<SegmentHeader>::load(&unfiltered_dir.path().join(&filename)).unwrap();
+        assert!(unfiltered.user_header().rows_root().is_some());
+        assert_eq!(filtered.user_header(), unfiltered.user_header());
+        assert_eq!(filtered.rows(), 8);
+        assert_eq!(unfiltered.rows(), 8);
     }
 }
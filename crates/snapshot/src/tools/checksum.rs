@@ -0,0 +1,70 @@
+use crate::segments::row_crc;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{NippyJar, NippyJarCursor};
+use reth_primitives::snapshot::SegmentHeader;
+use std::path::Path;
+
+/// Verifies the checksum of every row of the jar at `path`, see
+/// [`SegmentHeader::has_row_crcs`].
+///
+/// Returns [`ProviderError::SnapshotChecksumMismatch`] with the block or transaction number of
+/// the first corrupted row. Jars generated without checksums are not verified.
+pub fn verify_row_crcs(path: &Path) -> ProviderResult<()> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let header = jar.user_header();
+    if !header.has_row_crcs() {
+        return Ok(())
+    }
+
+    let mut cursor = NippyJarCursor::new(&jar)?;
+    let mut number = header.start();
+    while let Some(row) = cursor.next_row()? {
+        let (crc, columns) = row.split_last().expect("has a checksum column");
+        if *crc != row_crc(columns.iter().copied()) {
+            return Err(ProviderError::SnapshotChecksumMismatch(header.segment(), number))
+        }
+        number += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        segments::Transactions,
+        tools::test_utils::{corrupt_row, transactions_jar_with},
+    };
+    use reth_primitives::{
+        snapshot::{Compression, Filters},
+        SnapshotSegment,
+    };
+
+    #[test]
+    fn detects_corrupted_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment =
+            Transactions::new(Compression::Uncompressed, Filters::WithoutFilters).with_crc(true);
+        let (_db, path) = transactions_jar_with(dir.path(), 0..=3, segment);
+        verify_row_crcs(&path).unwrap();
+
+        corrupt_row(&path, 5, 4);
+
+        assert_eq!(
+            verify_row_crcs(&path),
+            Err(ProviderError::SnapshotChecksumMismatch(SnapshotSegment::Transactions, 5))
+        );
+    }
+
+    #[test]
+    fn skips_jars_without_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = Transactions::new(Compression::Uncompressed, Filters::WithoutFilters);
+        let (_db, path) = transactions_jar_with(dir.path(), 0..=3, segment);
+
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        assert!(!jar.user_header().has_row_crcs());
+        assert_eq!(verify_row_crcs(&path), Ok(()));
+    }
+}
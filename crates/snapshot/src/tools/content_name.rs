@@ -0,0 +1,54 @@
+use reth_interfaces::provider::ProviderResult;
+use reth_nippy_jar::NippyJar;
+use reth_primitives::{fs, hex, keccak256, snapshot::SegmentHeader};
+use std::path::Path;
+
+/// Returns the file name of the jar at `path` with the hash of its contents appended, e.g. to
+/// distribute jars under immutable names that can be verified after download.
+///
+/// The hash is the keccak256 hash of the data, offsets and configuration files of the jar, each
+/// prefixed with its length. It doesn't depend on where the jar is stored.
+pub fn content_addressed_name(path: &Path) -> ProviderResult<String> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+
+    let mut contents = Vec::new();
+    for file in [jar.data_path().to_path_buf(), jar.offsets_path(), jar.config_path()] {
+        let bytes = fs::read(&file)?;
+        contents.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        contents.extend_from_slice(&bytes);
+    }
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    Ok(format!("{name}_{}", hex::encode(keccak256(contents))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::transactions_jar;
+    use reth_primitives::snapshot::{Compression, Filters};
+
+    #[test]
+    fn identical_jars_have_identical_names() {
+        let jar = |compression| {
+            let dir = tempfile::tempdir().unwrap();
+            let (_, path) =
+                transactions_jar(dir.path(), 0..=3, compression, Filters::WithoutFilters);
+            let name = content_addressed_name(&path).unwrap();
+            let filename = path.file_name().unwrap().to_str().unwrap().to_string();
+            (name, filename)
+        };
+
+        let (name, filename) = jar(Compression::Lz4);
+        let (other_name, other_filename) = jar(Compression::Lz4);
+        assert_eq!(name, other_name);
+        assert_eq!(filename, other_filename);
+        assert_eq!(name.len(), filename.len() + 1 + 64);
+        assert!(name.starts_with(&format!("{filename}_")));
+
+        // different contents, but the same file name
+        let (uncompressed, uncompressed_filename) = jar(Compression::Uncompressed);
+        assert_eq!(uncompressed_filename, filename);
+        assert_ne!(uncompressed, name);
+    }
+}
@@ -0,0 +1,107 @@
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{
+    fs::{self, FsPathError},
+    BlockNumber, SnapshotSegment,
+};
+use std::{ops::RangeInclusive, path::Path};
+
+/// Block coverage of the snapshots of a segment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Highest block reachable from genesis without a gap, if the genesis block is covered.
+    pub highest_contiguous_block: Option<BlockNumber>,
+    /// Block ranges that aren't covered between the first and the last snapshot, in ascending
+    /// order. A missing genesis range is included.
+    pub gaps: Vec<RangeInclusive<BlockNumber>>,
+}
+
+impl Coverage {
+    /// Returns `true` if the snapshots cover a contiguous prefix of history.
+    pub fn is_contiguous(&self) -> bool {
+        self.gaps.is_empty()
+    }
+}
+
+/// Computes the block coverage of the snapshots of `segment` in `dir`.
+///
+/// Only the file names are parsed, see [`SnapshotSegment::parse_filename`], so the snapshot files
+/// themselves aren't validated.
+pub fn snapshot_coverage(dir: &Path, segment: SnapshotSegment) -> ProviderResult<Coverage> {
+    let mut ranges = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry.map_err(|err| FsPathError::read_dir(err, dir))?;
+        if let Some((file_segment, block_range, _)) =
+            SnapshotSegment::parse_filename(&entry.file_name())
+        {
+            if file_segment == segment {
+                ranges.push(block_range);
+            }
+        }
+    }
+    ranges.sort_unstable_by_key(|range| *range.start());
+
+    let mut coverage = Coverage::default();
+    // next block that isn't covered yet
+    let mut next_block = 0;
+    for range in ranges {
+        if *range.start() > next_block {
+            coverage.gaps.push(next_block..=*range.start() - 1);
+        }
+
+        if coverage.gaps.is_empty() {
+            coverage.highest_contiguous_block =
+                Some(coverage.highest_contiguous_block.unwrap_or_default().max(*range.end()));
+        }
+        next_block = next_block.max(range.end().saturating_add(1));
+    }
+
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_dir(
+        segment: SnapshotSegment,
+        ranges: &[RangeInclusive<BlockNumber>],
+    ) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for range in ranges {
+            let path = dir.path().join(segment.filename(range, &(0..=0)));
+            std::fs::write(path, b"").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn contiguous_coverage() {
+        let dir = snapshot_dir(SnapshotSegment::Transactions, &[20..=29, 0..=9, 10..=19]);
+        // files of other segments don't count
+        let headers = SnapshotSegment::Headers.filename(&(30..=39), &(0..=0));
+        std::fs::write(dir.path().join(headers), b"").unwrap();
+
+        let coverage = snapshot_coverage(dir.path(), SnapshotSegment::Transactions).unwrap();
+        assert_eq!(coverage, Coverage { highest_contiguous_block: Some(29), gaps: vec![] });
+        assert!(coverage.is_contiguous());
+
+        let coverage = snapshot_coverage(dir.path(), SnapshotSegment::Receipts).unwrap();
+        assert_eq!(coverage, Coverage::default());
+    }
+
+    #[test]
+    fn gapped_coverage() {
+        let dir = snapshot_dir(SnapshotSegment::Transactions, &[0..=9, 20..=29, 40..=49]);
+        let coverage = snapshot_coverage(dir.path(), SnapshotSegment::Transactions).unwrap();
+        assert_eq!(
+            coverage,
+            Coverage { highest_contiguous_block: Some(9), gaps: vec![10..=19, 30..=39] }
+        );
+        assert!(!coverage.is_contiguous());
+
+        // genesis is missing
+        let dir = snapshot_dir(SnapshotSegment::Transactions, &[10..=19, 20..=29]);
+        let coverage = snapshot_coverage(dir.path(), SnapshotSegment::Transactions).unwrap();
+        assert_eq!(coverage, Coverage { highest_contiguous_block: None, gaps: vec![0..=9] });
+    }
+}
@@ -0,0 +1,99 @@
+use super::content_addressed_name;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{
+    fs::{self, FsPathError},
+    SnapshotSegment,
+};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// Returns the jars of `segment` in `new_dir` that a client holding the jars in `old_dir` needs
+/// to fetch to sync incrementally, sorted by path.
+///
+/// A jar is part of the delta if `old_dir` has no jar with the same file name, or if the contents
+/// of the two jars differ, see [`content_addressed_name`].
+pub fn snapshot_delta(
+    old_dir: &Path,
+    new_dir: &Path,
+    segment: SnapshotSegment,
+) -> ProviderResult<Vec<PathBuf>> {
+    let old_jars = segment_jars(old_dir, segment)?;
+
+    let mut delta = Vec::new();
+    for (name, path) in segment_jars(new_dir, segment)? {
+        let unchanged = match old_jars.get(&name) {
+            Some(old_path) => content_addressed_name(old_path)? == content_addressed_name(&path)?,
+            None => false,
+        };
+        if !unchanged {
+            delta.push(path);
+        }
+    }
+    delta.sort_unstable();
+
+    Ok(delta)
+}
+
+/// Returns the jars of `segment` in `dir` by file name.
+fn segment_jars(
+    dir: &Path,
+    segment: SnapshotSegment,
+) -> ProviderResult<BTreeMap<OsString, PathBuf>> {
+    let mut jars = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry.map_err(|err| FsPathError::read_dir(err, dir))?;
+        let name = entry.file_name();
+        if SnapshotSegment::parse_filename(&name)
+            .is_some_and(|(file_segment, _, _)| file_segment == segment)
+        {
+            jars.insert(name, entry.path());
+        }
+    }
+    Ok(jars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::transactions_jar;
+    use reth_primitives::snapshot::{Compression, Filters};
+
+    #[test]
+    fn delta_contains_added_and_changed_jars() {
+        let old_dir = tempfile::tempdir().unwrap();
+        transactions_jar(old_dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+        transactions_jar(old_dir.path(), 4..=7, Compression::Lz4, Filters::WithoutFilters);
+
+        let new_dir = tempfile::tempdir().unwrap();
+        let (_, unchanged) =
+            transactions_jar(new_dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+        let (_, changed) = transactions_jar(
+            new_dir.path(),
+            4..=7,
+            Compression::Uncompressed,
+            Filters::WithoutFilters,
+        );
+        let (_, added) =
+            transactions_jar(new_dir.path(), 8..=11, Compression::Lz4, Filters::WithoutFilters);
+
+        let delta =
+            snapshot_delta(old_dir.path(), new_dir.path(), SnapshotSegment::Transactions).unwrap();
+        let mut expected = vec![changed, added];
+        expected.sort_unstable();
+        assert_eq!(delta, expected);
+        assert!(!delta.contains(&unchanged));
+
+        // other segments have no jars
+        let delta =
+            snapshot_delta(old_dir.path(), new_dir.path(), SnapshotSegment::Headers).unwrap();
+        assert!(delta.is_empty());
+
+        // nothing to fetch if both directories hold the same jars
+        let delta =
+            snapshot_delta(new_dir.path(), new_dir.path(), SnapshotSegment::Transactions).unwrap();
+        assert!(delta.is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+use reth_interfaces::provider::ProviderResult;
+use reth_nippy_jar::NippyJar;
+use reth_primitives::snapshot::SegmentHeader;
+use std::path::Path;
+
+/// Number of rows stored in a jar relative to the blocks it covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Density {
+    /// Number of rows in the jar.
+    pub rows: usize,
+    /// Number of blocks covered by the jar.
+    pub blocks: u64,
+    /// Average number of rows per block.
+    pub rows_per_block: f64,
+}
+
+/// Returns the [`Density`] of the jar at `path`, computed from its metadata only.
+pub fn snapshot_density(path: &Path) -> ProviderResult<Density> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let block_range = jar.user_header().block_range();
+
+    let rows = jar.rows();
+    let blocks = block_range.end().saturating_sub(*block_range.start()) + 1;
+    let rows_per_block = rows as f64 / blocks as f64;
+
+    Ok(Density { rows, blocks, rows_per_block })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::transactions_jar;
+    use reth_primitives::snapshot::{Compression, Filters};
+
+    #[test]
+    fn two_transactions_per_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+
+        assert_eq!(
+            snapshot_density(&path).unwrap(),
+            Density { rows: 8, blocks: 4, rows_per_block: 2.0 }
+        );
+    }
+}
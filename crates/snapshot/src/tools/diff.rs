@@ -0,0 +1,87 @@
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{NippyJar, NippyJarCursor};
+use reth_primitives::snapshot::SegmentHeader;
+use std::path::Path;
+
+/// Result of comparing two jars with [`diff_snapshots`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotDiff {
+    /// Both jars have the same rows.
+    Equal,
+    /// The jars first differ at the row with the given block or transaction number, either
+    /// because its values differ or because only one of the jars has it.
+    Diverged(u64),
+}
+
+/// Compares the jars at `a` and `b` row by row and returns the first row at which they diverge.
+///
+/// Rows are compared by their decompressed values, so jars with the same rows but different
+/// compression are equal. Both jars have to be of the same segment and cover the same block range.
+pub fn diff_snapshots(a: &Path, b: &Path) -> ProviderResult<SnapshotDiff> {
+    let first = NippyJar::<SegmentHeader>::load(a)?;
+    let second = NippyJar::<SegmentHeader>::load(b)?;
+    let (header, other) = (first.user_header(), second.user_header());
+    let segment = header.segment();
+
+    if other.segment() != segment {
+        return Err(ProviderError::SnapshotDiff(segment, "segments differ"))
+    }
+    if other.block_range() != header.block_range() {
+        return Err(ProviderError::SnapshotDiff(segment, "block ranges differ"))
+    }
+
+    let mut first_cursor = NippyJarCursor::new(&first)?;
+    let mut second_cursor = NippyJarCursor::new(&second)?;
+    let mut number = header.start();
+    loop {
+        match (first_cursor.next_row()?, second_cursor.next_row()?) {
+            (None, None) => return Ok(SnapshotDiff::Equal),
+            (Some(row), Some(other_row)) if row == other_row => number += 1,
+            _ => return Ok(SnapshotDiff::Diverged(number)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        segments::{Segment, Transactions},
+        tools::test_utils::{corrupt_row, transactions_jar, TestDB},
+    };
+    use reth_primitives::snapshot::{Compression, Filters};
+
+    #[test]
+    fn identical_jars() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+
+        let other_dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Uncompressed, Filters::WithoutFilters)
+            .snapshot::<TestDB>(&db.factory.provider().unwrap(), other_dir.path(), 0..=3)
+            .unwrap();
+        let other = other_dir.path().join(path.file_name().unwrap());
+
+        assert_eq!(diff_snapshots(&path, &path), Ok(SnapshotDiff::Equal));
+        assert_eq!(diff_snapshots(&path, &other), Ok(SnapshotDiff::Equal));
+    }
+
+    #[test]
+    fn flipped_byte() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Uncompressed, Filters::WithoutFilters);
+
+        let other_dir = tempfile::tempdir().unwrap();
+        Transactions::new(Compression::Uncompressed, Filters::WithoutFilters)
+            .snapshot::<TestDB>(&db.factory.provider().unwrap(), other_dir.path(), 0..=3)
+            .unwrap();
+        let other = other_dir.path().join(path.file_name().unwrap());
+
+        corrupt_row(&other, 5, 0);
+
+        assert_eq!(diff_snapshots(&path, &other), Ok(SnapshotDiff::Diverged(5)));
+        assert_eq!(diff_snapshots(&other, &path), Ok(SnapshotDiff::Diverged(5)));
+    }
+}
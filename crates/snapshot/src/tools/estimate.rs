@@ -0,0 +1,299 @@
+use crate::segments::{Headers, Receipts, Segment, Transactions};
+use reth_db::database::Database;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{
+    fs::{self, FsPathError},
+    snapshot::{Compression, Filters},
+    BlockNumber, SnapshotSegment,
+};
+use reth_provider::{BlockNumReader, BlockReader, DatabaseProviderRO};
+use std::{ops::RangeInclusive, path::Path};
+
+/// Prefix of the directory in the scratch directory the sample snapshots are written to.
+const ESTIMATE_DIR_PREFIX: &str = "estimate";
+
+/// Number of block ranges that are sampled across the history.
+const SAMPLES: u64 = 8;
+
+/// Number of blocks of each sampled block range.
+const SAMPLE_BLOCKS: u64 = 10_000;
+
+/// Estimates the total size in bytes of snapshotting the whole history of `segment` with the given
+/// compression and filters.
+///
+/// Block ranges spread evenly across the history are snapshotted into a subdirectory of
+/// `scratch_dir`, which is removed afterwards. Their size per row is extrapolated to all rows of
+/// the segment. Histories shorter than the samples are snapshotted as a whole.
+pub fn estimate_full_snapshot_size<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    segment: SnapshotSegment,
+    compression: Compression,
+    filters: Filters,
+    scratch_dir: &Path,
+) -> ProviderResult<u64> {
+    estimate_with_samples(
+        provider,
+        segment,
+        compression,
+        filters,
+        scratch_dir,
+        SAMPLES,
+        SAMPLE_BLOCKS,
+    )
+}
+
+/// Suggests a block interval for snapshots of `segment` with the given compression, so that each
+/// jar is approximately `target_bytes` large.
+///
+/// The size per block is extrapolated from samples of the history without filters, see
+/// [`estimate_full_snapshot_size`]. The suggestion is at least one block, and covers the whole
+/// history if it's smaller than the target.
+pub fn suggest_block_interval<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    segment: SnapshotSegment,
+    compression: Compression,
+    target_bytes: u64,
+    scratch_dir: &Path,
+) -> ProviderResult<u64> {
+    suggest_with_samples(
+        provider,
+        segment,
+        compression,
+        target_bytes,
+        scratch_dir,
+        SAMPLES,
+        SAMPLE_BLOCKS,
+    )
+}
+
+fn suggest_with_samples<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    segment: SnapshotSegment,
+    compression: Compression,
+    target_bytes: u64,
+    scratch_dir: &Path,
+    samples: u64,
+    sample_blocks: u64,
+) -> ProviderResult<u64> {
+    let total_blocks = provider.last_block_number()? + 1;
+    let total_bytes = estimate_with_samples(
+        provider,
+        segment,
+        compression,
+        Filters::WithoutFilters,
+        scratch_dir,
+        samples,
+        sample_blocks,
+    )?;
+    if total_bytes <= target_bytes {
+        return Ok(total_blocks)
+    }
+
+    let interval = target_bytes as u128 * total_blocks as u128 / total_bytes as u128;
+    Ok((interval as u64).max(1))
+}
+
+fn estimate_with_samples<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    segment: SnapshotSegment,
+    compression: Compression,
+    filters: Filters,
+    scratch_dir: &Path,
+    samples: u64,
+    sample_blocks: u64,
+) -> ProviderResult<u64> {
+    let tip = provider.last_block_number()?;
+    let history = 0..=tip;
+    let total_blocks = tip + 1;
+
+    let sample_ranges = if samples * sample_blocks >= total_blocks {
+        vec![history.clone()]
+    } else {
+        let stride = total_blocks / samples;
+        (0..samples).map(|i| i * stride..=i * stride + sample_blocks - 1).collect()
+    };
+
+    // A new directory, so other files in the scratch directory are not counted. It's removed when
+    // dropped, so the samples are cleaned up on errors as well.
+    fs::create_dir_all(scratch_dir)?;
+    let dir = tempfile::Builder::new()
+        .prefix(ESTIMATE_DIR_PREFIX)
+        .tempdir_in(scratch_dir)
+        .map_err(|err| FsPathError::create_dir(err, scratch_dir))?;
+    let mut sample_bytes = 0;
+    let mut sample_rows = 0;
+    let mut sampled_blocks = 0;
+    for range in sample_ranges {
+        match segment {
+            SnapshotSegment::Headers => Headers::new(compression, filters)
+                .snapshot::<DB>(provider, dir.path(), range.clone())?,
+            SnapshotSegment::Transactions => Transactions::new(compression, filters)
+                .snapshot::<DB>(provider, dir.path(), range.clone())?,
+            SnapshotSegment::Receipts => Receipts::new(compression, filters)
+                .snapshot::<DB>(provider, dir.path(), range.clone())?,
+        }
+        sample_rows += rows(provider, segment, &range)?;
+        sampled_blocks += range.end() - range.start() + 1;
+    }
+    for entry in fs::read_dir(dir.path())? {
+        let entry = entry.map_err(|err| FsPathError::read_dir(err, dir.path()))?;
+        sample_bytes += entry.metadata().map_err(|err| FsPathError::open(err, entry.path()))?.len();
+    }
+    let path = dir.path().to_path_buf();
+    dir.close().map_err(|err| FsPathError::remove_dir(err, path))?;
+
+    // Blocks without rows still take up space, so fall back to the size per block.
+    let total_rows = rows(provider, segment, &history)?;
+    let estimate = if sample_rows > 0 {
+        sample_bytes as u128 * total_rows as u128 / sample_rows as u128
+    } else {
+        sample_bytes as u128 * total_blocks as u128 / sampled_blocks as u128
+    };
+    Ok(estimate as u64)
+}
+
+/// Returns the number of rows of `segment` for the given block range.
+fn rows<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    segment: SnapshotSegment,
+    block_range: &RangeInclusive<BlockNumber>,
+) -> ProviderResult<u64> {
+    if segment == SnapshotSegment::Headers {
+        return Ok(block_range.end() - block_range.start() + 1)
+    }
+    let first = provider
+        .block_body_indices(*block_range.start())?
+        .ok_or(ProviderError::BlockBodyIndicesNotFound(*block_range.start()))?;
+    let last = provider
+        .block_body_indices(*block_range.end())?
+        .ok_or(ProviderError::BlockBodyIndicesNotFound(*block_range.end()))?;
+    Ok(last.next_tx_num() - first.first_tx_num())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::TestDB;
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_primitives::B256;
+    use reth_stages::test_utils::TestStageDB;
+
+    fn assert_close(estimate: u64, actual: u64) {
+        let error = (estimate as f64 - actual as f64).abs() / actual as f64;
+        assert!(error < 0.2, "estimate {estimate} is too far off from {actual}");
+    }
+
+    #[test]
+    fn extrapolates_samples() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=31, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        // snapshot the whole history in ranges of 8 blocks
+        let snapshots = tempfile::tempdir().unwrap();
+        for start in (0..32).step_by(8) {
+            Transactions::new(Compression::Uncompressed, Filters::WithoutFilters)
+                .snapshot::<TestDB>(&provider, snapshots.path(), start..=start + 7)
+                .unwrap();
+        }
+        let actual = std::fs::read_dir(snapshots.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .sum::<u64>();
+
+        // files of a previous run in the scratch directory are not counted
+        let scratch = tempfile::tempdir().unwrap();
+        let leftover = scratch.path().join(ESTIMATE_DIR_PREFIX);
+        std::fs::create_dir(&leftover).unwrap();
+        std::fs::write(leftover.join("leftover"), vec![0; actual as usize]).unwrap();
+
+        // only half of the history is sampled
+        let estimate = estimate_with_samples(
+            &provider,
+            SnapshotSegment::Transactions,
+            Compression::Uncompressed,
+            Filters::WithoutFilters,
+            scratch.path(),
+            2,
+            8,
+        )
+        .unwrap();
+        assert_close(estimate, actual);
+        // only the leftover directory remains
+        let entries = std::fs::read_dir(scratch.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(entries, vec![leftover]);
+
+        // the history is shorter than the default samples, so it's snapshotted as a whole
+        let estimate = estimate_full_snapshot_size(
+            &provider,
+            SnapshotSegment::Transactions,
+            Compression::Uncompressed,
+            Filters::WithoutFilters,
+            scratch.path(),
+        )
+        .unwrap();
+        assert_close(estimate, actual);
+    }
+
+    #[test]
+    fn suggests_interval_for_target_size() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=63, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        // Snapshots every range into its own directory, and returns the size of each jar.
+        let jar_sizes = |interval: u64| {
+            (0..64)
+                .step_by(interval as usize)
+                .map(|start| {
+                    let dir = tempfile::tempdir().unwrap();
+                    let end = (start + interval - 1).min(63);
+                    Transactions::new(Compression::Lz4, Filters::WithoutFilters)
+                        .snapshot::<TestDB>(&provider, dir.path(), start..=end)
+                        .unwrap();
+                    std::fs::read_dir(dir.path())
+                        .unwrap()
+                        .map(|entry| entry.unwrap().metadata().unwrap().len())
+                        .sum::<u64>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // aim for the size of jars of 16 blocks
+        let target = jar_sizes(16)[0];
+        let scratch = tempfile::tempdir().unwrap();
+        let interval = suggest_with_samples(
+            &provider,
+            SnapshotSegment::Transactions,
+            Compression::Lz4,
+            target,
+            scratch.path(),
+            2,
+            16,
+        )
+        .unwrap();
+        assert!((12..=20).contains(&interval), "unexpected interval {interval}");
+
+        // all jars but the last, which may cover fewer blocks, are close to the target
+        let sizes = jar_sizes(interval);
+        for size in &sizes[..sizes.len() - 1] {
+            assert_close(*size, target);
+        }
+
+        // the whole history is smaller than the target
+        let interval = suggest_block_interval(
+            &provider,
+            SnapshotSegment::Transactions,
+            Compression::Lz4,
+            u64::MAX,
+            scratch.path(),
+        )
+        .unwrap();
+        assert_eq!(interval, 64);
+    }
+}
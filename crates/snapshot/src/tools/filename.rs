@@ -0,0 +1,94 @@
+use super::recompress::compression_of;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::NippyJar;
+use reth_primitives::{snapshot::SegmentHeader, SnapshotSegment};
+use std::path::Path;
+
+/// Verifies that the name of the jar at `path` matches its contents.
+///
+/// The segment, block range and transaction range encoded in the file name, see
+/// [`SnapshotSegment::parse_filename`], have to match the segment header of the jar. The
+/// compression is only verified if the file name encodes it, see
+/// [`SnapshotSegment::parse_filename_configuration`].
+///
+/// Returns [`ProviderError::SnapshotFilenameMismatch`] with the first mismatch.
+pub fn verify_filename_matches_contents(path: &Path) -> ProviderResult<()> {
+    let mismatch = |reason| ProviderError::SnapshotFilenameMismatch(path.to_path_buf(), reason);
+    let name = path.file_name().unwrap_or_default();
+    let (segment, block_range, tx_range) =
+        SnapshotSegment::parse_filename(name).ok_or_else(|| mismatch("unrecognized file name"))?;
+
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let header = jar.user_header();
+    if header.segment() != segment {
+        return Err(mismatch("segments differ"))
+    }
+    if header.block_range() != &block_range {
+        return Err(mismatch("block ranges differ"))
+    }
+    if header.tx_range() != &tx_range {
+        return Err(mismatch("transaction ranges differ"))
+    }
+
+    if let Some((_, compression)) = SnapshotSegment::parse_filename_configuration(name) {
+        if compression != compression_of(&jar) {
+            return Err(mismatch("compressions differ"))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::transactions_jar;
+    use reth_primitives::snapshot::{Compression, Filters};
+    use std::path::PathBuf;
+
+    /// Moves all files of the jar at `from` to `to`.
+    fn rename_jar(from: &Path, to: &Path) {
+        let jar = NippyJar::<SegmentHeader>::load(from).unwrap();
+        for file in [jar.offsets_path(), jar.config_path()] {
+            std::fs::rename(&file, to.with_extension(file.extension().unwrap())).unwrap();
+        }
+        std::fs::rename(from, to).unwrap();
+    }
+
+    #[test]
+    fn detects_mislabeled_jar() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+        verify_filename_matches_contents(&path).unwrap();
+
+        let labeled = |compression: Compression, block_range, tx_range| -> PathBuf {
+            dir.path().join(SnapshotSegment::Transactions.filename_with_configuration(
+                Filters::WithoutFilters,
+                compression,
+                &block_range,
+                &tx_range,
+            ))
+        };
+
+        // correctly named with its configuration
+        let configured = labeled(Compression::Lz4, 0..=3, 0..=7);
+        rename_jar(&path, &configured);
+        verify_filename_matches_contents(&configured).unwrap();
+
+        // deliberately mislabeled
+        let mut current = configured;
+        for (mislabeled, reason) in [
+            (labeled(Compression::Zstd, 0..=3, 0..=7), "compressions differ"),
+            (labeled(Compression::Lz4, 0..=4, 0..=7), "block ranges differ"),
+            (labeled(Compression::Lz4, 0..=3, 0..=9), "transaction ranges differ"),
+        ] {
+            rename_jar(&current, &mislabeled);
+            assert_eq!(
+                verify_filename_matches_contents(&mislabeled),
+                Err(ProviderError::SnapshotFilenameMismatch(mislabeled.clone(), reason))
+            );
+            current = mislabeled;
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::recompress::{compression_of, read_columns, with_compression};
+use reth_db::table::Decompress;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::NippyJar;
+use reth_primitives::{snapshot::SegmentHeader, SnapshotSegment, TransactionSignedNoHash};
+use std::path::{Path, PathBuf};
+
+/// Merges the jar at `a` with the jar at `b`, which has to start right after `a` ends, into a
+/// single jar next to `a`, and returns the path of the new jar.
+///
+/// The new jar uses the compression of `a`. If the jars use filters, the filter and perfect hashing
+/// function are rebuilt over the rows of both jars. The rows root of the jars, if any, is not
+/// carried over.
+///
+/// Both jars need to be of the same segment and have the same columns. Transaction hashes can't be
+/// derived from receipts, so receipt jars can only be merged if they don't use filters.
+pub fn merge_snapshots(a: &Path, b: &Path) -> ProviderResult<PathBuf> {
+    let first = NippyJar::<SegmentHeader>::load(a)?.load_filters()?;
+    let second = NippyJar::<SegmentHeader>::load(b)?;
+    let (header, next) = (first.user_header(), second.user_header());
+    let segment = header.segment();
+
+    if next.segment() != segment {
+        return Err(ProviderError::SnapshotMerge(segment, "segments differ"))
+    }
+    if first.columns() != second.columns() || header.has_row_crcs() != next.has_row_crcs() {
+        return Err(ProviderError::SnapshotMerge(segment, "columns differ"))
+    }
+    if header.block_end().checked_add(1) != Some(next.block_start()) ||
+        header.tx_range().end().checked_add(1) != Some(next.tx_start())
+    {
+        return Err(ProviderError::SnapshotMerge(segment, "ranges are not adjacent"))
+    }
+
+    let block_range = header.block_start()..=next.block_end();
+    let tx_range = header.tx_start()..=*next.tx_range().end();
+    let mut merged_header = SegmentHeader::new(block_range.clone(), tx_range.clone(), segment)
+        .with_portable(header.is_portable() && next.is_portable());
+    merged_header.set_row_crcs(header.has_row_crcs());
+
+    let total_rows = first.rows() + second.rows();
+    let mut columns = vec![Vec::with_capacity(total_rows); first.columns()];
+    read_columns(&first, &mut columns)?;
+    read_columns(&second, &mut columns)?;
+
+    // Keys of the filter and perfect hashing function, in row order.
+    let keys = if first.uses_filters() {
+        Some(match segment {
+            SnapshotSegment::Headers => columns[2].clone(),
+            SnapshotSegment::Transactions => columns[0]
+                .iter()
+                .map(|row| Ok(TransactionSignedNoHash::decompress(row)?.hash().to_vec()))
+                .collect::<ProviderResult<Vec<_>>>()?,
+            SnapshotSegment::Receipts => {
+                return Err(ProviderError::SnapshotMerge(
+                    segment,
+                    "receipt filters can't be rebuilt",
+                ))
+            }
+        })
+    } else {
+        None
+    };
+
+    let compression = compression_of(&first);
+    let mut jar = first
+        .into_empty_at(&a.with_file_name(segment.filename(&block_range, &tx_range)))
+        .reset_filters(total_rows);
+    *jar.user_header_mut() = merged_header;
+    let mut jar = with_compression(jar, compression, 0, &columns)?;
+
+    if let Some(keys) = keys {
+        jar.prepare_index(keys.into_iter().map(Ok), total_rows)?;
+    }
+    jar.freeze(
+        columns.into_iter().map(|column| column.into_iter().map(Ok)).collect(),
+        total_rows as u64,
+    )?;
+
+    Ok(jar.data_path().to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        segments::{Segment, Transactions},
+        tools::test_utils::TestDB,
+    };
+    use reth_db::{tables, transaction::DbTx, RawKey, RawTable};
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_nippy_jar::NippyJarCursor;
+    use reth_primitives::{
+        snapshot::{Compression, Filters, InclusionFilter, PerfectHashingFunction},
+        B256,
+    };
+    use reth_provider::TransactionsProviderExt;
+    use reth_stages::test_utils::TestStageDB;
+    use std::ops::RangeInclusive;
+
+    #[test]
+    fn merge_adjacent_jars() {
+        let db = TestStageDB::default();
+        let blocks = random_block_range(&mut generators::rng(), 0..=5, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let provider = db.factory.provider().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let filters = Filters::WithFilters(InclusionFilter::Cuckoo, PerfectHashingFunction::Fmph);
+        let segment = Transactions::new(Compression::Lz4, filters);
+        segment.snapshot::<TestDB>(&provider, dir.path(), 0..=2).unwrap();
+        segment.snapshot::<TestDB>(&provider, dir.path(), 3..=5).unwrap();
+
+        let path = |blocks: RangeInclusive<u64>, txs: RangeInclusive<u64>| {
+            dir.path().join(SnapshotSegment::Transactions.filename(&blocks, &txs))
+        };
+        let (a, b) = (path(0..=2, 0..=5), path(3..=5, 6..=11));
+
+        // the jars have to be passed in order
+        assert_eq!(
+            merge_snapshots(&b, &a),
+            Err(ProviderError::SnapshotMerge(
+                SnapshotSegment::Transactions,
+                "ranges are not adjacent"
+            ))
+        );
+
+        let merged = merge_snapshots(&a, &b).unwrap();
+        assert_eq!(merged, path(0..=5, 0..=11));
+
+        let jar = NippyJar::<SegmentHeader>::load(&merged).unwrap().load_filters().unwrap();
+        assert_eq!(jar.user_header().block_range(), &(0..=5));
+        assert_eq!(jar.user_header().tx_range(), &(0..=11));
+        assert_eq!(jar.rows(), 12);
+
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        let hashes = provider.transaction_hashes_by_range(0..12).unwrap();
+        for (hash, id) in hashes {
+            let expected = provider
+                .tx_ref()
+                .get::<RawTable<tables::Transactions>>(RawKey::new(id))
+                .unwrap()
+                .unwrap();
+
+            let row = cursor.row_by_number(id as usize).unwrap().unwrap();
+            assert_eq!(row[0], expected.raw_value());
+            // the rebuilt filter and perfect hashing function find every row by hash
+            let row = cursor.row_by_key(hash.as_slice()).unwrap().unwrap();
+            assert_eq!(row[0], expected.raw_value());
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Tools to inspect and maintain existing snapshot jars.
+
+mod checksum;
+pub use checksum::verify_row_crcs;
+
+mod content_name;
+pub use content_name::content_addressed_name;
+
+mod coverage;
+pub use coverage::{snapshot_coverage, Coverage};
+
+mod delta;
+pub use delta::snapshot_delta;
+
+mod diff;
+pub use diff::{diff_snapshots, SnapshotDiff};
+
+mod density;
+pub use density::{snapshot_density, Density};
+
+mod estimate;
+pub use estimate::{estimate_full_snapshot_size, suggest_block_interval};
+
+mod filename;
+pub use filename::verify_filename_matches_contents;
+
+mod merge;
+pub use merge::merge_snapshots;
+
+mod proof;
+pub use proof::prove_transaction;
+
+mod read_amplification;
+pub use read_amplification::{measure_read_amplification, ReadAmpReport};
+
+mod recompress;
+pub use recompress::recompress_snapshot;
+
+mod restore;
+pub use restore::restore_transactions_from_snapshot;
+
+mod verify;
+pub use verify::verify_transactions_against_db;
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::segments::{Segment, Transactions};
+    use reth_db::{test_utils::TempDatabase, DatabaseEnv};
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_nippy_jar::{NippyJar, NippyJarCursor};
+    use reth_primitives::{
+        snapshot::{Compression, Filters, SegmentHeader},
+        BlockNumber, SnapshotSegment, B256,
+    };
+    use reth_provider::TransactionsProviderExt;
+    use reth_stages::test_utils::TestStageDB;
+    use std::{
+        ops::RangeInclusive,
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    /// Database type of [`TestStageDB`].
+    pub(crate) type TestDB = Arc<TempDatabase<DatabaseEnv>>;
+
+    /// Inserts random blocks with two transactions each for `block_range` into a new database,
+    /// and snapshots their transactions into a jar in `dir`.
+    ///
+    /// Returns the database and the path of the jar.
+    pub(crate) fn transactions_jar(
+        dir: &Path,
+        block_range: RangeInclusive<BlockNumber>,
+        compression: Compression,
+        filters: Filters,
+    ) -> (TestStageDB, PathBuf) {
+        transactions_jar_with(dir, block_range, Transactions::new(compression, filters))
+    }
+
+    /// Same as [`transactions_jar`], but snapshots the transactions with the given segment.
+    pub(crate) fn transactions_jar_with(
+        dir: &Path,
+        block_range: RangeInclusive<BlockNumber>,
+        segment: Transactions,
+    ) -> (TestStageDB, PathBuf) {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+        let blocks = random_block_range(&mut rng, block_range.clone(), B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+
+        let provider = db.factory.provider().expect("provider");
+        segment
+            .snapshot::<TestDB>(&provider, dir, block_range.clone())
+            .expect("snapshot transactions");
+
+        let tx_range = provider.transaction_range_by_block_range(block_range.clone()).unwrap();
+        let path = dir.join(SnapshotSegment::Transactions.filename(&block_range, &tx_range));
+        (db, path)
+    }
+
+    /// Flips the first byte of the transaction of `row` in the uncompressed jar at `path`.
+    ///
+    /// Uncompressed values are stored back to back, so the transaction of a row starts after the
+    /// transactions of the rows before it, and their `row_overhead` bytes of other columns, e.g.
+    /// 4 bytes of checksums.
+    pub(crate) fn corrupt_row(path: &Path, row: usize, row_overhead: usize) {
+        let jar = NippyJar::<SegmentHeader>::load(path).unwrap();
+        let offset = {
+            let mut cursor = NippyJarCursor::new(&jar).unwrap();
+            (0..row)
+                .map(|row| cursor.row_by_number(row).unwrap().unwrap()[0].len() + row_overhead)
+                .sum::<usize>()
+        };
+        let mut data = std::fs::read(jar.data_path()).unwrap();
+        data[offset] ^= 0xff;
+        std::fs::write(jar.data_path(), data).unwrap();
+    }
+}
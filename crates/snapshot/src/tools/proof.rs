@@ -0,0 +1,69 @@
+use crate::merkle::{leaf_hash, MerkleProof};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{NippyJar, NippyJarCursor};
+use reth_primitives::{snapshot::SegmentHeader, TxNumber};
+use std::path::Path;
+
+/// Returns a proof that the transaction `tx_id` is part of the transaction based jar at `path`,
+/// which can be verified against the rows root stored in the jar header.
+///
+/// Returns `None` if the transaction is not in the jar, or if the jar was generated without a rows
+/// root.
+pub fn prove_transaction(path: &Path, tx_id: TxNumber) -> ProviderResult<Option<MerkleProof>> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let header = jar.user_header();
+    if header.rows_root().is_none() || !header.tx_range().contains(&tx_id) {
+        return Ok(None)
+    }
+
+    let rows = header.tx_range().clone().count();
+    let mut cursor = NippyJarCursor::new(&jar)?;
+    let mut leaves = Vec::with_capacity(rows);
+    for row in 0..rows {
+        let tx = *header.tx_range().start() + row as u64;
+        let value = cursor
+            .row_by_number(row)?
+            .ok_or(ProviderError::MissingSnapshotTx(header.segment(), tx))?;
+        leaves.push(leaf_hash(value[0]));
+    }
+
+    Ok(MerkleProof::new(leaves, tx_id - header.tx_start()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{segments::Transactions, tools::test_utils::transactions_jar_with};
+    use reth_db::{tables, transaction::DbTx, RawKey, RawTable};
+    use reth_primitives::snapshot::{Compression, Filters};
+
+    #[test]
+    fn prove_and_verify_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = Transactions::new(Compression::Zstd, Filters::WithoutFilters).with_root(true);
+        let (db, path) = transactions_jar_with(dir.path(), 0..=3, segment);
+
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        let root = jar.user_header().rows_root().unwrap();
+
+        let provider = db.factory.provider().unwrap();
+        for tx_id in [0, 5, 7] {
+            let proof = prove_transaction(&path, tx_id).unwrap().unwrap();
+            let row = provider
+                .tx_ref()
+                .get::<RawTable<tables::Transactions>>(RawKey::new(tx_id))
+                .unwrap()
+                .unwrap();
+            assert!(proof.verify(row.raw_value(), root));
+        }
+
+        // out of range
+        assert_eq!(prove_transaction(&path, 8).unwrap(), None);
+
+        // a jar without a root can't prove anything
+        let dir = tempfile::tempdir().unwrap();
+        let segment = Transactions::new(Compression::Zstd, Filters::WithoutFilters);
+        let (_, path) = transactions_jar_with(dir.path(), 0..=3, segment);
+        assert_eq!(prove_transaction(&path, 0).unwrap(), None);
+    }
+}
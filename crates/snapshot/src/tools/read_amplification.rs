@@ -0,0 +1,117 @@
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{NippyJar, NippyJarCursor};
+use reth_primitives::{snapshot::SegmentHeader, TxNumber};
+use std::{collections::HashSet, path::Path, sync::Arc};
+
+/// Size of the pages in which the memory mapped data file is read from disk.
+const PAGE_SIZE: usize = 4096;
+
+/// Bytes read from disk compared to the bytes that were actually needed when looking up rows of a
+/// jar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadAmpReport {
+    /// Number of looked up rows.
+    pub lookups: usize,
+    /// Bytes of all disk pages touched by the lookups.
+    pub disk_bytes: usize,
+    /// Bytes of the compressed rows.
+    pub compressed_bytes: usize,
+    /// Bytes of the decoded rows.
+    pub useful_bytes: usize,
+}
+
+impl ReadAmpReport {
+    /// Returns how many bytes were read from disk per useful byte.
+    pub fn amplification(&self) -> f64 {
+        if self.useful_bytes == 0 {
+            return 0.0
+        }
+        self.disk_bytes as f64 / self.useful_bytes as f64
+    }
+}
+
+/// Measures the read amplification of looking up `sample_ids` in the transaction based jar at
+/// `path`.
+///
+/// Disk reads are accounted per page of the data file, so small rows spread across many pages, or
+/// rows that straddle page boundaries, inflate [`ReadAmpReport::disk_bytes`].
+pub fn measure_read_amplification(
+    path: &Path,
+    sample_ids: &[TxNumber],
+) -> ProviderResult<ReadAmpReport> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let reader = Arc::new(jar.open_data_reader()?);
+    let mut cursor = NippyJarCursor::with_reader(&jar, reader.clone())?;
+    let header = jar.user_header();
+    let tx_range = header.tx_range();
+
+    let mut pages = HashSet::new();
+    let mut report = ReadAmpReport::default();
+    for &id in sample_ids {
+        if !tx_range.contains(&id) {
+            return Err(ProviderError::MissingSnapshotTx(header.segment(), id))
+        }
+
+        let row = (id - tx_range.start()) as usize;
+        let start = reader.offset(row) as usize;
+        let end =
+            if id == *tx_range.end() { reader.size() } else { reader.offset(row + 1) as usize };
+        pages.extend(start / PAGE_SIZE..=end.saturating_sub(1).max(start) / PAGE_SIZE);
+
+        let value = cursor
+            .row_by_number(row)?
+            .ok_or(ProviderError::MissingSnapshotTx(header.segment(), id))?;
+
+        report.lookups += 1;
+        report.compressed_bytes += end - start;
+        report.useful_bytes += value[0].len();
+    }
+    report.disk_bytes = pages.len() * PAGE_SIZE;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::transactions_jar;
+    use reth_db::{tables, transaction::DbTx, RawKey, RawTable};
+    use reth_primitives::{
+        snapshot::{Compression, Filters},
+        SnapshotSegment,
+    };
+
+    #[test]
+    fn useful_bytes_match_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+
+        let sample_ids = [0, 3, 5, 7];
+        let report = measure_read_amplification(&path, &sample_ids).unwrap();
+
+        let provider = db.factory.provider().unwrap();
+        let expected = sample_ids
+            .iter()
+            .map(|id| {
+                provider
+                    .tx_ref()
+                    .get::<RawTable<tables::Transactions>>(RawKey::new(*id))
+                    .unwrap()
+                    .unwrap()
+                    .raw_value()
+                    .len()
+            })
+            .sum::<usize>();
+
+        assert_eq!(report.lookups, sample_ids.len());
+        assert_eq!(report.useful_bytes, expected);
+        assert!(report.disk_bytes >= report.compressed_bytes);
+        assert_eq!(report.disk_bytes % PAGE_SIZE, 0);
+
+        assert_eq!(
+            measure_read_amplification(&path, &[8]),
+            Err(ProviderError::MissingSnapshotTx(SnapshotSegment::Transactions, 8))
+        );
+    }
+}
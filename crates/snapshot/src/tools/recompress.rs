@@ -0,0 +1,129 @@
+use reth_interfaces::provider::ProviderResult;
+use reth_nippy_jar::{compression::Compressors, NippyJar, NippyJarCursor};
+use reth_primitives::snapshot::{Compression, SegmentHeader};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of rows used to train a zstd dictionary, the same as when a snapshot is
+/// generated from the database.
+const DICTIONARY_DATASET_ROWS: usize = 1000;
+
+/// Rewrites the jar at `path` into `directory` with a different `compression`, and returns the
+/// path of the new jar.
+///
+/// Rows are copied in order, so the segment header, filters and offsets index of the jar are
+/// preserved. `level` is the zstd compression level, where `0` uses zstd's default. It's ignored
+/// by other compressions.
+///
+/// The new jar has the same file name, so `directory` must not be the directory of `path`.
+pub fn recompress_snapshot(
+    path: &Path,
+    directory: &Path,
+    compression: Compression,
+    level: i32,
+) -> ProviderResult<PathBuf> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?.load_filters()?;
+
+    let mut columns = vec![Vec::with_capacity(jar.rows()); jar.columns()];
+    read_columns(&jar, &mut columns)?;
+
+    let header = jar.user_header();
+    let filename = header.segment().filename(header.block_range(), header.tx_range());
+    let total_rows = jar.rows() as u64;
+
+    let jar = jar.into_empty_at(&directory.join(filename));
+    let mut jar = with_compression(jar, compression, level, &columns)?;
+    jar.freeze(
+        columns.into_iter().map(|column| column.into_iter().map(Ok)).collect(),
+        total_rows,
+    )?;
+
+    Ok(jar.data_path().to_path_buf())
+}
+
+/// Appends the values of all rows of `jar` to `columns`.
+pub(super) fn read_columns(
+    jar: &NippyJar<SegmentHeader>,
+    columns: &mut [Vec<Vec<u8>>],
+) -> ProviderResult<()> {
+    let mut cursor = NippyJarCursor::new(jar)?;
+    while let Some(row) = cursor.next_row()? {
+        for (column, value) in columns.iter_mut().zip(row) {
+            column.push(value.to_vec());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the [`Compression`] of `jar`.
+pub(super) fn compression_of(jar: &NippyJar<SegmentHeader>) -> Compression {
+    match jar.compressor() {
+        None => Compression::Uncompressed,
+        Some(Compressors::Lz4(_)) => Compression::Lz4,
+        Some(Compressors::Zstd(zstd)) if zstd.use_dict => Compression::ZstdWithDictionary,
+        Some(Compressors::Zstd(_)) => Compression::Zstd,
+    }
+}
+
+/// Sets `compression` on the empty `jar` that is going to be frozen with `columns`.
+///
+/// For [`Compression::ZstdWithDictionary`], the dictionaries are trained with the most recent rows
+/// of `columns`.
+pub(super) fn with_compression(
+    jar: NippyJar<SegmentHeader>,
+    compression: Compression,
+    level: i32,
+    columns: &[Vec<Vec<u8>>],
+) -> ProviderResult<NippyJar<SegmentHeader>> {
+    Ok(match compression {
+        Compression::Lz4 => jar.with_lz4(),
+        Compression::Zstd => jar.with_zstd(false, 0).with_zstd_level(level),
+        Compression::ZstdWithDictionary => {
+            let dataset = columns
+                .iter()
+                .map(|column| {
+                    column[column.len().saturating_sub(DICTIONARY_DATASET_ROWS)..].to_vec()
+                })
+                .collect();
+
+            let mut jar = jar.with_zstd(true, 5_000_000).with_zstd_level(level);
+            jar.prepare_compression(dataset)?;
+            jar
+        }
+        Compression::Uncompressed => jar,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::transactions_jar;
+    use reth_primitives::snapshot::{Filters, InclusionFilter, PerfectHashingFunction};
+
+    #[test]
+    fn recompress_preserves_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let filters = Filters::WithFilters(InclusionFilter::Cuckoo, PerfectHashingFunction::Fmph);
+        let (_db, path) = transactions_jar(dir.path(), 0..=3, Compression::Lz4, filters);
+
+        let out = tempfile::tempdir().unwrap();
+        let new_path = recompress_snapshot(&path, out.path(), Compression::Zstd, 19).unwrap();
+        assert_eq!(new_path.file_name(), path.file_name());
+
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap().load_filters().unwrap();
+        let new_jar = NippyJar::<SegmentHeader>::load(&new_path).unwrap().load_filters().unwrap();
+        assert_eq!(new_jar.user_header(), jar.user_header());
+        assert_eq!(new_jar.rows(), 8);
+        // filters and offsets index are copied as they are
+        assert_eq!(
+            std::fs::read(new_jar.index_path()).unwrap(),
+            std::fs::read(jar.index_path()).unwrap()
+        );
+
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        let mut new_cursor = NippyJarCursor::new(&new_jar).unwrap();
+        for row in 0..8 {
+            let expected = cursor.row_by_number(row).unwrap().unwrap()[0].to_vec();
+            assert_eq!(new_cursor.row_by_number(row).unwrap().unwrap()[0], expected.as_slice());
+        }
+    }
+}
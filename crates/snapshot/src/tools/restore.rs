@@ -0,0 +1,99 @@
+use reth_db::{
+    database::Database,
+    table::Decompress,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{NippyJar, NippyJarCursor};
+use reth_primitives::{snapshot::SegmentHeader, SnapshotSegment, TransactionSignedNoHash};
+use reth_provider::DatabaseProviderRW;
+use std::path::Path;
+
+/// Writes the transactions of the jar at `path` back into the database, e.g. to recover from a
+/// lost or corrupted database.
+///
+/// Every transaction is written to [`tables::Transactions`] and its hash to
+/// [`tables::TxHashNumber`]. Transactions that are already present in the database are skipped.
+/// The block body indices aren't part of the jar, so they have to be restored separately.
+///
+/// Returns the number of restored transactions. The changes only persist once the provider is
+/// committed.
+pub fn restore_transactions_from_snapshot<DB: Database>(
+    provider: &DatabaseProviderRW<DB>,
+    path: &Path,
+) -> ProviderResult<usize> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let header = jar.user_header();
+    if header.segment() != SnapshotSegment::Transactions {
+        return Err(ProviderError::SnapshotRestore(header.segment(), "not a transactions jar"))
+    }
+
+    let tx = provider.tx_ref();
+    let mut cursor = NippyJarCursor::new(&jar)?;
+    let mut restored = 0;
+    for (row, tx_number) in header.tx_range().clone().enumerate() {
+        if tx.get::<tables::Transactions>(tx_number)?.is_some() {
+            continue
+        }
+
+        let value = cursor
+            .row_by_number(row)?
+            .ok_or(ProviderError::MissingSnapshotTx(header.segment(), tx_number))?;
+        let transaction = TransactionSignedNoHash::decompress(value[0])?;
+        tx.put::<tables::TxHashNumber>(transaction.hash(), tx_number)?;
+        tx.put::<tables::Transactions>(tx_number, transaction)?;
+        restored += 1;
+    }
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::{transactions_jar, TestDB};
+    use reth_db::{cursor::DbCursorRO, RawTable};
+    use reth_primitives::snapshot::{Compression, Filters};
+    use reth_stages::test_utils::TestStageDB;
+
+    #[test]
+    fn restore_into_empty_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Zstd, Filters::WithoutFilters);
+
+        let restored_db = TestStageDB::default();
+        let provider = restored_db.factory.provider_rw().unwrap();
+        assert_eq!(restore_transactions_from_snapshot::<TestDB>(&provider, &path).unwrap(), 8);
+        // everything is present already
+        assert_eq!(restore_transactions_from_snapshot::<TestDB>(&provider, &path).unwrap(), 0);
+        provider.commit().unwrap();
+
+        let rows = |db: &TestStageDB| {
+            let provider = db.factory.provider().unwrap();
+            let mut cursor =
+                provider.tx_ref().cursor_read::<RawTable<tables::Transactions>>().unwrap();
+            cursor
+                .walk(None)
+                .unwrap()
+                .map(|row| {
+                    let (key, value) = row.unwrap();
+                    (key.raw_key().clone(), value.raw_value().to_vec())
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(&restored_db), rows(&db));
+
+        let jar = NippyJar::<SegmentHeader>::load(&path).unwrap();
+        let mut cursor = NippyJarCursor::new(&jar).unwrap();
+        let provider = restored_db.factory.provider().unwrap();
+        for tx_number in 0..8 {
+            let row = cursor.row_by_number(tx_number as usize).unwrap().unwrap()[0].to_vec();
+            let transaction = TransactionSignedNoHash::decompress(row.as_slice()).unwrap();
+            let restored =
+                provider.tx_ref().get::<tables::TxHashNumber>(transaction.hash()).unwrap();
+            assert_eq!(restored, Some(tx_number));
+        }
+    }
+}
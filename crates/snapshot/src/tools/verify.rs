@@ -0,0 +1,121 @@
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx, RawKey, RawTable};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::{NippyJar, NippyJarCursor};
+use reth_primitives::{snapshot::SegmentHeader, SnapshotSegment, TxNumber};
+use reth_provider::ProviderFactory;
+use std::{ops::Range, path::Path, thread};
+
+/// Verifies that every transaction in the jar at `path` matches the transaction in the database,
+/// e.g. right after generating the jar.
+///
+/// The rows are split into up to `threads` consecutive ranges that are verified in parallel, each
+/// with its own read-only provider and jar cursor.
+///
+/// Returns [`ProviderError::SnapshotRowMismatch`] with the lowest mismatching transaction number.
+/// Transactions missing from the database count as mismatches.
+pub fn verify_transactions_against_db<DB: Database>(
+    factory: &ProviderFactory<DB>,
+    path: &Path,
+    threads: usize,
+) -> ProviderResult<()> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let segment = jar.user_header().segment();
+    if segment != SnapshotSegment::Transactions {
+        return Err(ProviderError::SnapshotVerify(segment, "not a transactions jar"))
+    }
+
+    let rows = jar.rows();
+    let chunk_size = rows.div_ceil(threads.max(1)).max(1);
+    let mismatches = thread::scope(|scope| {
+        let verifiers = (0..rows)
+            .step_by(chunk_size)
+            .map(|start| {
+                let rows = start..rows.min(start + chunk_size);
+                scope.spawn(move || first_mismatch(factory, path, rows))
+            })
+            .collect::<Vec<_>>();
+        verifiers
+            .into_iter()
+            .map(|verifier| verifier.join().expect("verifier thread panicked"))
+            .collect::<ProviderResult<Vec<_>>>()
+    })?;
+
+    match mismatches.into_iter().flatten().min() {
+        Some(tx_number) => Err(ProviderError::SnapshotRowMismatch(segment, tx_number)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the number of the first transaction in the given rows of the jar at `path` that
+/// doesn't match the database.
+fn first_mismatch<DB: Database>(
+    factory: &ProviderFactory<DB>,
+    path: &Path,
+    rows: Range<usize>,
+) -> ProviderResult<Option<TxNumber>> {
+    let jar = NippyJar::<SegmentHeader>::load(path)?;
+    let header = jar.user_header();
+    let mut cursor = NippyJarCursor::new(&jar)?;
+    let provider = factory.provider()?;
+    let mut db_cursor = provider.tx_ref().cursor_read::<RawTable<tables::Transactions>>()?;
+
+    for row in rows {
+        let tx_number = header.tx_start() + row as u64;
+        let value = cursor
+            .row_by_number(row)?
+            .ok_or(ProviderError::MissingSnapshotTx(header.segment(), tx_number))?;
+        let db_value = db_cursor.seek_exact(RawKey::new(tx_number))?;
+        if db_value.as_ref().map(|(_, db_value)| db_value.raw_value()) != Some(value[0]) {
+            return Ok(Some(tx_number))
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::{corrupt_row, transactions_jar};
+    use reth_db::transaction::DbTxMut;
+    use reth_primitives::snapshot::{Compression, Filters};
+
+    #[test]
+    fn detects_mismatching_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Lz4, Filters::WithoutFilters);
+
+        for threads in [1, 3, 8, 16] {
+            verify_transactions_against_db(&db.factory, &path, threads).unwrap();
+        }
+
+        // overwrite two transactions with another one
+        let provider = db.factory.provider_rw().unwrap();
+        let tx = provider.tx_ref().get::<tables::Transactions>(0).unwrap().unwrap();
+        provider.tx_ref().put::<tables::Transactions>(6, tx.clone()).unwrap();
+        provider.tx_ref().put::<tables::Transactions>(2, tx).unwrap();
+        provider.commit().unwrap();
+
+        for threads in [1, 3, 8, 16] {
+            assert!(matches!(
+                verify_transactions_against_db(&db.factory, &path, threads),
+                Err(ProviderError::SnapshotRowMismatch(SnapshotSegment::Transactions, 2))
+            ));
+        }
+    }
+
+    #[test]
+    fn detects_corrupted_jar() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, path) =
+            transactions_jar(dir.path(), 0..=3, Compression::Uncompressed, Filters::WithoutFilters);
+        corrupt_row(&path, 5, 0);
+
+        for threads in [1, 3, 8, 16] {
+            assert!(matches!(
+                verify_transactions_against_db(&db.factory, &path, threads),
+                Err(ProviderError::SnapshotRowMismatch(SnapshotSegment::Transactions, 5))
+            ));
+        }
+    }
+}
@@ -117,6 +117,10 @@ pub struct NippyJar<H = ()> {
     /// Data path for file. Supporting files will have a format `{path}.{extension}`.
     #[serde(skip)]
     path: PathBuf,
+    /// Upper bound in bytes on the data held in memory by the writer while freezing, see
+    /// [`Self::with_max_memory`].
+    #[serde(skip)]
+    max_memory: Option<usize>,
 }
 
 impl<H: NippyJarHeader> std::fmt::Debug for NippyJar<H> {
@@ -147,11 +151,6 @@ impl NippyJar<()> {
     pub fn load_without_header(path: &Path) -> Result<Self, NippyJarError> {
         NippyJar::<()>::load(path)
     }
-
-    /// Whether this [`NippyJar`] uses a [`InclusionFilters`] and [`Functions`].
-    pub fn uses_filters(&self) -> bool {
-        self.filter.is_some() && self.phf.is_some()
-    }
 }
 
 impl<H: NippyJarHeader> NippyJar<H> {
@@ -168,6 +167,7 @@ impl<H: NippyJarHeader> NippyJar<H> {
             phf: None,
             offsets_index: PrefixSummedEliasFano::default(),
             path: path.to_path_buf(),
+            max_memory: None,
         }
     }
 
@@ -178,12 +178,30 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self
     }
 
+    /// Sets the compression level of [`compression::Zstd`] compression, see
+    /// [`compression::Zstd::with_level`]. Has no effect on other compressors.
+    pub fn with_zstd_level(mut self, level: i32) -> Self {
+        if let Some(Compressors::Zstd(zstd)) = &mut self.compressor {
+            zstd.level = level;
+        }
+        self
+    }
+
     /// Adds [`compression::Lz4`] compression.
     pub fn with_lz4(mut self) -> Self {
         self.compressor = Some(Compressors::Lz4(compression::Lz4::default()));
         self
     }
 
+    /// Bounds the memory used by the writer while freezing the jar to roughly `max_memory` bytes.
+    ///
+    /// Offsets are flushed to disk whenever the next row wouldn't fit into the bound anymore,
+    /// trading speed for a lower memory footprint. This is a hint: rows are always written whole.
+    pub fn with_max_memory(mut self, max_memory: usize) -> Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+
     /// Adds [`filter::Cuckoo`] filter.
     pub fn with_cuckoo_filter(mut self, max_capacity: usize) -> Self {
         self.filter = Some(InclusionFilters::Cuckoo(Cuckoo::new(max_capacity)));
@@ -202,11 +220,64 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self
     }
 
+    /// Returns an empty, uncompressed jar at `path` with the same columns, user header, filter,
+    /// perfect hashing function and offsets index as this one.
+    ///
+    /// The filter, perfect hashing function and offsets index only depend on the order of the
+    /// rows, so they remain valid as long as the same rows are written in the same order, eg. when
+    /// rewriting a jar with a different compression. They are only kept if they were loaded with
+    /// [`Self::load_filters`].
+    pub fn into_empty_at(mut self, path: &Path) -> Self {
+        self.rows = 0;
+        self.max_row_size = 0;
+        self.compressor = None;
+        self.path = path.to_path_buf();
+        self
+    }
+
+    /// Replaces the filter and perfect hashing function with empty ones of the same kind, with
+    /// the filter sized for `max_capacity` elements, and clears the offsets index.
+    ///
+    /// Used when the rows of the jar change, since they have to be populated again with
+    /// [`Self::prepare_index`] before freezing the jar.
+    pub fn reset_filters(mut self, max_capacity: usize) -> Self {
+        self.filter = self.filter.map(|filter| match filter {
+            InclusionFilters::Cuckoo(_) => InclusionFilters::Cuckoo(Cuckoo::new(max_capacity)),
+            InclusionFilters::Unused => InclusionFilters::Unused,
+        });
+        self.phf = self.phf.map(|phf| match phf {
+            Functions::Fmph(_) => Functions::Fmph(Fmph::new()),
+            Functions::GoFmph(_) => Functions::GoFmph(GoFmph::new()),
+        });
+        self.offsets_index = PrefixSummedEliasFano::default();
+        self
+    }
+
+    /// Whether this [`NippyJar`] uses a [`InclusionFilters`] and [`Functions`].
+    pub fn uses_filters(&self) -> bool {
+        self.filter.is_some() && self.phf.is_some()
+    }
+
+    /// Returns the number of columns.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
     /// Gets a reference to the user header.
     pub fn user_header(&self) -> &H {
         &self.user_header
     }
 
+    /// Gets a mutable reference to the user header.
+    pub fn user_header_mut(&mut self) -> &mut H {
+        &mut self.user_header
+    }
+
     /// Returns the size of inclusion filter
     pub fn filter_size(&self) -> usize {
         self.size()
@@ -1022,6 +1093,50 @@ mod tests {
         test_append_consistency_partial_commit(file_path.path(), &col1, &col2);
     }
 
+    #[test]
+    fn test_max_memory() {
+        let (col1, col2) = test_data(Some(1));
+        let num_rows = col1.len() as u64;
+        let num_columns = 2;
+        let max_memory = 64;
+
+        let unbounded_path = tempfile::NamedTempFile::new().unwrap();
+        let mut unbounded = NippyJar::new_without_header(num_columns, unbounded_path.path());
+        unbounded
+            .freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows)
+            .unwrap();
+
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+        let mut nippy = NippyJar::new_without_header(num_columns, file_path.path())
+            .with_max_memory(max_memory);
+        let mut writer = NippyJarWriter::new(&mut nippy).unwrap();
+        for (value1, value2) in col1.iter().zip(&col2) {
+            writer.append_rows(vec![vec![Ok(value1)], vec![Ok(value2)]], 1).unwrap();
+            assert!(writer.offsets().len() * std::mem::size_of::<u64>() <= max_memory);
+        }
+        writer.commit().unwrap();
+
+        // Flushing early doesn't change the resulting files
+        assert_eq!(
+            std::fs::read(nippy.offsets_path()).unwrap(),
+            std::fs::read(unbounded.offsets_path()).unwrap()
+        );
+        assert_eq!(
+            std::fs::read(nippy.data_path()).unwrap(),
+            std::fs::read(unbounded.data_path()).unwrap()
+        );
+
+        let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert_eq!(loaded_nippy.rows, col1.len());
+        let mut cursor = NippyJarCursor::new(&loaded_nippy).unwrap();
+        let mut row_index = 0usize;
+        while let Some(row) = cursor.next_row().unwrap() {
+            assert_eq!((row[0], row[1]), (col1[row_index].as_slice(), col2[row_index].as_slice()));
+            row_index += 1;
+        }
+        assert_eq!(row_index, col1.len());
+    }
+
     #[test]
     fn test_pruner() {
         let (col1, col2) = test_data(None);
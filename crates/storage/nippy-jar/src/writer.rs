@@ -52,13 +52,14 @@ impl<'a, H: NippyJarHeader> NippyJarWriter<'a, H> {
         let (data_file, offsets_file, is_created) =
             Self::create_or_open_files(jar.data_path(), &jar.offsets_path())?;
 
+        let capacity = jar.max_memory.map_or(1_000_000, |max_memory| max_memory.min(1_000_000));
         let mut writer = Self {
             jar,
             data_file,
             offsets_file,
-            tmp_buf: Vec::with_capacity(1_000_000),
+            tmp_buf: Vec::with_capacity(capacity),
             uncompressed_row_size: 0,
-            offsets: Vec::with_capacity(1_000_000),
+            offsets: Vec::with_capacity(capacity / OFFSET_SIZE_BYTES as usize),
             column: 0,
         };
 
@@ -211,11 +212,23 @@ impl<'a, H: NippyJarHeader> NippyJarWriter<'a, H> {
             }
 
             column_iterators = iterators.into_iter();
+
+            if self.offsets_exceed_max_memory() {
+                self.commit_offsets()?;
+            }
         }
 
         Ok(())
     }
 
+    /// Returns `true` if the offsets of the next row wouldn't fit into
+    /// [`NippyJar::with_max_memory`] anymore.
+    fn offsets_exceed_max_memory(&self) -> bool {
+        self.jar.max_memory.is_some_and(|max_memory| {
+            (self.offsets.len() + self.jar.columns) * OFFSET_SIZE_BYTES as usize > max_memory
+        })
+    }
+
     /// Appends a column to data file. `fn commit()` should be called to flush offsets and config to
     /// disk.
     pub fn append_column(
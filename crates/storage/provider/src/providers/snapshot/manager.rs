@@ -9,7 +9,10 @@ use parking_lot::RwLock;
 use reth_db::{
     codecs::CompactU256,
     models::StoredBlockBodyIndices,
-    snapshot::{iter_snapshots, HeaderMask, ReceiptMask, SnapshotCursor, TransactionMask},
+    snapshot::{
+        iter_snapshots, ColumnSelectorOne, HeaderMask, ReceiptMask, SnapshotCursor,
+        TransactionMask,
+    },
 };
 use reth_interfaces::provider::{ProviderError, ProviderResult};
 use reth_nippy_jar::NippyJar;
@@ -310,6 +313,38 @@ impl SnapshotProvider {
         Ok(None)
     }
 
+    /// Returns an iterator over the decoded rows of the `segment` jar at `path`, in block or
+    /// transaction number order. Each row is only read and decoded when the iterator reaches it.
+    ///
+    /// The mask `M` selects the decoded column, eg.
+    /// [`TransactionMask<TransactionSignedNoHash>`](TransactionMask) for the transactions segment.
+    pub fn iter_segment<'a, M: ColumnSelectorOne + 'a>(
+        &'a self,
+        segment: SnapshotSegment,
+        path: &Path,
+    ) -> ProviderResult<impl Iterator<Item = ProviderResult<M::FIRST>> + 'a> {
+        let provider = self
+            .get_segment_provider(segment, || None, Some(path))?
+            .ok_or_else(|| ProviderError::MissingSnapshotPath(segment, path.to_path_buf()))?;
+        let start = provider.user_header().start();
+        let end = start + provider.rows() as u64;
+
+        Ok((start..end).map(move |number| {
+            // The cursor borrows the jar provider, so it can't be stored next to it in the
+            // iterator. Creating one is cheap, since the mmap handle is shared.
+            SnapshotCursor::new(provider.value(), provider.mmap_handle())?
+                .get_one::<M>(number.into())?
+                .ok_or_else(|| match segment {
+                    SnapshotSegment::Headers => {
+                        ProviderError::MissingSnapshotBlock(segment, number)
+                    }
+                    SnapshotSegment::Transactions | SnapshotSegment::Receipts => {
+                        ProviderError::MissingSnapshotTx(segment, number)
+                    }
+                })
+        }))
+    }
+
     /// Fetches data within a specified range across multiple snapshot files.
     ///
     /// This function iteratively retrieves data using `get_fn` for each item in the given range.
@@ -45,13 +45,13 @@ mod tests {
     use rand::{self, seq::SliceRandom};
     use reth_db::{
         cursor::DbCursorRO,
-        snapshot::create_snapshot_T1_T2_T3,
+        snapshot::{create_snapshot_T1, create_snapshot_T1_T2_T3, TransactionMask},
         transaction::{DbTx, DbTxMut},
-        CanonicalHeaders, HeaderNumbers, HeaderTD, Headers, RawTable,
+        CanonicalHeaders, HeaderNumbers, HeaderTD, Headers, RawTable, Transactions,
     };
-    use reth_interfaces::test_utils::generators::{self, random_header_range};
-    use reth_nippy_jar::NippyJar;
-    use reth_primitives::{BlockNumber, B256, U256};
+    use reth_interfaces::test_utils::generators::{self, random_header_range, random_signed_tx};
+    use reth_nippy_jar::{ColumnResult, NippyJar};
+    use reth_primitives::{BlockNumber, TransactionSignedNoHash, TxNumber, B256, U256};
 
     #[test]
     fn test_snap() {
@@ -157,4 +157,54 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn iter_transactions_segment() {
+        let range = 0..=9u64;
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let snap_file =
+            snap_path.path().join(SnapshotSegment::Transactions.filename(&range, &range));
+
+        let mut rng = generators::rng();
+        let mut provider_rw = factory.provider_rw().unwrap();
+        for tx_id in range.clone() {
+            let transaction: TransactionSignedNoHash = random_signed_tx(&mut rng).into();
+            provider_rw.tx_mut().put::<Transactions>(tx_id, transaction).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let mut nippy_jar = NippyJar::new(
+            1,
+            snap_file.as_path(),
+            SegmentHeader::new(range.clone(), range.clone(), SnapshotSegment::Transactions),
+        )
+        .with_lz4();
+        create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+            provider.tx_ref(),
+            range.clone(),
+            None,
+            None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+            None::<std::vec::IntoIter<ColumnResult<B256>>>,
+            10,
+            &mut nippy_jar,
+        )
+        .unwrap();
+
+        let manager = SnapshotProvider::new(snap_path.path()).unwrap();
+        let rows = manager
+            .iter_segment::<TransactionMask<TransactionSignedNoHash>>(
+                SnapshotSegment::Transactions,
+                &snap_file,
+            )
+            .unwrap()
+            .collect::<ProviderResult<Vec<_>>>()
+            .unwrap();
+
+        let mut cursor = provider.tx_ref().cursor_read::<Transactions>().unwrap();
+        let expected = cursor.walk(None).unwrap().map(|row| row.unwrap().1).collect::<Vec<_>>();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(rows, expected);
+    }
 }
@@ -1,6 +1,7 @@
 use futures_util::Future;
 use reth::{
-    providers::StateProviderFactory, tasks::TaskSpawner, transaction_pool::TransactionPool,
+    primitives::B256, providers::StateProviderFactory, tasks::TaskSpawner,
+    transaction_pool::TransactionPool,
 };
 use reth_basic_payload_builder::{PayloadBuilder, PayloadConfig};
 use reth_payload_builder::{error::PayloadBuilderError, KeepPayloadJobAlive, PayloadJob};
@@ -52,6 +53,10 @@ where
         Ok(self.config.attributes.clone())
     }
 
+    fn parent_hash(&self) -> B256 {
+        self.config.parent_block.hash()
+    }
+
     fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
         let payload = self.best_payload();
         (futures_util::future::ready(payload), KeepPayloadJobAlive::No)